@@ -0,0 +1,264 @@
+//! Backup store for jars the tool overwrites or removes.
+//!
+//! Every operation gets its own timestamped folder under
+//! `.mod-replacer/backups/` next to the mods folder, holding copies of the
+//! original files and a `manifest.json` describing where they came from.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::fsio;
+use crate::hashes::sha256_hex;
+use crate::mods::file_name;
+
+const MANIFEST: &str = "manifest.json";
+
+/// Folder holding the tool's own state for the instance that owns `mods_dir`.
+///
+/// It lives next to the mods folder rather than inside it, so mod loaders
+/// never pick up backed-up jars.
+pub fn state_dir(mods_dir: &Path) -> PathBuf {
+    let mods_dir = fs::canonicalize(mods_dir).unwrap_or_else(|_| mods_dir.to_path_buf());
+    mods_dir.parent().unwrap_or(&mods_dir).join(".mod-replacer")
+}
+
+#[derive(Debug, Clone)]
+pub struct BackupStore {
+    root: PathBuf,
+}
+
+impl BackupStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn for_mods_dir(mods_dir: &Path) -> Self {
+        Self::new(state_dir(mods_dir).join("backups"))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Starts a new, still empty, backup set. Its folder is created right
+    /// away so that runs starting within the same second never share an id;
+    /// it is removed again if the set is dropped without any files.
+    pub fn begin(&self, operation: &str) -> Result<BackupSet> {
+        fs::create_dir_all(&self.root)?;
+        let stamp = timestamp();
+        let mut id = stamp.clone();
+        let mut n = 1;
+        loop {
+            match fs::create_dir(self.root.join(&id)) {
+                Ok(()) => break,
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    id = format!("{stamp}-{n}");
+                    n += 1;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(BackupSet {
+            dir: self.root.join(&id),
+            id,
+            manifest: BackupManifest {
+                operation: operation.to_string(),
+                created: unix_now(),
+                entries: Vec::new(),
+            },
+        })
+    }
+
+    /// All backup sets, newest first.
+    pub fn list(&self) -> Result<Vec<BackupSet>> {
+        if !self.root.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut sets = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let dir = entry?.path();
+            if dir.join(MANIFEST).is_file() {
+                sets.push(BackupSet::load(dir)?);
+            }
+        }
+        sets.sort_by(|a, b| id_order(&b.id).cmp(&id_order(&a.id)));
+        Ok(sets)
+    }
+
+    pub fn get(&self, id: &str) -> Result<BackupSet> {
+        let dir = self.root.join(id);
+        if !dir.join(MANIFEST).is_file() {
+            return Err(Error::BackupNotFound(id.to_string()));
+        }
+        BackupSet::load(dir)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub operation: String,
+    /// Seconds since the Unix epoch.
+    pub created: u64,
+    pub entries: Vec<BackupEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupEntry {
+    /// Where the file lived when it was backed up.
+    pub original_path: PathBuf,
+    /// Name of the copy inside the backup set folder.
+    pub stored_as: String,
+    pub size: u64,
+    pub sha256: String,
+    /// The jar that was written over the original, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_with: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BackupSet {
+    pub id: String,
+    pub dir: PathBuf,
+    pub manifest: BackupManifest,
+}
+
+impl BackupSet {
+    fn load(dir: PathBuf) -> Result<Self> {
+        let manifest = serde_json::from_slice(&fs::read(dir.join(MANIFEST))?)
+            .map_err(|err| Error::InvalidBackup(dir.clone(), err.to_string()))?;
+        Ok(Self {
+            id: file_name(&dir),
+            dir,
+            manifest,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.manifest.entries.is_empty()
+    }
+
+    /// Copies `original` into the set and records it in the manifest.
+    pub fn add(&mut self, original: &Path, replaced_with: Option<&Path>) -> Result<&BackupEntry> {
+        fs::create_dir_all(&self.dir)?;
+
+        let name = file_name(original);
+        let mut stored_as = name.clone();
+        let mut n = 1;
+        while self.dir.join(&stored_as).exists() || stored_as == MANIFEST {
+            stored_as = format!("{n}-{name}");
+            n += 1;
+        }
+
+        let data = fs::read(original)?;
+        // The caller overwrites the original next, so the copy must be on disk
+        fsio::write_new(&self.dir.join(&stored_as), &data)?;
+
+        self.manifest.entries.push(BackupEntry {
+            original_path: absolute(original),
+            stored_as,
+            size: data.len() as u64,
+            sha256: sha256_hex(&data),
+            replaced_with: replaced_with.map(absolute),
+        });
+        self.save()?;
+        Ok(self.manifest.entries.last().unwrap())
+    }
+
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_vec_pretty(&self.manifest)
+            .map_err(|err| Error::InvalidBackup(self.dir.clone(), err.to_string()))?;
+        fsio::write_atomic(&self.dir.join(MANIFEST), &json)
+    }
+
+    /// Reads a stored copy back, checking it against the recorded hash.
+    pub fn read(&self, entry: &BackupEntry) -> Result<Vec<u8>> {
+        let data = fs::read(self.dir.join(&entry.stored_as))?;
+        if data.len() as u64 != entry.size || sha256_hex(&data) != entry.sha256 {
+            return Err(Error::InvalidBackup(
+                self.dir.join(&entry.stored_as),
+                "stored copy does not match its recorded hash".to_string(),
+            ));
+        }
+        Ok(data)
+    }
+
+    /// Puts `entry` back at its original path. Whatever is there now is
+    /// first saved into `displaced`.
+    pub fn restore(&self, entry: &BackupEntry, displaced: &mut BackupSet) -> Result<()> {
+        let data = self.read(entry)?;
+        if entry.original_path.is_file() {
+            displaced.add(&entry.original_path, None)?;
+        }
+        fsio::write_atomic(&entry.original_path, &data)
+    }
+
+    /// Writes every file in the set back to where it came from, newest
+    /// first, without backing up what it overwrites. Used to undo an
+    /// operation that failed half way.
+    pub fn rollback(&self) -> Result<()> {
+        for entry in self.manifest.entries.iter().rev() {
+            fsio::write_atomic(&entry.original_path, &self.read(entry)?)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for BackupSet {
+    fn drop(&mut self) {
+        if self.is_empty() {
+            // Only succeeds while the folder is still empty
+            let _ = fs::remove_dir(&self.dir);
+        }
+    }
+}
+
+/// Sort key of a backup id: the timestamp, then the counter `begin` adds
+/// when several sets start within the same second.
+fn id_order(id: &str) -> (&str, u32) {
+    match id.rsplit_once('-') {
+        Some((stamp, n)) if stamp.contains('-') => match n.parse() {
+            Ok(n) => (stamp, n),
+            Err(_) => (id, 0),
+        },
+        _ => (id, 0),
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// `YYYYMMDD-HHMMSS` in UTC, which also sorts chronologically.
+fn timestamp() -> String {
+    let secs = unix_now();
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days, see https://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
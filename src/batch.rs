@@ -0,0 +1,157 @@
+//! Several replacements described in a TOML plan file, applied as one
+//! operation.
+//!
+//! ```toml
+//! mods_dir = "mods"          # optional, relative to the plan file
+//!
+//! [[replace]]
+//! target = "sodium"          # file name or mod id
+//! with = "updates/sodium-0.5.8.jar"
+//!
+//! [[replace]]
+//! target = "lithium-0.11.2.jar"
+//! with = "updates/lithium-0.11.3.jar"
+//! force = true               # accept a mismatch for this pair only
+//! ```
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::backup::{BackupSet, BackupStore};
+use crate::deps::{self, ModSet, Problem};
+use crate::error::{Error, Result};
+use crate::mods::{self, file_name};
+use crate::replace::{ReplaceOptions, ReplacePlan};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanFile {
+    pub mods_dir: Option<PathBuf>,
+    #[serde(default, rename = "replace")]
+    pub replacements: Vec<PlanEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanEntry {
+    /// File name or mod id of the jar to replace.
+    pub target: String,
+    #[serde(rename = "with")]
+    pub replacement: PathBuf,
+    #[serde(default)]
+    pub force: bool,
+}
+
+impl PlanFile {
+    /// Loads a plan, resolving relative paths against the plan's folder.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let mut plan: PlanFile = toml::from_str(&text)
+            .map_err(|err| Error::InvalidPlan(path.to_path_buf(), err.to_string()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        if let Some(dir) = &mut plan.mods_dir {
+            *dir = base.join(&*dir);
+        }
+        for entry in &mut plan.replacements {
+            entry.replacement = base.join(&entry.replacement);
+        }
+        Ok(plan)
+    }
+
+    /// Resolves every target and prepares every replacement. Nothing is
+    /// written, so any error here leaves the mods folder as it was.
+    pub fn prepare(&self, mods_dir: &Path, options: &ReplaceOptions) -> Result<BatchPlan> {
+        let before = ModSet::scan(mods_dir)?;
+        let mut seen = HashSet::new();
+        let mut plans = Vec::with_capacity(self.replacements.len());
+        for entry in &self.replacements {
+            let target = mods::resolve_target_or_id(mods_dir, &entry.target)?;
+            if !seen.insert(target.clone()) {
+                return Err(Error::InvalidPlan(
+                    target,
+                    "the same jar is targeted more than once".to_string(),
+                ));
+            }
+            let options = ReplaceOptions {
+                force: options.force || entry.force,
+                backup: true,
+                ..options.clone()
+            };
+            plans.push(ReplacePlan::with_mod_set(
+                &target,
+                &entry.replacement,
+                &options,
+                &before,
+            )?);
+        }
+
+        // Judge dependencies on the folder after every replacement, since a
+        // library and its dependents are often updated together
+        let mut after = before.clone();
+        for plan in &plans {
+            if let Some(jar) = &plan.replacement_metadata {
+                after.swap(&plan.target, Some(jar));
+            }
+        }
+        let mut problems = deps::introduced(&before, &after);
+        for plan in &mut plans {
+            let (mine, rest) = problems
+                .into_iter()
+                .partition(|p| p.involves(&plan.target, &before));
+            plan.dependency_problems = mine;
+            problems = rest;
+        }
+        Ok(BatchPlan {
+            plans,
+            problems,
+            force: options.force,
+        })
+    }
+}
+
+/// Prepared replacements, ready to apply together.
+#[derive(Debug, Clone)]
+pub struct BatchPlan {
+    pub plans: Vec<ReplacePlan>,
+    /// Dependency problems the batch introduces that no single replacement
+    /// is behind, such as those of a folder that changes loader family.
+    pub problems: Vec<Problem>,
+    pub force: bool,
+}
+
+impl BatchPlan {
+    /// Problems of the whole batch that stop it unless forced.
+    pub fn dependency_errors(&self) -> Vec<&Problem> {
+        self.problems.iter().filter(|p| p.is_error()).collect()
+    }
+
+    /// Whether `apply` would refuse to go on because of the whole batch.
+    /// Each replacement can still be blocked on its own.
+    pub fn is_blocked(&self) -> bool {
+        !self.force && !self.dependency_errors().is_empty()
+    }
+}
+
+/// Runs every plan, backing all originals up into a single set. If one
+/// fails, every jar already replaced is put back.
+pub fn apply(batch: &BatchPlan, store: &BackupStore) -> Result<BackupSet> {
+    if batch.is_blocked() {
+        return Err(Error::Dependencies(
+            batch.dependency_errors().into_iter().cloned().collect(),
+        ));
+    }
+    let mut backup = store.begin("apply")?;
+    for plan in &batch.plans {
+        if let Err(err) = plan.execute_into(&mut backup) {
+            backup.rollback()?;
+            return Err(Error::RolledBack {
+                target: file_name(&plan.target),
+                source: Box::new(err),
+            });
+        }
+    }
+    Ok(backup)
+}
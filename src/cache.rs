@@ -0,0 +1,100 @@
+//! A local folder of jars and other files, looked up by content hash.
+//!
+//! Pack formats name their files by hash; this lets them be installed from
+//! whatever has already been downloaded, without network access.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::hashes::HashAlgo;
+
+#[derive(Debug, Clone)]
+pub struct CachedFile {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileCache {
+    root: PathBuf,
+    files: Vec<CachedFile>,
+    index: HashMap<(HashAlgo, String), usize>,
+}
+
+impl FileCache {
+    /// Scans `root` recursively and hashes every file with each of `algos`.
+    pub fn open(root: &Path, algos: &[HashAlgo]) -> Result<Self> {
+        if !root.is_dir() {
+            return Err(Error::NotADirectory(root.to_path_buf()));
+        }
+        let mut cache = Self {
+            root: root.to_path_buf(),
+            files: Vec::new(),
+            index: HashMap::new(),
+        };
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if path.is_file() {
+                    cache.add(path, algos)?;
+                }
+            }
+        }
+        Ok(cache)
+    }
+
+    fn add(&mut self, path: PathBuf, algos: &[HashAlgo]) -> Result<()> {
+        let data = fs::read(&path)?;
+        let n = self.files.len();
+        for &algo in algos {
+            self.index.entry((algo, algo.digest(&data))).or_insert(n);
+        }
+        self.files.push(CachedFile {
+            path,
+            size: data.len() as u64,
+        });
+        Ok(())
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn files(&self) -> &[CachedFile] {
+        &self.files
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn find(&self, algo: HashAlgo, digest: &str) -> Option<&CachedFile> {
+        self.index
+            .get(&(algo, digest.to_ascii_lowercase()))
+            .map(|&i| &self.files[i])
+    }
+
+    /// Reads the cached file with the given hash, checking it on the way.
+    pub fn read(&self, algo: HashAlgo, digest: &str) -> Result<Option<Vec<u8>>> {
+        let Some(file) = self.find(algo, digest) else {
+            return Ok(None);
+        };
+        let data = fs::read(&file.path)?;
+        if !algo.digest(&data).eq_ignore_ascii_case(digest) {
+            return Err(Error::VerifyFailed(
+                file.path.clone(),
+                format!("{algo} changed since the cache was scanned"),
+            ));
+        }
+        Ok(Some(data))
+    }
+}
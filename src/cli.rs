@@ -0,0 +1,384 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+/// Replace Minecraft mod jars while keeping the exact original file size.
+///
+/// Runs the interactive picker when no subcommand is given.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Answer yes to every confirmation prompt
+    #[arg(short, long, global = true)]
+    pub yes: bool,
+
+    /// Show what would be written and where, without changing any file
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Interactive mode: replace even if the jars look like different mods or
+    /// the replacement breaks dependencies, without asking
+    #[arg(long)]
+    pub force: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Replace a mod in the mods folder, padding to its original size
+    Replace(ReplaceArgs),
+    /// List the jars in a mods folder
+    List(ListArgs),
+    /// List the Minecraft instances found on this machine
+    Instances,
+    /// Show the ZIP layout of a jar
+    Inspect(InspectArgs),
+    /// List past replacements, or put one back
+    Restore(RestoreArgs),
+    /// Enable disabled mods by dropping their `.disabled` suffix
+    Enable(ToggleArgs),
+    /// Disable mods by renaming them to `<name>.jar.disabled`
+    Disable(ToggleArgs),
+    /// Replace several mods at once from a TOML plan, or make the folder match a spec
+    Apply(ApplyArgs),
+    /// Check a jar's headers, decompress every entry and compare CRCs
+    Verify(VerifyArgs),
+    /// Check the dependencies and incompatibilities of every mod in a mods folder
+    Check(CheckArgs),
+    /// Find jars that declare the same mod, and optionally keep only the newest
+    Duplicates(DuplicatesArgs),
+    /// Show what changed between two versions of a jar
+    Diff(DiffArgs),
+    /// Record the jars of a mods folder, with their hashes, in a lockfile
+    Lock(LockArgs),
+    /// Report jars added, removed, modified or renamed since the last `lock`
+    Status(LockArgs),
+    /// Pair a folder of newer jars with the installed mods and replace them
+    Upgrade(UpgradeArgs),
+    /// Import or export Modrinth `.mrpack` modpacks
+    #[command(subcommand)]
+    Mrpack(MrpackCommand),
+    /// Compare CurseForge modpack exports against a mods folder
+    #[command(subcommand)]
+    Curseforge(CurseforgeCommand),
+    /// Compare or sync an instance against a packwiz pack
+    #[command(subcommand)]
+    Packwiz(PackwizCommand),
+    /// Switch a mods folder between named sets of mods
+    #[command(subcommand)]
+    Profile(ProfileCommand),
+}
+
+#[derive(Debug, Args)]
+pub struct ReplaceArgs {
+    /// Minecraft 'mods' folder
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: PathBuf,
+
+    /// File name of the jar to replace (the .jar extension is optional)
+    #[arg(long, value_name = "NAME")]
+    pub target: String,
+
+    /// Replacement jar
+    #[arg(long = "with", value_name = "JAR")]
+    pub replacement: PathBuf,
+
+    /// Fail instead of appending null bytes when the ZIP comment cannot hold the padding
+    #[arg(long)]
+    pub no_append: bool,
+
+    /// Overwrite the original without saving a backup first
+    #[arg(long)]
+    pub no_backup: bool,
+
+    /// Replace even if the jars declare different mod ids, loaders or Minecraft versions
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// Minecraft 'mods' folder
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: PathBuf,
+
+    /// Only show jars this replacement could be padded to, closest in size first
+    #[arg(long = "with", value_name = "JAR")]
+    pub replacement: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct InspectArgs {
+    /// Jar to inspect
+    pub jar: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct RestoreArgs {
+    /// Minecraft 'mods' folder
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: PathBuf,
+
+    /// Backup to restore; lists all backups when left out
+    pub id: Option<String>,
+
+    /// Only restore the file with this name from the backup
+    #[arg(long, value_name = "NAME")]
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// Jar to verify
+    pub jar: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// The jar as it was
+    pub old: PathBuf,
+    /// The jar as it is now
+    pub new: PathBuf,
+
+    /// Only list changed entries and metadata, without text diffs
+    #[arg(long)]
+    pub no_text: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct LockArgs {
+    /// Minecraft 'mods' folder
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: PathBuf,
+
+    /// Lockfile [default: .mod-replacer/mods.lock next to the mods folder]
+    #[arg(long, value_name = "FILE")]
+    pub lockfile: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    /// Minecraft 'mods' folder
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct DuplicatesArgs {
+    /// Minecraft 'mods' folder
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: PathBuf,
+
+    /// Keep the newest jar of each mod and move the others into a backup
+    #[arg(long)]
+    pub fix: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ApplyArgs {
+    /// TOML file listing `[[replace]]` pairs of `target` and `with`
+    #[arg(long, value_name = "FILE", required_unless_present = "spec")]
+    pub plan: Option<PathBuf>,
+
+    /// TOML file listing the `[[mod]]`s the folder should contain; makes the folder match it
+    #[arg(long, value_name = "FILE", conflicts_with = "plan")]
+    pub spec: Option<PathBuf>,
+
+    /// Folder of previously downloaded jars to install from, for --spec
+    #[arg(long, value_name = "DIR", requires = "spec")]
+    pub cache: Option<PathBuf>,
+
+    /// Minecraft 'mods' folder, overriding `mods_dir` in the plan
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: Option<PathBuf>,
+
+    /// Fail instead of appending null bytes when the ZIP comment cannot hold the padding
+    #[arg(long)]
+    pub no_append: bool,
+
+    /// Replace even if jars declare different mod ids, loaders or Minecraft versions
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct UpgradeArgs {
+    /// Minecraft 'mods' folder
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: PathBuf,
+
+    /// Folder holding the new jars
+    #[arg(long, value_name = "DIR")]
+    pub from: PathBuf,
+
+    /// Fail instead of appending null bytes when the ZIP comment cannot hold the padding
+    #[arg(long)]
+    pub no_append: bool,
+
+    /// Replace even if jars declare different mod ids, loaders or Minecraft versions
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ToggleArgs {
+    /// Minecraft 'mods' folder
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: PathBuf,
+
+    /// File names or mod ids of the mods
+    #[arg(required = true, value_name = "MOD")]
+    pub mods: Vec<String>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MrpackCommand {
+    /// Install a pack into an instance, taking its files from a local cache
+    Import(MrpackImportArgs),
+    /// Pack the enabled jars of a mods folder into an `.mrpack`
+    Export(MrpackExportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct MrpackImportArgs {
+    /// The `.mrpack` file
+    pub pack: PathBuf,
+
+    /// Instance game folder, the one that holds 'mods'
+    #[arg(long, value_name = "DIR")]
+    pub game_dir: PathBuf,
+
+    /// Folder of previously downloaded files, searched by hash
+    #[arg(long, value_name = "DIR")]
+    pub cache: PathBuf,
+
+    /// Install the server side of the pack instead of the client side
+    #[arg(long)]
+    pub server: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct MrpackExportArgs {
+    /// Minecraft 'mods' folder
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: PathBuf,
+
+    /// Where to write the `.mrpack`
+    #[arg(long, short, value_name = "FILE")]
+    pub output: PathBuf,
+
+    /// Pack name
+    #[arg(long)]
+    pub name: String,
+
+    /// Pack version
+    #[arg(long = "pack-version", value_name = "VERSION", default_value = "1.0.0")]
+    pub version_id: String,
+
+    /// Short pack description
+    #[arg(long)]
+    pub summary: Option<String>,
+
+    /// Minecraft version; taken from the launcher instance when left out
+    #[arg(long, value_name = "VERSION")]
+    pub minecraft: Option<String>,
+
+    /// Loader dependency such as `fabric-loader=0.15.11`; taken from the launcher instance when left out
+    #[arg(long, value_name = "ID=VERSION")]
+    pub loader: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CurseforgeCommand {
+    /// Show which manifest entries are present, missing or different in a mods folder
+    Status(CurseforgeStatusArgs),
+    /// Print the CurseForge fingerprint of jars
+    Fingerprint(FingerprintArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct CurseforgeStatusArgs {
+    /// Exported pack zip, or its `manifest.json`
+    pub pack: PathBuf,
+
+    /// Minecraft 'mods' folder
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: PathBuf,
+
+    /// CurseForge app `minecraftinstance.json` holding known fingerprints; defaults to the one next to the mods folder
+    #[arg(long, value_name = "FILE")]
+    pub instance_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct FingerprintArgs {
+    /// Jars to fingerprint
+    #[arg(required = true, value_name = "JAR")]
+    pub jars: Vec<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PackwizCommand {
+    /// Compare the files and hashes a pack declares against the game folder
+    Status(PackwizArgs),
+    /// Make the game folder match the pack, taking jars from a local cache
+    Sync(PackwizSyncArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct PackwizArgs {
+    /// The pack's `pack.toml`
+    pub pack: PathBuf,
+
+    /// Instance game folder, the one that holds 'mods'
+    #[arg(long, value_name = "DIR")]
+    pub game_dir: PathBuf,
+
+    /// Use the server side of the pack instead of the client side
+    #[arg(long)]
+    pub server: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct PackwizSyncArgs {
+    #[command(flatten)]
+    pub pack: PackwizArgs,
+
+    /// Folder of previously downloaded files, searched by hash
+    #[arg(long, value_name = "DIR")]
+    pub cache: PathBuf,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProfileCommand {
+    /// List the profiles and mark the one the folder matches
+    List(ProfileArgs),
+    /// Enable, disable and install jars so the folder matches a profile
+    Switch(ProfileSwitchArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ProfileArgs {
+    /// Minecraft 'mods' folder
+    #[arg(long, value_name = "DIR")]
+    pub mods_dir: PathBuf,
+
+    /// Profiles file [default: .mod-replacer/profiles.toml next to the mods folder]
+    #[arg(long, value_name = "FILE")]
+    pub profiles: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ProfileSwitchArgs {
+    /// Profile to switch to
+    pub name: String,
+
+    #[command(flatten)]
+    pub profiles: ProfileArgs,
+
+    /// Folder of jars to install missing mods from, instead of the file's `store`
+    #[arg(long, value_name = "DIR")]
+    pub store: Option<PathBuf>,
+}
@@ -0,0 +1,185 @@
+use std::error::Error;
+use std::path::Path;
+
+use minecraft_mod_replacer::ReplaceOptions;
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::batch::{self, BatchPlan, PlanFile};
+use minecraft_mod_replacer::cache::FileCache;
+use minecraft_mod_replacer::mods::file_name;
+use minecraft_mod_replacer::spec::PackSpec;
+
+use crate::cli::ApplyArgs;
+use crate::commands::Context;
+
+pub fn run(args: ApplyArgs, ctx: Context) -> Result<(), Box<dyn Error>> {
+    if let Some(spec) = &args.spec {
+        return apply_spec(spec, &args, ctx);
+    }
+    let plan_path = args.plan.as_deref().ok_or("pass --plan or --spec")?;
+    let plan_file = PlanFile::load(plan_path)?;
+    let mods_dir = args
+        .mods_dir
+        .clone()
+        .or(plan_file.mods_dir.clone())
+        .ok_or("no mods folder given; pass --mods-dir or set mods_dir in the plan")?;
+
+    let options = ReplaceOptions {
+        allow_append: !args.no_append,
+        backup: true,
+        force: args.force,
+    };
+    let batch = plan_file.prepare(&mods_dir, &options)?;
+    if batch.plans.is_empty() {
+        println!("The plan has no replacements.");
+        return Ok(());
+    }
+
+    let blocked = print_plans(&batch);
+    if blocked > 0 {
+        return Err(format!(
+            "{blocked} replacement(s) do not match their target or break dependencies; pass --force or set force = true on them"
+        )
+        .into());
+    }
+    if ctx.dry_run {
+        println!(
+            "Dry run, nothing was changed. Would replace {} jar(s) in {}.",
+            batch.plans.len(),
+            mods_dir.display()
+        );
+        return Ok(());
+    }
+    if !ctx.confirm(&format!("Apply {} replacement(s)?", batch.plans.len()))? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let backup = batch::apply(&batch, &BackupStore::for_mods_dir(&mods_dir))?;
+    println!(
+        "Replaced {} jar(s). Originals saved as backup {} (undo with `restore {}`).",
+        batch.plans.len(),
+        backup.id,
+        backup.id
+    );
+    Ok(())
+}
+
+/// Prints one line per replacement with its warnings, then the problems of
+/// the batch as a whole, and returns how many replacements are blocked by a
+/// mismatch or a dependency problem. Blocking batch problems count as one.
+pub fn print_plans(batch: &BatchPlan) -> usize {
+    let mut blocked = 0;
+    for plan in &batch.plans {
+        println!(
+            "{} ← {} | {} | {}, {} bytes padding",
+            file_name(&plan.target),
+            plan.replacement.display(),
+            super::describe_mod(plan.replacement_metadata.as_ref()),
+            plan.strategy,
+            plan.padding()
+        );
+        if let Some(delta) = &plan.version_change {
+            println!("    {} → {} ({})", delta.from, delta.to, delta.change);
+        }
+        for mismatch in &plan.mismatches {
+            eprintln!("    ⚠️  {mismatch}");
+        }
+        for problem in &plan.dependency_problems {
+            eprintln!("    ⚠️  {problem}");
+        }
+        if plan.is_blocked() {
+            blocked += 1;
+        }
+    }
+    if !batch.problems.is_empty() {
+        println!("The replacements together:");
+        for problem in &batch.problems {
+            eprintln!("    ⚠️  {problem}");
+        }
+    }
+    if batch.is_blocked() {
+        blocked += 1;
+    }
+    println!();
+    blocked
+}
+
+/// Makes the mods folder match a spec. Safe to run again: a folder that
+/// already matches is left alone.
+fn apply_spec(path: &Path, args: &ApplyArgs, ctx: Context) -> Result<(), Box<dyn Error>> {
+    let spec = PackSpec::load(path)?;
+    let mods_dir = args
+        .mods_dir
+        .as_deref()
+        .ok_or("no mods folder given; pass --mods-dir")?;
+    let cache = match &args.cache {
+        Some(dir) => Some(FileCache::open(dir, &spec.hash_formats())?),
+        None => None,
+    };
+    let plan = spec.reconcile(mods_dir, cache.as_ref())?;
+
+    if let Some(name) = &spec.name {
+        println!("{name}");
+    }
+    println!(
+        "{} of {} mod(s) already in place.",
+        plan.satisfied,
+        spec.mods.len()
+    );
+    for path in &plan.enables {
+        println!("  enable  | {}", file_name(path));
+    }
+    for install in &plan.installs {
+        let action = if install.replaces {
+            "replace"
+        } else {
+            "install"
+        };
+        println!(
+            "  {action} | {} ← {} ({})",
+            file_name(&install.path),
+            install.source.display(),
+            install.id
+        );
+    }
+    for path in &plan.removals {
+        println!("  remove  | {}", file_name(path));
+    }
+    for (entry, reason) in &plan.unresolved {
+        eprintln!("⚠️  {entry}: {reason}");
+    }
+    println!();
+
+    if plan.is_empty() {
+        println!("{} already matches the spec.", mods_dir.display());
+        return unresolved(&plan.unresolved);
+    }
+    let changes = plan.enables.len() + plan.installs.len() + plan.removals.len();
+    if ctx.dry_run {
+        println!("Dry run, nothing was changed. Would make {changes} change(s).");
+        return Ok(());
+    }
+    if !ctx.confirm(&format!("Make {changes} change(s)?"))? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let mut backup = BackupStore::for_mods_dir(mods_dir).begin("apply spec")?;
+    plan.apply(&mut backup)?;
+    print!("Made {changes} change(s).");
+    if !backup.is_empty() {
+        print!(
+            " Removed and overwritten jars saved as backup {} (undo with `restore {}`).",
+            backup.id, backup.id
+        );
+    }
+    println!();
+    unresolved(&plan.unresolved)
+}
+
+fn unresolved(entries: &[(String, String)]) -> Result<(), Box<dyn Error>> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    Err(format!("{} mod(s) of the spec could not be resolved", entries.len()).into())
+}
@@ -0,0 +1,35 @@
+use std::error::Error;
+
+use minecraft_mod_replacer::deps::ModSet;
+
+use crate::cli::CheckArgs;
+
+pub fn run(args: CheckArgs) -> Result<(), Box<dyn Error>> {
+    let set = ModSet::scan(&args.mods_dir)?;
+    let problems = set.problems();
+    let (errors, warnings): (Vec<_>, Vec<_>) = problems.iter().partition(|p| p.is_error());
+
+    for problem in &errors {
+        eprintln!("✗ {problem}");
+    }
+    for problem in &warnings {
+        eprintln!("⚠️  {problem}");
+    }
+    if !errors.is_empty() {
+        return Err(format!(
+            "{}: {} dependency problem(s), {} warning(s)",
+            args.mods_dir.display(),
+            errors.len(),
+            warnings.len()
+        )
+        .into());
+    }
+
+    println!(
+        "{}: OK ({} mods checked, {} warning(s))",
+        args.mods_dir.display(),
+        set.mods.len(),
+        warnings.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use std::error::Error;
+use std::fs;
+
+use minecraft_mod_replacer::curseforge::{self, EntryStatus, OverrideStatus, Pack};
+use minecraft_mod_replacer::hashes::curseforge_fingerprint;
+use minecraft_mod_replacer::mods::file_name;
+
+use crate::cli::{CurseforgeCommand, CurseforgeStatusArgs, FingerprintArgs};
+
+pub fn run(command: CurseforgeCommand) -> Result<(), Box<dyn Error>> {
+    match command {
+        CurseforgeCommand::Status(args) => status(args),
+        CurseforgeCommand::Fingerprint(args) => fingerprint(args),
+    }
+}
+
+fn status(args: CurseforgeStatusArgs) -> Result<(), Box<dyn Error>> {
+    let pack = Pack::read(&args.pack)?;
+    let instance_file = args
+        .instance_file
+        .or_else(|| curseforge::instance_file(&args.mods_dir));
+    let known = match &instance_file {
+        Some(path) => curseforge::read_known_files(path)?,
+        None => Vec::new(),
+    };
+    let jars = curseforge::scan(&args.mods_dir, &known)?;
+    let status = curseforge::status(&pack, jars, &known);
+
+    let manifest = &pack.manifest;
+    let loaders: Vec<&str> = manifest
+        .minecraft
+        .mod_loaders
+        .iter()
+        .map(|l| l.id.as_str())
+        .collect();
+    println!(
+        "{} {} | Minecraft {} | {}",
+        manifest.name,
+        manifest.version,
+        manifest.minecraft.version,
+        loaders.join(", ")
+    );
+    if instance_file.is_none() {
+        eprintln!(
+            "⚠️  no {} found; manifest entries can only be matched with its fingerprints (pass --instance-file)",
+            curseforge::INSTANCE_FILE
+        );
+    }
+    println!();
+
+    let (mut present, mut different, mut missing) = (0, 0, 0);
+    for (file, entry) in &status.entries {
+        let id = format!("{}/{}", file.project_id, file.file_id);
+        let optional = if file.required { "" } else { " (optional)" };
+        match entry {
+            EntryStatus::Present(path) => {
+                present += 1;
+                println!("  present | {id}{optional} | {}", file_name(path));
+            }
+            EntryStatus::Different {
+                path,
+                installed_file_id,
+            } => {
+                different += 1;
+                let installed = match installed_file_id {
+                    Some(file_id) => format!("file {file_id} installed"),
+                    None => "modified".to_string(),
+                };
+                println!(
+                    "different | {id}{optional} | {} ({installed})",
+                    file_name(path)
+                );
+            }
+            EntryStatus::Missing => {
+                missing += 1;
+                println!("  missing | {id}{optional}");
+            }
+        }
+    }
+    for (name, entry) in &status.overrides {
+        match entry {
+            OverrideStatus::Present(path) => {
+                present += 1;
+                println!("  present | override {name} | {}", file_name(path));
+            }
+            OverrideStatus::Different(path) => {
+                different += 1;
+                println!("different | override {name} | {}", file_name(path));
+            }
+            OverrideStatus::Missing => {
+                missing += 1;
+                println!("  missing | override {name}");
+            }
+        }
+    }
+    for jar in &status.extra {
+        let known = match &jar.known {
+            Some(k) => format!("{}/{}", k.project_id, k.file_id),
+            None => format!("fingerprint {}", jar.fingerprint),
+        };
+        println!("    extra | {known} | {}", jar.candidate.file_name());
+    }
+
+    println!();
+    println!(
+        "{present} present, {different} different, {missing} missing, {} not in the pack.",
+        status.extra.len()
+    );
+    Ok(())
+}
+
+fn fingerprint(args: FingerprintArgs) -> Result<(), Box<dyn Error>> {
+    for jar in &args.jars {
+        println!(
+            "{} {}",
+            curseforge_fingerprint(&fs::read(jar)?),
+            jar.display()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fs;
+
+use minecraft_mod_replacer::diff::{self, EntryChange};
+
+use crate::cli::DiffArgs;
+
+pub fn run(args: DiffArgs) -> Result<(), Box<dyn Error>> {
+    let old = fs::read(&args.old)?;
+    let new = fs::read(&args.new)?;
+    let result = diff::diff(&old, &new)?;
+
+    println!("{} → {}", args.old.display(), args.new.display());
+    let (added, removed, modified) = result.counts();
+    println!(
+        "Entries: {added} added, {removed} removed, {modified} modified, {} unchanged",
+        result.unchanged
+    );
+    if result.is_empty() {
+        println!("The jars have the same content.");
+        return Ok(());
+    }
+
+    if !result.metadata.is_empty() {
+        println!();
+        println!("Metadata:");
+        for change in &result.metadata {
+            println!("  {change}");
+        }
+    }
+
+    if !result.entries.is_empty() {
+        println!();
+        for entry in &result.entries {
+            match &entry.change {
+                EntryChange::Added { size } => println!("  + {} ({size} bytes)", entry.name),
+                EntryChange::Removed { size } => println!("  - {} ({size} bytes)", entry.name),
+                EntryChange::Modified {
+                    old_crc,
+                    new_crc,
+                    old_size,
+                    new_size,
+                } => println!(
+                    "  M {} ({old_size} → {new_size} bytes, crc {old_crc:08x} → {new_crc:08x})",
+                    entry.name
+                ),
+            }
+        }
+    }
+
+    if !args.no_text {
+        for text in result.text.iter().filter(|t| !t.hunks.is_empty()) {
+            println!();
+            println!("--- a/{}", text.name);
+            println!("+++ b/{}", text.name);
+            for hunk in &text.hunks {
+                print!("{hunk}");
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::duplicates::{self, DuplicateGroup, ModJar};
+
+use crate::cli::DuplicatesArgs;
+use crate::commands::Context;
+
+pub fn run(args: DuplicatesArgs, ctx: Context) -> Result<(), Box<dyn Error>> {
+    let groups = duplicates::find(&args.mods_dir)?;
+    if groups.is_empty() {
+        println!("No duplicate mods in {}", args.mods_dir.display());
+        return Ok(());
+    }
+    print_groups(&groups);
+    if args.fix {
+        fix(&args.mods_dir, &groups, ctx)?;
+    } else {
+        println!("Pass --fix to keep the newest jar of each mod and back up the rest.");
+    }
+    Ok(())
+}
+
+pub fn print_groups(groups: &[DuplicateGroup]) {
+    for group in groups {
+        eprintln!(
+            "⚠️  {} is installed {} times:",
+            group.mod_id,
+            group.jars.len()
+        );
+        println!("    keep   {}", label(group.kept()));
+        for jar in group.others() {
+            if jar.bundled.is_none() {
+                println!("    remove {}", label(jar));
+            } else if group.is_newer_than_kept(jar) {
+                println!(
+                    "    ignore {}, newer than the kept jar; the loader may use it instead",
+                    label(jar)
+                );
+            } else {
+                println!("    ignore {}", label(jar));
+            }
+        }
+    }
+    println!();
+}
+
+fn label(jar: &ModJar) -> String {
+    let version = jar.metadata.version.as_deref().unwrap_or("unknown version");
+    match &jar.bundled {
+        Some(nested) => format!(
+            "{nested} ({version}, bundled in {})",
+            jar.candidate.file_name()
+        ),
+        None => format!("{} ({version})", jar.candidate.file_name()),
+    }
+}
+
+/// Keeps the newest jar of each group and moves the rest into a backup.
+pub fn fix(mods_dir: &Path, groups: &[DuplicateGroup], ctx: Context) -> Result<(), Box<dyn Error>> {
+    let older: Vec<PathBuf> = groups
+        .iter()
+        .flat_map(|g| g.removable().map(|jar| jar.candidate.path.clone()))
+        .collect();
+    if older.is_empty() {
+        println!("The other copies are bundled; they stay with the mods that ship them.");
+        return Ok(());
+    }
+    if ctx.dry_run {
+        println!(
+            "Dry run, nothing was changed. Would move {} jar(s) into a backup.",
+            older.len()
+        );
+        return Ok(());
+    }
+    if !ctx.confirm(&format!(
+        "Keep the newest and move {} older jar(s) into a backup?",
+        older.len()
+    ))? {
+        println!("Left the duplicates in place.");
+        return Ok(());
+    }
+
+    let mut backup = BackupStore::for_mods_dir(mods_dir).begin("duplicates")?;
+    if let Err(err) = duplicates::remove(&older, &mut backup) {
+        backup.rollback()?;
+        return Err(err.into());
+    }
+    println!(
+        "Moved {} jar(s) out. They are saved as backup {} (undo with `restore {}`).",
+        older.len(),
+        backup.id,
+        backup.id
+    );
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use std::error::Error;
+use std::fs;
+
+use minecraft_mod_replacer::metadata::{self, NestedJar};
+use minecraft_mod_replacer::zip::{Archive, MAX_COMMENT_LEN};
+
+use crate::cli::InspectArgs;
+
+pub fn run(args: InspectArgs) -> Result<(), Box<dyn Error>> {
+    let data = fs::read(&args.jar)?;
+    let archive = Archive::parse(&data)?;
+    let eocd = &archive.eocd;
+
+    println!("{}", args.jar.display());
+    println!("Size:              {} bytes", data.len());
+    println!("Entries:           {}", eocd.entries);
+    println!(
+        "Central directory: {} bytes at offset {}",
+        eocd.cd_size, eocd.cd_offset
+    );
+    println!("EOCD offset:       {}", eocd.offset);
+    if let Some(zip64) = &eocd.zip64 {
+        println!(
+            "ZIP64:             record at {}, locator at {}",
+            zip64.record_offset, zip64.locator_offset
+        );
+    }
+    println!("Comment length:    {} bytes", eocd.comment_len);
+    println!(
+        "Comment padding:   up to {} more bytes",
+        MAX_COMMENT_LEN.saturating_sub(archive.trailing_len())
+    );
+    println!();
+
+    match metadata::read_archive(&archive) {
+        Ok(jar) => {
+            if jar.is_empty() {
+                println!("No mod descriptor found");
+            }
+            for m in &jar.mods {
+                println!("{} [{}]", super::describe_mod_metadata(m), m.source);
+            }
+            for err in &jar.errors {
+                println!("Unreadable descriptor: {err}");
+            }
+            if !jar.nested.is_empty() {
+                println!();
+                println!("Bundled jars:");
+                print_tree(&jar.nested, "");
+            }
+        }
+        Err(err) => println!("Unreadable mod metadata: {err}"),
+    }
+    println!();
+
+    for entry in &archive.entries {
+        println!(
+            "{:>10} {:>10} {:<9} {:08x} {}",
+            entry.uncompressed_size, entry.compressed_size, entry.method, entry.crc32, entry.name
+        );
+    }
+    Ok(())
+}
+
+/// Prints bundled jars as a tree, each with the mods it declares.
+fn print_tree(nested: &[NestedJar], indent: &str) {
+    for (i, jar) in nested.iter().enumerate() {
+        let last = i + 1 == nested.len();
+        let (branch, next) = if last {
+            ("└─ ", "   ")
+        } else {
+            ("├─ ", "│  ")
+        };
+        let child_indent = format!("{indent}{next}");
+        let mut line = jar.path.clone();
+        if let Some(artifact) = &jar.artifact {
+            line.push_str(&format!(" [{artifact}"));
+            if let Some(version) = &jar.artifact_version {
+                line.push_str(&format!(" {version}"));
+            }
+            line.push(']');
+        }
+        println!("{indent}{branch}{line}");
+        if let Some(err) = &jar.error {
+            println!("{child_indent}unreadable: {err}");
+        }
+        for m in &jar.metadata.mods {
+            println!("{child_indent}{}", super::describe_mod_metadata(m));
+        }
+        print_tree(&jar.metadata.nested, &child_indent);
+    }
+}
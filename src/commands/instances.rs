@@ -0,0 +1,25 @@
+use std::error::Error;
+
+use minecraft_mod_replacer::instances::{self, Instance};
+
+pub fn run() -> Result<(), Box<dyn Error>> {
+    let found = instances::discover();
+    if found.is_empty() {
+        println!("No Minecraft instances with a mods folder found.");
+    }
+    for instance in &found {
+        println!("{}", label(instance));
+    }
+    Ok(())
+}
+
+pub fn label(instance: &Instance) -> String {
+    format!(
+        "{} | {} | Minecraft {} | {} | {}",
+        instance.name,
+        instance.launcher,
+        instance.minecraft.as_deref().unwrap_or("?"),
+        instance.loader.as_deref().unwrap_or("no loader"),
+        instance.mods_dir.display()
+    )
+}
@@ -0,0 +1,35 @@
+use std::error::Error;
+use std::fs;
+
+use minecraft_mod_replacer::mods;
+
+use crate::cli::ListArgs;
+use crate::commands::describe_mod;
+
+pub fn run(args: ListArgs) -> Result<(), Box<dyn Error>> {
+    let Some(replacement) = args.replacement else {
+        for jar in mods::list_jars(&args.mods_dir)? {
+            println!(
+                "{} | {} | {} | {} bytes",
+                jar.file_name(),
+                if jar.disabled { "disabled" } else { "enabled" },
+                describe_mod(jar.metadata().ok().as_ref()),
+                jar.size
+            );
+        }
+        return Ok(());
+    };
+
+    let replacement_size = fs::metadata(&replacement)?.len();
+    let candidates = mods::find_candidates(&args.mods_dir, replacement_size)?;
+    if candidates.is_empty() {
+        println!("No suitable .jar mod files found in {:?}", args.mods_dir);
+    }
+    for candidate in candidates {
+        println!(
+            "{}",
+            crate::interactive::candidate_label(&candidate, replacement_size)
+        );
+    }
+    Ok(())
+}
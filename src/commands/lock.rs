@@ -0,0 +1,76 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use minecraft_mod_replacer::lockfile::{self, Change, LockedJar, Lockfile};
+
+use crate::cli::LockArgs;
+use crate::commands::Context;
+
+fn path(args: &LockArgs) -> PathBuf {
+    args.lockfile
+        .clone()
+        .unwrap_or_else(|| lockfile::default_path(&args.mods_dir))
+}
+
+pub fn lock(args: LockArgs, ctx: Context) -> Result<(), Box<dyn Error>> {
+    let path = path(&args);
+    let lock = Lockfile::scan(&args.mods_dir)?;
+    if ctx.dry_run {
+        println!(
+            "Dry run, nothing was changed. Would record {} jar(s) in {}.",
+            lock.jars.len(),
+            path.display()
+        );
+        return Ok(());
+    }
+    lock.save(&path)?;
+    println!("Recorded {} jar(s) in {}.", lock.jars.len(), path.display());
+    Ok(())
+}
+
+pub fn status(args: LockArgs) -> Result<(), Box<dyn Error>> {
+    let path = path(&args);
+    if !path.is_file() {
+        return Err(format!("no lockfile at {}, run `lock` first", path.display()).into());
+    }
+    let locked = Lockfile::load(&path)?;
+    let current = Lockfile::scan(&args.mods_dir)?;
+    let changes = locked.changes(&current);
+
+    println!("Compared with {}", path.display());
+    if changes.is_empty() {
+        println!(
+            "Nothing changed, the {} jar(s) match the lockfile.",
+            current.jars.len()
+        );
+        return Ok(());
+    }
+    println!();
+    for change in &changes {
+        match change {
+            Change::Added(jar) => println!("  added:    {}", label(jar)),
+            Change::Removed(jar) => println!("  removed:  {}", label(jar)),
+            Change::Modified { old, new } => {
+                let versions = match (&old.mod_version, &new.mod_version) {
+                    (Some(a), Some(b)) if a != b => format!(" ({a} → {b})"),
+                    _ => String::new(),
+                };
+                println!("  modified: {}{versions}", new.file);
+            }
+            Change::Renamed { old, new } => println!("  renamed:  {} → {}", old.file, new.file),
+        }
+    }
+    println!();
+    println!(
+        "{} change(s). Run `lock` to record the folder as it is now.",
+        changes.len()
+    );
+    Ok(())
+}
+
+fn label(jar: &LockedJar) -> String {
+    match jar.describe() {
+        Some(description) => format!("{} ({description})", jar.file),
+        None => jar.file.clone(),
+    }
+}
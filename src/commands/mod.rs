@@ -0,0 +1,97 @@
+use std::error::Error;
+
+use dialoguer::Confirm;
+use minecraft_mod_replacer::metadata::{JarMetadata, ModMetadata};
+
+use crate::cli::{Cli, Command};
+
+mod apply;
+mod check;
+mod curseforge;
+mod diff;
+pub mod duplicates;
+mod inspect;
+pub mod instances;
+mod list;
+mod lock;
+mod mrpack;
+mod packwiz;
+mod profile;
+pub mod replace;
+mod restore;
+mod toggle;
+mod upgrade;
+mod verify;
+
+/// Flags shared by every command.
+#[derive(Debug, Clone, Copy)]
+pub struct Context {
+    pub yes: bool,
+    pub dry_run: bool,
+}
+
+impl Context {
+    /// Asks `prompt`, or assumes yes when `--yes` was given.
+    pub fn confirm(&self, prompt: &str) -> Result<bool, Box<dyn Error>> {
+        if self.yes {
+            return Ok(true);
+        }
+        Ok(Confirm::new()
+            .with_prompt(prompt)
+            .default(false)
+            .interact()?)
+    }
+}
+
+pub fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let ctx = Context {
+        yes: cli.yes,
+        dry_run: cli.dry_run,
+    };
+    let Some(command) = cli.command else {
+        return crate::interactive::run(ctx, cli.force);
+    };
+    match command {
+        Command::Replace(args) => replace::run(args, ctx),
+        Command::List(args) => list::run(args),
+        Command::Instances => instances::run(),
+        Command::Inspect(args) => inspect::run(args),
+        Command::Restore(args) => restore::run(args, ctx),
+        Command::Enable(args) => toggle::run(args, true, ctx),
+        Command::Disable(args) => toggle::run(args, false, ctx),
+        Command::Apply(args) => apply::run(args, ctx),
+        Command::Verify(args) => verify::run(args),
+        Command::Check(args) => check::run(args),
+        Command::Duplicates(args) => duplicates::run(args, ctx),
+        Command::Diff(args) => diff::run(args),
+        Command::Lock(args) => lock::lock(args, ctx),
+        Command::Status(args) => lock::status(args),
+        Command::Upgrade(args) => upgrade::run(args, ctx),
+        Command::Mrpack(command) => mrpack::run(command, ctx),
+        Command::Curseforge(command) => curseforge::run(command),
+        Command::Packwiz(command) => packwiz::run(command, ctx),
+        Command::Profile(command) => profile::run(command, ctx),
+    }
+}
+
+/// One-line description of the main mod in a jar, for lists and prompts.
+pub fn describe_mod(metadata: Option<&JarMetadata>) -> String {
+    match metadata.and_then(JarMetadata::primary) {
+        Some(m) => describe_mod_metadata(m),
+        None => "unknown mod".to_string(),
+    }
+}
+
+pub fn describe_mod_metadata(m: &ModMetadata) -> String {
+    let mut out = m.display_name().to_string();
+    if let Some(version) = &m.version {
+        out.push(' ');
+        out.push_str(version);
+    }
+    out.push_str(&format!(" ({}, {}", m.id, m.loader));
+    if let Some(minecraft) = &m.minecraft {
+        out.push_str(&format!(", Minecraft {minecraft}"));
+    }
+    out.push(')');
+    out
+}
@@ -0,0 +1,151 @@
+use std::error::Error;
+use std::fs;
+
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::cache::FileCache;
+use minecraft_mod_replacer::fsio;
+use minecraft_mod_replacer::hashes::HashAlgo;
+use minecraft_mod_replacer::instances;
+use minecraft_mod_replacer::mrpack::{self, ExportOptions, FileChange, FileSource, Pack, Side};
+
+use crate::cli::{MrpackCommand, MrpackExportArgs, MrpackImportArgs};
+use crate::commands::Context;
+
+pub fn run(command: MrpackCommand, ctx: Context) -> Result<(), Box<dyn Error>> {
+    match command {
+        MrpackCommand::Import(args) => import(args, ctx),
+        MrpackCommand::Export(args) => export(args, ctx),
+    }
+}
+
+fn import(args: MrpackImportArgs, ctx: Context) -> Result<(), Box<dyn Error>> {
+    let pack = Pack::read(&args.pack)?;
+    let cache = FileCache::open(&args.cache, &[HashAlgo::Sha1, HashAlgo::Sha512])?;
+    let side = if args.server {
+        Side::Server
+    } else {
+        Side::Client
+    };
+    let plan = pack.plan_install(&args.game_dir, &cache, side)?;
+
+    println!("{} {}", pack.index.name, pack.index.version_id);
+    for (id, version) in &pack.index.dependencies {
+        println!("    {id} {version}");
+    }
+    println!();
+    for file in &plan.files {
+        let change = match file.change {
+            FileChange::New => "new",
+            FileChange::Replaced => "replace",
+            FileChange::Unchanged => "unchanged",
+        };
+        let source = match file.source {
+            FileSource::Cache => "cache",
+            FileSource::Override => "override",
+        };
+        println!("{change:>9} | {source:<8} | {}", file.path.display());
+    }
+    for path in &plan.missing {
+        eprintln!("⚠️  not in the cache: {path}");
+    }
+    println!();
+
+    let changed = plan
+        .files
+        .iter()
+        .filter(|f| f.change != FileChange::Unchanged)
+        .count();
+    if ctx.dry_run {
+        println!(
+            "Dry run, nothing was changed. Would write {changed} file(s) into {}.",
+            args.game_dir.display()
+        );
+        return Ok(());
+    }
+    if !plan.missing.is_empty()
+        && !ctx.confirm(&format!(
+            "{} file(s) are missing from the cache. Install the rest anyway?",
+            plan.missing.len()
+        ))?
+    {
+        println!("Aborted.");
+        return Ok(());
+    }
+    if changed > 0 && !ctx.confirm(&format!("Write {changed} file(s)?"))? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let mut backup = BackupStore::for_mods_dir(&plan.mods_dir()).begin("mrpack import")?;
+    plan.apply(&mut backup)?;
+    print!("Wrote {changed} file(s).");
+    if !backup.is_empty() {
+        print!(
+            " Overwritten files saved as backup {} (undo with `restore {}`).",
+            backup.id, backup.id
+        );
+    }
+    println!();
+    Ok(())
+}
+
+fn export(args: MrpackExportArgs, ctx: Context) -> Result<(), Box<dyn Error>> {
+    let mods_dir = fs::canonicalize(&args.mods_dir)?;
+    let mut dependencies = instances::discover()
+        .into_iter()
+        .find(|i| fs::canonicalize(&i.mods_dir).is_ok_and(|dir| dir == mods_dir))
+        .map(|i| mrpack::instance_dependencies(&i))
+        .unwrap_or_default();
+    if let Some(minecraft) = args.minecraft {
+        dependencies.insert("minecraft".to_string(), minecraft);
+    }
+    if let Some(loader) = args.loader {
+        let (id, version) = loader
+            .split_once('=')
+            .ok_or("--loader must look like fabric-loader=0.15.11")?;
+        let id = mrpack::loader_id(id).ok_or_else(|| format!("unknown loader '{id}'"))?;
+        dependencies.retain(|k, _| k == "minecraft");
+        dependencies.insert(id.to_string(), version.to_string());
+    }
+    if !dependencies.contains_key("minecraft") {
+        return Err("could not tell the Minecraft version; pass --minecraft".into());
+    }
+
+    let options = ExportOptions {
+        name: args.name,
+        version_id: args.version_id,
+        summary: args.summary,
+        dependencies,
+    };
+    let known = mrpack::saved_index(&mods_dir);
+    let (data, summary) = mrpack::export(&mods_dir, &options, known.as_ref())?;
+
+    for name in &summary.indexed {
+        println!("  linked | {name}");
+    }
+    for name in &summary.embedded {
+        println!("embedded | {name}");
+    }
+    println!();
+
+    if ctx.dry_run {
+        println!(
+            "Dry run, nothing was changed. Would write {} ({} bytes).",
+            args.output.display(),
+            data.len()
+        );
+        return Ok(());
+    }
+    if args.output.exists() && !ctx.confirm(&format!("Overwrite {}?", args.output.display()))? {
+        println!("Aborted.");
+        return Ok(());
+    }
+    fsio::write_atomic(&args.output, &data)?;
+    println!(
+        "Wrote {} with {} linked and {} embedded jar(s).",
+        args.output.display(),
+        summary.indexed.len(),
+        summary.embedded.len()
+    );
+    Ok(())
+}
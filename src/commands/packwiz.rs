@@ -0,0 +1,154 @@
+use std::error::Error;
+
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::cache::FileCache;
+use minecraft_mod_replacer::mrpack::Side;
+use minecraft_mod_replacer::packwiz::{self, FileStatus, Pack, Status};
+
+use crate::cli::{PackwizArgs, PackwizCommand, PackwizSyncArgs};
+use crate::commands::Context;
+
+pub fn run(command: PackwizCommand, ctx: Context) -> Result<(), Box<dyn Error>> {
+    match command {
+        PackwizCommand::Status(args) => {
+            let (_, status) = load(&args)?;
+            print_status(&status);
+            Ok(())
+        }
+        PackwizCommand::Sync(args) => sync(args, ctx),
+    }
+}
+
+fn load(args: &PackwizArgs) -> Result<(Pack, Status), Box<dyn Error>> {
+    let pack = Pack::load(&args.pack)?;
+    let side = if args.server {
+        Side::Server
+    } else {
+        Side::Client
+    };
+    let status = packwiz::status(&pack, &args.game_dir, side)?;
+
+    let versions: Vec<String> = pack
+        .pack
+        .versions
+        .iter()
+        .map(|(id, version)| format!("{id} {version}"))
+        .collect();
+    println!(
+        "{} {} | {}",
+        pack.pack.name,
+        pack.pack.version.as_deref().unwrap_or(""),
+        versions.join(", ")
+    );
+    println!();
+    Ok((pack, status))
+}
+
+fn print_status(status: &Status) {
+    let (mut ok, mut modified, mut missing, mut unverifiable) = (0, 0, 0, 0);
+    for (file, file_status) in &status.files {
+        let label = match file_status {
+            FileStatus::Ok => {
+                ok += 1;
+                continue;
+            }
+            FileStatus::Preserved => continue,
+            FileStatus::Modified => {
+                modified += 1;
+                "modified"
+            }
+            FileStatus::Missing => {
+                missing += 1;
+                " missing"
+            }
+            FileStatus::Unverifiable => {
+                unverifiable += 1;
+                "no check"
+            }
+        };
+        match &file.name {
+            Some(name) => println!("{label} | {} ({name})", file.path),
+            None => println!("{label} | {}", file.path),
+        }
+    }
+    for path in &status.extra {
+        println!("   extra | {}", path.display());
+    }
+    println!();
+    println!(
+        "{ok} ok, {modified} modified, {missing} missing, {} not in the pack.",
+        status.extra.len()
+    );
+    if unverifiable > 0 {
+        eprintln!(
+            "⚠️  {unverifiable} file(s) are hashed with a format this tool cannot check, such as md5"
+        );
+    }
+}
+
+fn sync(args: PackwizSyncArgs, ctx: Context) -> Result<(), Box<dyn Error>> {
+    let (pack, status) = load(&args.pack)?;
+    if status.is_clean() {
+        println!("{} already matches the pack.", args.pack.game_dir.display());
+        return Ok(());
+    }
+    let cache = FileCache::open(&args.cache, &pack.hash_formats())?;
+    let plan = status.plan_sync(&cache)?;
+
+    for write in &plan.writes {
+        let action = if write.replaces { "replace" } else { "  write" };
+        println!("{action} | {}", write.path.display());
+    }
+    for path in &plan.removals {
+        println!(" remove | {}", path.display());
+    }
+    for path in &plan.unavailable {
+        eprintln!("⚠️  not in the cache, or cannot be verified: {path}");
+    }
+    println!();
+
+    if ctx.dry_run {
+        println!(
+            "Dry run, nothing was changed. Would write {} and remove {} file(s).",
+            plan.writes.len(),
+            plan.removals.len()
+        );
+        return Ok(());
+    }
+    if plan.is_empty() {
+        println!("Nothing to do.");
+        return Ok(());
+    }
+    if !ctx.confirm(&format!(
+        "Write {} and remove {} file(s)?",
+        plan.writes.len(),
+        plan.removals.len()
+    ))? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let mods_dir = args.pack.game_dir.join("mods");
+    let mut backup = BackupStore::for_mods_dir(&mods_dir).begin("packwiz sync")?;
+    plan.apply(&mut backup)?;
+    print!(
+        "Wrote {} and removed {} file(s).",
+        plan.writes.len(),
+        plan.removals.len()
+    );
+    if !backup.is_empty() {
+        print!(
+            " Overwritten and removed files saved as backup {} (undo with `restore {}`).",
+            backup.id, backup.id
+        );
+    }
+    println!();
+    if !plan.unavailable.is_empty() {
+        return Err(format!(
+            "{} file(s) could not be found in the cache",
+            plan.unavailable.len()
+        )
+        .into());
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use minecraft_mod_replacer::mods::file_name;
+use minecraft_mod_replacer::profiles::{self, ProfilesFile, SwitchAction, SwitchPlan};
+
+use crate::cli::{ProfileArgs, ProfileCommand, ProfileSwitchArgs};
+use crate::commands::Context;
+
+pub fn run(command: ProfileCommand, ctx: Context) -> Result<(), Box<dyn Error>> {
+    match command {
+        ProfileCommand::List(args) => list(args),
+        ProfileCommand::Switch(args) => switch(args, ctx),
+    }
+}
+
+fn load(args: &ProfileArgs) -> Result<(PathBuf, ProfilesFile), Box<dyn Error>> {
+    let path = args
+        .profiles
+        .clone()
+        .unwrap_or_else(|| profiles::default_path(&args.mods_dir));
+    if !path.is_file() {
+        return Err(format!("no profiles file at {}", path.display()).into());
+    }
+    let file = ProfilesFile::load(&path)?;
+    Ok((path, file))
+}
+
+fn list(args: ProfileArgs) -> Result<(), Box<dyn Error>> {
+    let (path, file) = load(&args)?;
+    if file.profiles.is_empty() {
+        println!("No profiles in {}", path.display());
+        return Ok(());
+    }
+    for (name, profile) in &file.profiles {
+        let current = file.plan_switch(name, &args.mods_dir, None)?.is_current();
+        println!(
+            "{} {name} | {} mod(s){}",
+            if current { "*" } else { " " },
+            profile.mods.len(),
+            profile
+                .description
+                .as_deref()
+                .map(|d| format!(" | {d}"))
+                .unwrap_or_default()
+        );
+    }
+    if !file.common.is_empty() {
+        println!();
+        println!("In every profile: {}", file.common.join(", "));
+    }
+    Ok(())
+}
+
+fn switch(args: ProfileSwitchArgs, ctx: Context) -> Result<(), Box<dyn Error>> {
+    let mods_dir = &args.profiles.mods_dir;
+    let (_, file) = load(&args.profiles)?;
+    let plan = file.plan_switch(&args.name, mods_dir, args.store.as_deref())?;
+    if plan.is_current() {
+        println!(
+            "{} already matches profile '{}'.",
+            mods_dir.display(),
+            args.name
+        );
+        return Ok(());
+    }
+
+    print_plan(&plan);
+    for problem in plan.dependency_problems(mods_dir)? {
+        let marker = if problem.is_error() { "✗" } else { "⚠️ " };
+        eprintln!("{marker} {problem}");
+    }
+    if plan.actions.is_empty() && plan.conflicts.is_empty() {
+        return Err("nothing can be changed until the missing mods are in the store".into());
+    }
+    println!();
+    if ctx.dry_run {
+        println!(
+            "Dry run, nothing was changed. Would make {} change(s).",
+            plan.actions.len()
+        );
+        return Ok(());
+    }
+    if !plan.conflicts.is_empty() {
+        return Err(format!(
+            "{} jar(s) from the store would overwrite other files; rename or remove those first",
+            plan.conflicts.len()
+        )
+        .into());
+    }
+    if !ctx.confirm(&format!("Switch to profile '{}'?", args.name))? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    plan.apply()?;
+    println!(
+        "Switched {} to profile '{}' ({} change(s)).",
+        mods_dir.display(),
+        args.name,
+        plan.actions.len()
+    );
+    Ok(())
+}
+
+fn print_plan(plan: &SwitchPlan) {
+    println!("Switching to profile '{}':", plan.profile);
+    for action in &plan.actions {
+        match action {
+            SwitchAction::Enable(path) => println!("  enable  {}", file_name(path)),
+            SwitchAction::Disable(path) => println!("  disable {}", file_name(path)),
+            SwitchAction::Install { from, .. } => {
+                println!("  install {} (from {})", file_name(from), from.display())
+            }
+        }
+    }
+    for entry in &plan.unresolved {
+        eprintln!("⚠️  {entry} is neither in the mods folder nor in the store");
+    }
+    for conflict in &plan.conflicts {
+        eprintln!(
+            "✗ {} cannot be installed from {}: {} is already taken by a jar that is not {}",
+            conflict.entry,
+            conflict.from.display(),
+            file_name(&conflict.to),
+            conflict.entry
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use minecraft_mod_replacer::backup::{BackupSet, BackupStore};
+use minecraft_mod_replacer::compat::VersionChange;
+use minecraft_mod_replacer::mods;
+use minecraft_mod_replacer::{PaddingStrategy, ReplaceOptions, ReplacePlan};
+
+use crate::cli::ReplaceArgs;
+use crate::commands::Context;
+
+pub fn run(args: ReplaceArgs, ctx: Context) -> Result<(), Box<dyn Error>> {
+    let target = mods::resolve_target(&args.mods_dir, &args.target)?;
+    if ctx.dry_run {
+        let replacement_size = fs::metadata(&args.replacement)?.len();
+        println!("Candidates in {}:", args.mods_dir.display());
+        for candidate in mods::find_candidates(&args.mods_dir, replacement_size)? {
+            let marker = if candidate.path == target { "→" } else { " " };
+            println!(
+                "{marker} {}",
+                crate::interactive::candidate_label(&candidate, replacement_size)
+            );
+        }
+        println!();
+    }
+
+    let options = ReplaceOptions {
+        allow_append: !args.no_append,
+        backup: !args.no_backup,
+        force: args.force,
+    };
+    let plan = ReplacePlan::new(&target, &args.replacement, &options)?;
+
+    print_plan(&plan);
+    if ctx.dry_run {
+        print_dry_run(&plan);
+        return Ok(());
+    }
+    if plan.is_blocked() {
+        return Err(format!("{}; pass --force to replace anyway", refusal(&plan)).into());
+    }
+    let prompt = format!("Replace '{}'?", mods::file_name(&plan.target));
+    if !ctx.confirm(&prompt)? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let backup = plan.execute()?;
+    print_replaced(&plan, backup.as_ref());
+    Ok(())
+}
+
+pub fn print_plan(plan: &ReplacePlan) {
+    println!("Target:      {}", plan.target.display());
+    println!(
+        "             {}",
+        super::describe_mod(plan.target_metadata.as_ref())
+    );
+    println!("Replacement: {}", plan.replacement.display());
+    println!(
+        "             {}",
+        super::describe_mod(plan.replacement_metadata.as_ref())
+    );
+    println!(
+        "Size:        {} → {} bytes ({} bytes padding)",
+        plan.replacement_size,
+        plan.original_size,
+        plan.padding()
+    );
+    if let Some(delta) = &plan.version_change {
+        println!(
+            "Version:     {} → {} ({})",
+            delta.from, delta.to, delta.change
+        );
+    }
+    for mismatch in &plan.mismatches {
+        eprintln!("⚠️  Warning: {mismatch}");
+    }
+    if let Some(delta) = &plan.version_change
+        && delta.change == VersionChange::Downgrade
+    {
+        eprintln!("⚠️  Warning: this downgrades {}", delta.mod_id);
+    }
+    for problem in &plan.dependency_problems {
+        eprintln!("⚠️  Dependency: {problem}");
+    }
+    match plan.strategy {
+        PaddingStrategy::None => println!("Padding:     none needed"),
+        PaddingStrategy::ZipComment => println!("Padding:     ZIP comment"),
+        PaddingStrategy::Append => {
+            println!("Padding:     simple append");
+            if let Some(err) = &plan.comment_error {
+                eprintln!("{}", err);
+            }
+            eprintln!("⚠️  Warning: Could not pad using ZIP comment. Using simple append method.");
+            eprintln!(
+                "This may cause issues with strict ZIP parsers, but often works in practice."
+            );
+        }
+    }
+}
+
+/// Why `execute` would refuse a blocked plan.
+fn refusal(plan: &ReplacePlan) -> &'static str {
+    if plan.blocking_mismatches().is_empty() {
+        "the replacement breaks other mods' dependencies"
+    } else {
+        "the replacement does not match the target"
+    }
+}
+
+/// What `execute` would do, without doing it.
+pub fn print_dry_run(plan: &ReplacePlan) {
+    println!();
+    println!("Dry run, nothing was changed. Would:");
+    if plan.is_blocked() {
+        println!("  refuse to replace, {} (needs --force)", refusal(plan));
+        return;
+    }
+    if plan.backup {
+        let store = BackupStore::for_mods_dir(plan.target.parent().unwrap_or(Path::new(".")));
+        println!(
+            "  back up the original into {}",
+            store.root().join("<timestamp>").display()
+        );
+    }
+    println!(
+        "  write {} bytes to {} through a temp file in the same folder",
+        plan.output().len(),
+        plan.target.display()
+    );
+    match plan.verify_output() {
+        Ok(report) if report.is_ok() => {
+            println!("  pass verification ({} entries)", report.entries)
+        }
+        Ok(report) => {
+            println!("  FAIL verification and leave the target untouched:");
+            for problem in &report.problems {
+                println!("    {problem}");
+            }
+        }
+        Err(err) => println!("  FAIL verification and leave the target untouched: {err}"),
+    }
+}
+
+pub fn print_replaced(plan: &ReplacePlan, backup: Option<&BackupSet>) {
+    println!(
+        "Replaced '{}' with '{}'. Padded from {} → {} bytes.",
+        mods::file_name(&plan.target),
+        plan.replacement.display(),
+        plan.replacement_size,
+        plan.original_size
+    );
+    if let Some(backup) = backup {
+        println!(
+            "Original saved as backup {} (undo with `restore {}`).",
+            backup.id, backup.id
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use std::error::Error;
+
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::mods::file_name;
+
+use crate::cli::RestoreArgs;
+use crate::commands::Context;
+
+pub fn run(args: RestoreArgs, ctx: Context) -> Result<(), Box<dyn Error>> {
+    let store = BackupStore::for_mods_dir(&args.mods_dir);
+
+    let Some(id) = args.id else {
+        let sets = store.list()?;
+        if sets.is_empty() {
+            println!("No backups in {}", store.root().display());
+        }
+        for set in sets {
+            println!(
+                "{} | {} | {} file(s)",
+                set.id,
+                set.manifest.operation,
+                set.manifest.entries.len()
+            );
+            for entry in &set.manifest.entries {
+                match &entry.replaced_with {
+                    Some(with) => println!(
+                        "    {} (replaced with {})",
+                        file_name(&entry.original_path),
+                        with.display()
+                    ),
+                    None => println!("    {}", file_name(&entry.original_path)),
+                }
+            }
+        }
+        return Ok(());
+    };
+
+    let set = store.get(&id)?;
+    let entries: Vec<_> = set
+        .manifest
+        .entries
+        .iter()
+        .filter(|e| {
+            args.file
+                .as_ref()
+                .is_none_or(|name| file_name(&e.original_path) == *name)
+        })
+        .collect();
+    if entries.is_empty() {
+        return Err(format!("backup {id} has no matching files").into());
+    }
+
+    for entry in &entries {
+        println!("{} → {}", entry.stored_as, entry.original_path.display());
+    }
+    if ctx.dry_run {
+        println!("Dry run: {} file(s) would be restored.", entries.len());
+        return Ok(());
+    }
+    if !ctx.confirm(&format!("Restore {} file(s)?", entries.len()))? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let mut displaced = store.begin("restore")?;
+    for entry in entries {
+        set.restore(entry, &mut displaced)?;
+        println!("Restored {}", entry.original_path.display());
+    }
+    if !displaced.is_empty() {
+        println!("Overwritten files saved as backup {}.", displaced.id);
+    }
+    Ok(())
+}
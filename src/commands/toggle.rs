@@ -0,0 +1,35 @@
+use std::collections::HashSet;
+use std::error::Error;
+
+use minecraft_mod_replacer::mods::{self, file_name};
+
+use crate::cli::ToggleArgs;
+use crate::commands::Context;
+
+pub fn run(args: ToggleArgs, enable: bool, ctx: Context) -> Result<(), Box<dyn Error>> {
+    let mut paths = args
+        .mods
+        .iter()
+        .map(|name| mods::resolve_target_or_id(&args.mods_dir, name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut seen = HashSet::new();
+    paths.retain(|path| seen.insert(path.clone()));
+
+    for path in paths {
+        if mods::is_disabled(&path) != enable {
+            println!(
+                "{} is already {}",
+                file_name(&path),
+                if enable { "enabled" } else { "disabled" }
+            );
+            continue;
+        }
+        if ctx.dry_run {
+            println!("Would rename {}", file_name(&path));
+            continue;
+        }
+        let new_path = mods::set_enabled(&path, enable)?;
+        println!("{} → {}", file_name(&path), file_name(&new_path));
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use std::error::Error;
+
+use dialoguer::{Confirm, Select};
+use minecraft_mod_replacer::ReplaceOptions;
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::batch;
+use minecraft_mod_replacer::compat::VersionChange;
+use minecraft_mod_replacer::mods::file_name;
+use minecraft_mod_replacer::upgrade::{self, Suggestions, UpgradePair};
+
+use crate::cli::UpgradeArgs;
+use crate::commands::Context;
+
+pub fn run(args: UpgradeArgs, ctx: Context) -> Result<(), Box<dyn Error>> {
+    let suggestions = upgrade::suggest(&args.mods_dir, &args.from)?;
+    print_suggestions(&suggestions);
+
+    let applicable: Vec<&UpgradePair> = suggestions.pairs.iter().filter(|p| p.fits()).collect();
+    if applicable.is_empty() {
+        println!("Nothing to upgrade.");
+        return Ok(());
+    }
+
+    // Downgrades and same-version pairs only go in when picked one by one
+    let upgrades: Vec<&UpgradePair> = applicable
+        .iter()
+        .copied()
+        .filter(|p| p.change == VersionChange::Upgrade)
+        .collect();
+    let chosen = if ctx.yes || ctx.dry_run {
+        if upgrades.len() < applicable.len() {
+            eprintln!(
+                "⚠️  skipping {} pair(s) that are not upgrades; run without --yes to pick them",
+                applicable.len() - upgrades.len()
+            );
+        }
+        upgrades
+    } else {
+        let options = [
+            format!("Accept all {} upgrade(s)", upgrades.len()),
+            "Pick pairs one by one".to_string(),
+            "Cancel".to_string(),
+        ];
+        match Select::new().items(&options).default(0).interact()? {
+            0 => upgrades,
+            1 => pick(applicable)?,
+            _ => {
+                println!("Aborted.");
+                return Ok(());
+            }
+        }
+    };
+    if chosen.is_empty() {
+        println!("Nothing selected.");
+        return Ok(());
+    }
+
+    let options = ReplaceOptions {
+        allow_append: !args.no_append,
+        backup: true,
+        force: args.force,
+    };
+    let batch = upgrade::plan(&chosen).prepare(&args.mods_dir, &options)?;
+    let blocked = super::apply::print_plans(&batch);
+    if blocked > 0 {
+        return Err(format!(
+            "{blocked} replacement(s) do not match their target or break dependencies; pass --force to replace them anyway"
+        )
+        .into());
+    }
+    if ctx.dry_run {
+        println!(
+            "Dry run, nothing was changed. Would replace {} jar(s) in {}.",
+            batch.plans.len(),
+            args.mods_dir.display()
+        );
+        return Ok(());
+    }
+    if !ctx.confirm(&format!("Apply {} replacement(s)?", batch.plans.len()))? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let backup = batch::apply(&batch, &BackupStore::for_mods_dir(&args.mods_dir))?;
+    println!(
+        "Replaced {} jar(s). Originals saved as backup {} (undo with `restore {}`).",
+        batch.plans.len(),
+        backup.id,
+        backup.id
+    );
+    Ok(())
+}
+
+fn print_suggestions(suggestions: &Suggestions) {
+    for pair in &suggestions.pairs {
+        println!("{}", label(pair));
+        if !pair.fits() {
+            eprintln!(
+                "    ⚠️  {} is larger than {} ({} > {} bytes) and cannot replace it",
+                pair.update.file_name(),
+                pair.installed.file_name(),
+                pair.update.size,
+                pair.installed.size
+            );
+        } else if pair.change == VersionChange::Downgrade {
+            eprintln!("    ⚠️  the new jar is older than the installed one");
+        }
+    }
+    for path in &suggestions.unmatched {
+        println!("not installed | {}", file_name(path));
+    }
+    for path in &suggestions.superseded {
+        println!("older copy    | {}", file_name(path));
+    }
+    for (mod_id, paths) in &suggestions.ambiguous {
+        let names: Vec<String> = paths.iter().map(|p| file_name(p)).collect();
+        eprintln!(
+            "⚠️  {mod_id} is installed as several jars ({}); run `duplicates` first",
+            names.join(", ")
+        );
+    }
+    println!();
+}
+
+fn label(pair: &UpgradePair) -> String {
+    format!(
+        "{} | {} → {} | {} → {} ({})",
+        pair.mod_id,
+        pair.installed.file_name(),
+        pair.update.file_name(),
+        pair.from.as_deref().unwrap_or("?"),
+        pair.to.as_deref().unwrap_or("?"),
+        pair.change
+    )
+}
+
+/// Asks about each pair, suggesting yes for upgrades only.
+fn pick(pairs: Vec<&UpgradePair>) -> Result<Vec<&UpgradePair>, Box<dyn Error>> {
+    let mut chosen = Vec::new();
+    for pair in pairs {
+        if Confirm::new()
+            .with_prompt(format!("Replace? {}", label(pair)))
+            .default(pair.change == VersionChange::Upgrade)
+            .interact()?
+        {
+            chosen.push(pair);
+        }
+    }
+    Ok(chosen)
+}
@@ -0,0 +1,31 @@
+use std::error::Error;
+use std::fs;
+
+use minecraft_mod_replacer::zip;
+
+use crate::cli::VerifyArgs;
+
+pub fn run(args: VerifyArgs) -> Result<(), Box<dyn Error>> {
+    let data = fs::read(&args.jar)?;
+    let report = zip::verify(&data)?;
+
+    for problem in &report.problems {
+        eprintln!("✗ {}", problem);
+    }
+    if !report.is_ok() {
+        return Err(format!(
+            "{}: {} of {} entries failed verification",
+            args.jar.display(),
+            report.problems.len(),
+            report.entries
+        )
+        .into());
+    }
+
+    println!(
+        "{}: OK ({} entries verified)",
+        args.jar.display(),
+        report.entries
+    );
+    Ok(())
+}
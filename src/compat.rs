@@ -0,0 +1,189 @@
+//! Checks that a replacement jar is plausibly the same mod as its target.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::metadata::{JarMetadata, Loader};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The jars declare no mod id in common.
+    ModId {
+        target: Vec<String>,
+        replacement: Vec<String>,
+    },
+    /// The jars are built for different loaders.
+    Loader {
+        target: Vec<Loader>,
+        replacement: Vec<Loader>,
+    },
+    /// No Minecraft version satisfies both jars.
+    Minecraft { target: String, replacement: String },
+    /// One of the jars has no readable metadata, so nothing could be compared.
+    Unknown { which: &'static str },
+}
+
+impl Mismatch {
+    /// Whether this blocks a replacement unless it is forced. Missing
+    /// metadata only warrants a warning.
+    pub fn is_blocking(&self) -> bool {
+        !matches!(self, Self::Unknown { .. })
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ModId {
+                target,
+                replacement,
+            } => write!(
+                f,
+                "different mod: target is '{}', replacement is '{}'",
+                target.join("', '"),
+                replacement.join("', '")
+            ),
+            Self::Loader {
+                target,
+                replacement,
+            } => write!(
+                f,
+                "different loader: target is for {}, replacement is for {}",
+                join(target),
+                join(replacement)
+            ),
+            Self::Minecraft {
+                target,
+                replacement,
+            } => write!(
+                f,
+                "Minecraft versions do not overlap: target wants {target}, replacement wants {replacement}"
+            ),
+            Self::Unknown { which } => write!(f, "could not read the {which}'s mod metadata"),
+        }
+    }
+}
+
+/// How the replacement's version of a mod relates to the target's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionChange {
+    Upgrade,
+    Downgrade,
+    Same,
+    /// The versions follow no scheme the loader can order.
+    Incomparable,
+}
+
+impl fmt::Display for VersionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Upgrade => "upgrade",
+            Self::Downgrade => "downgrade",
+            Self::Same => "same version",
+            Self::Incomparable => "versions cannot be compared",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionDelta {
+    pub mod_id: String,
+    pub from: String,
+    pub to: String,
+    pub change: VersionChange,
+}
+
+/// Compares the versions of the first mod both jars declare for the same
+/// loader, the way that loader orders versions.
+pub fn version_change(target: &JarMetadata, replacement: &JarMetadata) -> Option<VersionDelta> {
+    target.mods.iter().find_map(|t| {
+        let r = replacement
+            .mods
+            .iter()
+            .find(|r| r.id == t.id && r.loader == t.loader)?;
+        let (from, to) = (t.version.clone()?, r.version.clone()?);
+        let change = match (t.parsed_version(), r.parsed_version()) {
+            (Some(a), Some(b)) => match a.compare(&b) {
+                Some(Ordering::Less) => VersionChange::Upgrade,
+                Some(Ordering::Greater) => VersionChange::Downgrade,
+                Some(Ordering::Equal) => VersionChange::Same,
+                None => VersionChange::Incomparable,
+            },
+            _ => VersionChange::Incomparable,
+        };
+        Some(VersionDelta {
+            mod_id: t.id.clone(),
+            from,
+            to,
+            change,
+        })
+    })
+}
+
+fn join(loaders: &[Loader]) -> String {
+    loaders
+        .iter()
+        .map(Loader::to_string)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Compares the mods in `replacement` against those in `target`.
+pub fn compare(target: Option<&JarMetadata>, replacement: Option<&JarMetadata>) -> Vec<Mismatch> {
+    let (target, replacement) = match (target, replacement) {
+        (Some(t), Some(r)) if !t.is_empty() && !r.is_empty() => (t, r),
+        (None, _) => return vec![Mismatch::Unknown { which: "target" }],
+        (Some(t), _) if t.is_empty() => return vec![Mismatch::Unknown { which: "target" }],
+        _ => {
+            return vec![Mismatch::Unknown {
+                which: "replacement",
+            }];
+        }
+    };
+
+    let mut mismatches = Vec::new();
+
+    let shares_id = target
+        .mods
+        .iter()
+        .any(|t| replacement.mods.iter().any(|r| r.id == t.id));
+    if !shares_id {
+        mismatches.push(Mismatch::ModId {
+            target: target.mods.iter().map(|m| m.id.clone()).collect(),
+            replacement: replacement.mods.iter().map(|m| m.id.clone()).collect(),
+        });
+    }
+
+    let target_loaders = target.loaders();
+    let replacement_loaders = replacement.loaders();
+    if !target_loaders
+        .iter()
+        .any(|l| replacement_loaders.contains(l))
+    {
+        mismatches.push(Mismatch::Loader {
+            target: target_loaders,
+            replacement: replacement_loaders,
+        });
+    }
+
+    // Compare Minecraft ranges of the same mod on the same loader
+    for t in &target.mods {
+        let Some(r) = replacement
+            .mods
+            .iter()
+            .find(|r| r.id == t.id && r.loader == t.loader)
+        else {
+            continue;
+        };
+        if let (Some(t_range), Some(r_range)) = (t.minecraft_range(), r.minecraft_range())
+            && !t_range.overlaps(&r_range)
+        {
+            mismatches.push(Mismatch::Minecraft {
+                target: t.minecraft.clone().unwrap_or_default(),
+                replacement: r.minecraft.clone().unwrap_or_default(),
+            });
+        }
+    }
+
+    mismatches
+}
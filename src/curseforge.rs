@@ -0,0 +1,299 @@
+//! CurseForge modpack exports: a `manifest.json` listing mods by
+//! `projectID`/`fileID`, plus an `overrides/` folder.
+//!
+//! Local jars are matched to manifest entries offline, by their CurseForge
+//! fingerprint. The fingerprints of known files come from the
+//! `minecraftinstance.json` the CurseForge app keeps next to the mods
+//! folder.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::hashes::curseforge_fingerprint;
+use crate::mods::{self, Candidate};
+use crate::zip::Archive;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+pub const INSTANCE_FILE: &str = "minecraftinstance.json";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub minecraft: MinecraftInfo,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub files: Vec<ManifestFile>,
+    #[serde(default = "default_overrides")]
+    pub overrides: String,
+}
+
+fn default_overrides() -> String {
+    "overrides".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinecraftInfo {
+    pub version: String,
+    #[serde(default)]
+    pub mod_loaders: Vec<ModLoader>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModLoader {
+    /// Loader and version, e.g. `forge-47.2.0`.
+    pub id: String,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestFile {
+    #[serde(rename = "projectID")]
+    pub project_id: u64,
+    #[serde(rename = "fileID")]
+    pub file_id: u64,
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// A CurseForge export: the manifest plus the jars under
+/// `overrides/mods/`.
+#[derive(Debug, Clone)]
+pub struct Pack {
+    pub manifest: Manifest,
+    /// File name and content of each override jar.
+    pub override_mods: Vec<(String, Vec<u8>)>,
+}
+
+impl Pack {
+    /// Reads an exported pack zip, or a bare `manifest.json`.
+    pub fn read(path: &Path) -> Result<Self> {
+        let data = fs::read(path)?;
+        let Ok(archive) = Archive::parse(&data) else {
+            return Ok(Self {
+                manifest: parse_manifest(&data)?,
+                override_mods: Vec::new(),
+            });
+        };
+
+        let manifest = parse_manifest(
+            &archive
+                .read_file(MANIFEST_FILE)?
+                .ok_or_else(|| Error::InvalidPack(format!("no {MANIFEST_FILE}")))?,
+        )?;
+        let prefix = format!("{}/mods/", manifest.overrides.trim_end_matches('/'));
+        let mut override_mods = Vec::new();
+        for entry in &archive.entries {
+            if let Some(name) = entry.name.strip_prefix(&prefix)
+                && !name.is_empty()
+                && !name.contains('/')
+            {
+                override_mods.push((name.to_string(), archive.read(entry)?));
+            }
+        }
+        Ok(Self {
+            manifest,
+            override_mods,
+        })
+    }
+}
+
+fn parse_manifest(data: &[u8]) -> Result<Manifest> {
+    serde_json::from_slice(data)
+        .map_err(|err| Error::InvalidPack(format!("{MANIFEST_FILE}: {err}")))
+}
+
+/// A CurseForge file whose fingerprint is known locally.
+#[derive(Debug, Clone)]
+pub struct KnownFile {
+    pub project_id: u64,
+    pub file_id: u64,
+    pub file_name: String,
+    pub fingerprint: u32,
+}
+
+/// Reads the installed files recorded in a CurseForge app
+/// `minecraftinstance.json`.
+pub fn read_known_files(path: &Path) -> Result<Vec<KnownFile>> {
+    let json: Value = serde_json::from_slice(&fs::read(path)?)
+        .map_err(|err| Error::InvalidPack(format!("{INSTANCE_FILE}: {err}")))?;
+    let addons = json
+        .get("installedAddons")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    Ok(addons
+        .iter()
+        .filter_map(|addon| {
+            let file = addon.get("installedFile")?;
+            Some(KnownFile {
+                project_id: addon.get("addonID")?.as_u64()?,
+                file_id: file.get("id")?.as_u64()?,
+                file_name: file.get("fileName")?.as_str()?.to_string(),
+                fingerprint: u32::try_from(file.get("packageFingerprint")?.as_u64()?).ok()?,
+            })
+        })
+        .collect())
+}
+
+/// Where `minecraftinstance.json` would be for `mods_dir`, if it exists.
+pub fn instance_file(mods_dir: &Path) -> Option<PathBuf> {
+    let path = mods_dir.parent()?.join(INSTANCE_FILE);
+    path.is_file().then_some(path)
+}
+
+/// A local jar with its fingerprint and, when known, its CurseForge file.
+#[derive(Debug, Clone)]
+pub struct LocalJar {
+    pub candidate: Candidate,
+    pub fingerprint: u32,
+    pub known: Option<KnownFile>,
+}
+
+#[derive(Debug, Clone)]
+pub enum EntryStatus {
+    /// A jar with exactly this file's fingerprint is installed.
+    Present(PathBuf),
+    /// The project is installed, but as another file: a different version,
+    /// or the expected file name with different content.
+    Different {
+        path: PathBuf,
+        installed_file_id: Option<u64>,
+    },
+    Missing,
+}
+
+#[derive(Debug, Clone)]
+pub enum OverrideStatus {
+    Present(PathBuf),
+    Different(PathBuf),
+    Missing,
+}
+
+#[derive(Debug, Clone)]
+pub struct Status {
+    pub entries: Vec<(ManifestFile, EntryStatus)>,
+    pub overrides: Vec<(String, OverrideStatus)>,
+    /// Jars that belong to no manifest entry or override.
+    pub extra: Vec<LocalJar>,
+}
+
+/// Fingerprints every jar in `mods_dir`, disabled ones included.
+pub fn scan(mods_dir: &Path, known: &[KnownFile]) -> Result<Vec<LocalJar>> {
+    mods::list_jars(mods_dir)?
+        .into_iter()
+        .map(|candidate| {
+            let fingerprint = curseforge_fingerprint(&fs::read(&candidate.path)?);
+            let known = known.iter().find(|k| k.fingerprint == fingerprint).cloned();
+            Ok(LocalJar {
+                candidate,
+                fingerprint,
+                known,
+            })
+        })
+        .collect()
+}
+
+/// Compares a pack against the jars of a mods folder.
+pub fn status(pack: &Pack, jars: Vec<LocalJar>, known: &[KnownFile]) -> Status {
+    let mut claimed = vec![false; jars.len()];
+    let mut claim = |pred: &dyn Fn(&LocalJar) -> bool| {
+        let i = jars
+            .iter()
+            .enumerate()
+            .position(|(i, jar)| !claimed[i] && pred(jar))?;
+        claimed[i] = true;
+        Some(i)
+    };
+
+    // Exact matches go first, so that a looser match never takes a jar
+    // that another entry or override matches exactly
+    let exact: Vec<Option<usize>> = pack
+        .manifest
+        .files
+        .iter()
+        .map(|file| {
+            claim(&|jar| {
+                jar.known
+                    .as_ref()
+                    .is_some_and(|k| k.project_id == file.project_id && k.file_id == file.file_id)
+            })
+        })
+        .collect();
+    let identical: Vec<Option<usize>> = pack
+        .override_mods
+        .iter()
+        .map(|(_, data)| {
+            let fingerprint = curseforge_fingerprint(data);
+            claim(&|jar| jar.fingerprint == fingerprint)
+        })
+        .collect();
+
+    let mut entries = Vec::new();
+    for (file, exact) in pack.manifest.files.iter().zip(exact) {
+        let status = if let Some(i) = exact {
+            EntryStatus::Present(jars[i].candidate.path.clone())
+        } else if let Some(i) = claim(&|jar| {
+            jar.known
+                .as_ref()
+                .is_some_and(|k| k.project_id == file.project_id)
+        }) {
+            EntryStatus::Different {
+                path: jars[i].candidate.path.clone(),
+                installed_file_id: jars[i].known.as_ref().map(|k| k.file_id),
+            }
+        } else if let Some(expected) = known
+            .iter()
+            .find(|k| k.project_id == file.project_id && k.file_id == file.file_id)
+            && let Some(i) =
+                claim(&|jar| mods::enabled_name(&jar.candidate.path) == expected.file_name)
+        {
+            EntryStatus::Different {
+                path: jars[i].candidate.path.clone(),
+                installed_file_id: None,
+            }
+        } else {
+            EntryStatus::Missing
+        };
+        entries.push((file.clone(), status));
+    }
+
+    let mut overrides = Vec::new();
+    for ((name, _), identical) in pack.override_mods.iter().zip(identical) {
+        let status = if let Some(i) = identical {
+            OverrideStatus::Present(jars[i].candidate.path.clone())
+        } else if let Some(i) = claim(&|jar| mods::enabled_name(&jar.candidate.path) == *name) {
+            OverrideStatus::Different(jars[i].candidate.path.clone())
+        } else {
+            OverrideStatus::Missing
+        };
+        overrides.push((name.clone(), status));
+    }
+
+    let extra = jars
+        .into_iter()
+        .zip(claimed)
+        .filter(|(_, claimed)| !claimed)
+        .map(|(jar, _)| jar)
+        .collect();
+    Status {
+        entries,
+        overrides,
+        extra,
+    }
+}
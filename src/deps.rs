@@ -0,0 +1,312 @@
+//! Dependency checking across a whole mods folder.
+//!
+//! Every mod's declared dependencies, recommendations and
+//! incompatibilities are resolved against the other mods in the folder.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::metadata::{self, Dependency, DependencyKind, JarMetadata, Loader, ModMetadata};
+use crate::mods::{self, file_name};
+use crate::version::Version;
+
+/// Dependencies on the game and the loader itself, which live outside the
+/// mods folder.
+const PLATFORM_IDS: &[&str] = &[
+    "minecraft",
+    "java",
+    "fabricloader",
+    "fabric-loader",
+    "quilt_loader",
+    "forge",
+    "neoforge",
+];
+
+/// A mod together with the jar it came from.
+#[derive(Debug, Clone)]
+pub struct InstalledMod {
+    pub path: PathBuf,
+    /// Entry name of the bundled jar inside `path` that declares the mod,
+    /// for mods that are not declared by `path` itself.
+    pub bundled: Option<String>,
+    pub metadata: ModMetadata,
+}
+
+/// The mods of a folder, as the loader would see them.
+#[derive(Debug, Clone, Default)]
+pub struct ModSet {
+    pub mods: Vec<InstalledMod>,
+}
+
+impl ModSet {
+    /// Reads every enabled jar in `mods_dir`. Jars without readable
+    /// metadata are left out.
+    pub fn scan(mods_dir: &Path) -> Result<Self> {
+        let jars = mods::list_jars(mods_dir)?
+            .into_iter()
+            .filter(|c| !c.disabled)
+            .filter_map(|c| Some((metadata::read_jar(&c.path).ok()?, c.path)))
+            .collect();
+        Ok(Self::from_jars(jars))
+    }
+
+    pub fn from_jars(jars: Vec<(JarMetadata, PathBuf)>) -> Self {
+        let mut set = Self::default();
+        for (jar, path) in jars {
+            set.insert(&path, &jar);
+        }
+        set
+    }
+
+    fn insert(&mut self, path: &Path, jar: &JarMetadata) {
+        self.mods.extend(jar.mods.iter().map(|m| InstalledMod {
+            path: path.to_path_buf(),
+            bundled: None,
+            metadata: m.clone(),
+        }));
+        for nested in jar.bundled() {
+            self.mods
+                .extend(nested.metadata.mods.iter().map(|m| InstalledMod {
+                    path: path.to_path_buf(),
+                    bundled: Some(nested.path.clone()),
+                    metadata: m.clone(),
+                }));
+        }
+    }
+
+    /// Pretends the jar at `path` now holds `jar` instead, as a
+    /// replacement would make it. `None` removes the jar.
+    pub fn swap(&mut self, path: &Path, jar: Option<&JarMetadata>) {
+        self.mods.retain(|m| m.path != path);
+        // A disabled jar is not loaded, whatever it holds
+        if let Some(jar) = jar
+            && !mods::is_disabled(path)
+        {
+            self.insert(path, jar);
+        }
+    }
+
+    /// The mods the loader would actually load. When a mod is bundled,
+    /// loaders keep only the newest of all its copies, bundled or not, and
+    /// prefer a top-level jar on a tie. Several top-level copies are left
+    /// alone; the loader refuses those outright.
+    fn loaded(&self) -> Vec<&InstalledMod> {
+        self.mods
+            .iter()
+            .enumerate()
+            .filter(|&(i, m)| {
+                !self.mods.iter().enumerate().any(|(j, other)| {
+                    if i == j
+                        || other.metadata.id != m.metadata.id
+                        || !same_family(other.metadata.loader, m.metadata.loader)
+                        || (m.bundled.is_none() && other.bundled.is_none())
+                    {
+                        return false;
+                    }
+                    let newer = other
+                        .metadata
+                        .parsed_version()
+                        .zip(m.metadata.parsed_version())
+                        .and_then(|(a, b)| a.compare(&b));
+                    match newer {
+                        Some(Ordering::Greater) => true,
+                        Some(Ordering::Less) => false,
+                        // Equal or incomparable: top-level first, then the first bundled copy
+                        _ => m.bundled.is_some() && (other.bundled.is_none() || j < i),
+                    }
+                })
+            })
+            .map(|(_, m)| m)
+            .collect()
+    }
+
+    pub fn problems(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let family = self.family();
+        let loaded = self.loaded();
+        // Mods answering to `id` on loaders that can load `loader`'s mods
+        let providers = |id: &str, loader: Loader| -> Vec<&InstalledMod> {
+            loaded
+                .iter()
+                .copied()
+                .filter(|m| same_family(m.metadata.loader, loader) && m.metadata.answers_to(id))
+                .collect()
+        };
+        for installed in loaded
+            .iter()
+            .copied()
+            .filter(|m| same_family(m.metadata.loader, family))
+        {
+            let m = &installed.metadata;
+            for dependency in &m.dependencies {
+                if PLATFORM_IDS.contains(&dependency.id.as_str()) || m.answers_to(&dependency.id) {
+                    continue;
+                }
+                let range = m.dependency_range(dependency);
+                let providers = providers(&dependency.id, m.loader);
+                let matching = providers.iter().find(|p| {
+                    match p
+                        .metadata
+                        .version
+                        .as_deref()
+                        .and_then(|v| Version::parse(v, m.range_syntax()))
+                    {
+                        Some(version) => range.contains(&version),
+                        // An unknown version cannot be held against it
+                        None => true,
+                    }
+                });
+
+                let kind = match (dependency.kind, providers.first(), matching) {
+                    (DependencyKind::Required, None, _) => ProblemKind::Missing,
+                    (DependencyKind::Recommended, None, _) => ProblemKind::Recommended,
+                    (DependencyKind::Required | DependencyKind::Optional, Some(p), None) => {
+                        ProblemKind::WrongVersion(found(p))
+                    }
+                    (DependencyKind::Recommended, Some(_), None) => ProblemKind::Recommended,
+                    (DependencyKind::Breaks, _, Some(p)) => ProblemKind::Breaks(found(p)),
+                    (DependencyKind::Conflicts, _, Some(p)) => ProblemKind::Conflicts(found(p)),
+                    _ => continue,
+                };
+                problems.push(Problem {
+                    path: installed.path.clone(),
+                    mod_id: m.id.clone(),
+                    dependency: dependency.clone(),
+                    provider: matching.or(providers.first()).map(|p| p.path.clone()),
+                    kind,
+                });
+            }
+        }
+        problems
+    }
+
+    /// Multi-loader jars declare their mod once per loader. Only the
+    /// entries for the loader family most of the folder is built for are
+    /// checked.
+    fn family(&self) -> Loader {
+        let fabric = self
+            .mods
+            .iter()
+            .filter(|m| same_family(m.metadata.loader, Loader::Fabric))
+            .count();
+        if fabric * 2 >= self.mods.len() {
+            Loader::Fabric
+        } else {
+            Loader::Forge
+        }
+    }
+}
+
+/// Quilt loads Fabric mods, and NeoForge grew out of Forge.
+fn same_family(a: Loader, b: Loader) -> bool {
+    let fabric_like = |l| matches!(l, Loader::Fabric | Loader::Quilt);
+    fabric_like(a) == fabric_like(b)
+}
+
+fn found(provider: &InstalledMod) -> String {
+    let m = &provider.metadata;
+    let mut out = match &m.version {
+        Some(version) => format!("{} {version}", m.id),
+        None => m.id.clone(),
+    };
+    if provider.bundled.is_some() {
+        out.push_str(&format!(" (bundled in {})", file_name(&provider.path)));
+    }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemKind {
+    /// A required dependency is not installed.
+    Missing,
+    /// A dependency is installed, but not in an accepted version. Holds
+    /// the id and version that was found.
+    WrongVersion(String),
+    /// A mod the declaring mod breaks with is installed.
+    Breaks(String),
+    /// A mod the declaring mod conflicts with is installed.
+    Conflicts(String),
+    /// A recommended mod is missing or in another version.
+    Recommended,
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    /// Jar declaring the dependency.
+    pub path: PathBuf,
+    pub mod_id: String,
+    pub dependency: Dependency,
+    /// Jar that provides, or breaks, the dependency, if installed.
+    pub provider: Option<PathBuf>,
+    pub kind: ProblemKind,
+}
+
+impl Problem {
+    /// Whether the loader would refuse to start. Conflicts and
+    /// recommendations only warrant a warning.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self.kind,
+            ProblemKind::Missing | ProblemKind::WrongVersion(_) | ProblemKind::Breaks(_)
+        )
+    }
+
+    /// Whether replacing the jar at `jar` could be behind this problem:
+    /// it declares the dependency, provides it now, or provided it in
+    /// `before`.
+    pub fn involves(&self, jar: &Path, before: &ModSet) -> bool {
+        self.path == jar
+            || self.provider.as_deref() == Some(jar)
+            || before
+                .mods
+                .iter()
+                .any(|m| m.path == jar && m.metadata.answers_to(&self.dependency.id))
+    }
+
+    /// Whether `other` is the same problem, ignoring which version was found.
+    fn same_as(&self, other: &Self) -> bool {
+        self.path == other.path
+            && self.mod_id == other.mod_id
+            && self.dependency.id == other.dependency.id
+            && std::mem::discriminant(&self.kind) == std::mem::discriminant(&other.kind)
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let versions = self.dependency.versions.as_deref().unwrap_or("*");
+        let dep = &self.dependency.id;
+        write!(f, "{} ({}): ", self.mod_id, file_name(&self.path))?;
+        match &self.kind {
+            ProblemKind::Missing => write!(f, "requires {dep} {versions}, which is not installed"),
+            ProblemKind::WrongVersion(found) => {
+                write!(f, "requires {dep} {versions}, but {found} is installed")
+            }
+            ProblemKind::Breaks(found) => {
+                write!(
+                    f,
+                    "is incompatible with {dep} {versions}, and {found} is installed"
+                )
+            }
+            ProblemKind::Conflicts(found) => {
+                write!(
+                    f,
+                    "conflicts with {dep} {versions}, and {found} is installed"
+                )
+            }
+            ProblemKind::Recommended => write!(f, "recommends {dep} {versions}"),
+        }
+    }
+}
+
+/// Problems `after` has that `before` did not.
+pub fn introduced(before: &ModSet, after: &ModSet) -> Vec<Problem> {
+    let old = before.problems();
+    after
+        .problems()
+        .into_iter()
+        .filter(|p| !old.iter().any(|o| o.same_as(p)))
+        .collect()
+}
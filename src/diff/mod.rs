@@ -0,0 +1,313 @@
+//! Differences between two versions of a jar: which entries changed, what
+//! the mod metadata says differently, and line diffs of text resources.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::error::Result;
+use crate::metadata::{self, DependencyKind, JarMetadata, ModMetadata};
+use crate::zip::Archive;
+
+mod text;
+
+pub use text::{Hunk, LineChange, diff_lines};
+
+/// Unchanged lines shown around each change in text diffs.
+pub const CONTEXT_LINES: usize = 3;
+
+/// Extensions of entries that are diffed line by line when they are valid
+/// UTF-8.
+const TEXT_EXTENSIONS: &[&str] = &[
+    "json",
+    "json5",
+    "toml",
+    "txt",
+    "properties",
+    "lang",
+    "cfg",
+    "conf",
+    "ini",
+    "yml",
+    "yaml",
+    "mcmeta",
+    "md",
+    "mf",
+    "xml",
+    "accesswidener",
+    "classtweaker",
+    "csv",
+    "glsl",
+    "fsh",
+    "vsh",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryChange {
+    Added {
+        size: u64,
+    },
+    Removed {
+        size: u64,
+    },
+    /// The CRC or the uncompressed size differs.
+    Modified {
+        old_crc: u32,
+        new_crc: u32,
+        old_size: u64,
+        new_size: u64,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct EntryDiff {
+    pub name: String,
+    pub change: EntryChange,
+}
+
+/// One difference in what a mod declares about itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataChange {
+    pub mod_id: String,
+    /// What changed, such as `version` or `depends fabric-api`.
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl fmt::Display for MetadataChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ", self.mod_id, self.field)?;
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, "{old} → {new}"),
+            (None, Some(new)) => write!(f, "added ({new})"),
+            (Some(old), None) => write!(f, "removed ({old})"),
+            (None, None) => write!(f, "changed"),
+        }
+    }
+}
+
+/// A text resource that changed, as unified diff hunks.
+#[derive(Debug, Clone)]
+pub struct TextDiff {
+    pub name: String,
+    pub hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JarDiff {
+    /// Added, removed and modified entries, by name.
+    pub entries: Vec<EntryDiff>,
+    pub unchanged: usize,
+    pub metadata: Vec<MetadataChange>,
+    pub text: Vec<TextDiff>,
+}
+
+impl JarDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.metadata.is_empty()
+    }
+
+    /// How many entries were added, removed and modified.
+    pub fn counts(&self) -> (usize, usize, usize) {
+        let count =
+            |f: fn(&EntryChange) -> bool| self.entries.iter().filter(|e| f(&e.change)).count();
+        (
+            count(|c| matches!(c, EntryChange::Added { .. })),
+            count(|c| matches!(c, EntryChange::Removed { .. })),
+            count(|c| matches!(c, EntryChange::Modified { .. })),
+        )
+    }
+}
+
+/// Compares the jar `old` with the jar `new`. Entries are matched by name
+/// and compared by CRC and size, so nothing but changed text resources is
+/// decompressed.
+pub fn diff(old: &[u8], new: &[u8]) -> Result<JarDiff> {
+    let old = Archive::parse(old)?;
+    let new = Archive::parse(new)?;
+    let mut result = JarDiff::default();
+
+    let files = |archive: &Archive| {
+        archive
+            .entries
+            .iter()
+            .filter(|e| !e.is_dir())
+            .map(|e| (e.name.clone(), e.clone()))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let old_entries = files(&old);
+    let new_entries = files(&new);
+
+    for (name, entry) in &old_entries {
+        match new_entries.get(name) {
+            None => result.entries.push(EntryDiff {
+                name: name.clone(),
+                change: EntryChange::Removed {
+                    size: entry.uncompressed_size,
+                },
+            }),
+            Some(other)
+                if other.crc32 != entry.crc32
+                    || other.uncompressed_size != entry.uncompressed_size =>
+            {
+                result.entries.push(EntryDiff {
+                    name: name.clone(),
+                    change: EntryChange::Modified {
+                        old_crc: entry.crc32,
+                        new_crc: other.crc32,
+                        old_size: entry.uncompressed_size,
+                        new_size: other.uncompressed_size,
+                    },
+                });
+                if is_text(name)
+                    && let Ok(Some(before)) = read_utf8(&old, name)
+                    && let Ok(Some(after)) = read_utf8(&new, name)
+                {
+                    result.text.push(TextDiff {
+                        name: name.clone(),
+                        hunks: diff_lines(&before, &after, CONTEXT_LINES),
+                    });
+                }
+            }
+            Some(_) => result.unchanged += 1,
+        }
+    }
+    for (name, entry) in &new_entries {
+        if !old_entries.contains_key(name) {
+            result.entries.push(EntryDiff {
+                name: name.clone(),
+                change: EntryChange::Added {
+                    size: entry.uncompressed_size,
+                },
+            });
+        }
+    }
+    result.entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // A descriptor that does not parse still shows up in the text diff
+    result.metadata = metadata_changes(
+        &metadata::read_archive(&old).unwrap_or_default(),
+        &metadata::read_archive(&new).unwrap_or_default(),
+    );
+    Ok(result)
+}
+
+fn is_text(name: &str) -> bool {
+    name.rsplit_once('.')
+        .is_some_and(|(_, ext)| TEXT_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+fn read_utf8(archive: &Archive, name: &str) -> Result<Option<String>> {
+    Ok(archive
+        .read_file(name)?
+        .and_then(|data| String::from_utf8(data).ok()))
+}
+
+/// Compares the mods two jars declare, matched by id and loader.
+pub fn metadata_changes(old: &JarMetadata, new: &JarMetadata) -> Vec<MetadataChange> {
+    let mut changes = Vec::new();
+    for m in &old.mods {
+        match new
+            .mods
+            .iter()
+            .find(|n| n.id == m.id && n.loader == m.loader)
+        {
+            Some(n) => mod_changes(m, n, &mut changes),
+            None => changes.push(change(&m.id, "mod", Some(describe(m)), None)),
+        }
+    }
+    for n in &new.mods {
+        if !old
+            .mods
+            .iter()
+            .any(|m| m.id == n.id && m.loader == n.loader)
+        {
+            changes.push(change(&n.id, "mod", None, Some(describe(n))));
+        }
+    }
+    changes
+}
+
+fn mod_changes(old: &ModMetadata, new: &ModMetadata, changes: &mut Vec<MetadataChange>) {
+    let id = &old.id;
+    for (field, a, b) in [
+        ("name", &old.name, &new.name),
+        ("version", &old.version, &new.version),
+        ("Minecraft", &old.minecraft, &new.minecraft),
+    ] {
+        if a != b {
+            changes.push(change(id, field, a.clone(), b.clone()));
+        }
+    }
+    if old.environment != new.environment {
+        changes.push(change(
+            id,
+            "environment",
+            Some(format!("{:?}", old.environment).to_lowercase()),
+            Some(format!("{:?}", new.environment).to_lowercase()),
+        ));
+    }
+
+    let dependencies = |m: &ModMetadata| {
+        m.dependencies
+            .iter()
+            .map(|d| {
+                (
+                    format!("{} {}", kind_label(d.kind), d.id),
+                    d.versions.clone().unwrap_or_else(|| "*".to_string()),
+                )
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+    let (before, after) = (dependencies(old), dependencies(new));
+    for (field, versions) in &before {
+        match after.get(field) {
+            Some(other) if other == versions => {}
+            other => changes.push(change(id, field, Some(versions.clone()), other.cloned())),
+        }
+    }
+    for (field, versions) in &after {
+        if !before.contains_key(field) {
+            changes.push(change(id, field, None, Some(versions.clone())));
+        }
+    }
+
+    for (field, a, b) in [
+        ("provides", &old.provides, &new.provides),
+        ("mixin config", &old.mixins, &new.mixins),
+    ] {
+        for item in a.iter().filter(|item| !b.contains(item)) {
+            changes.push(change(id, field, Some(item.clone()), None));
+        }
+        for item in b.iter().filter(|item| !a.contains(item)) {
+            changes.push(change(id, field, None, Some(item.clone())));
+        }
+    }
+}
+
+fn change(mod_id: &str, field: &str, old: Option<String>, new: Option<String>) -> MetadataChange {
+    MetadataChange {
+        mod_id: mod_id.to_string(),
+        field: field.to_string(),
+        old,
+        new,
+    }
+}
+
+fn describe(m: &ModMetadata) -> String {
+    format!(
+        "{} {}",
+        m.loader,
+        m.version.as_deref().unwrap_or("unknown version")
+    )
+}
+
+fn kind_label(kind: DependencyKind) -> &'static str {
+    match kind {
+        DependencyKind::Required => "depends",
+        DependencyKind::Recommended => "recommends",
+        DependencyKind::Optional => "optional",
+        DependencyKind::Breaks => "breaks",
+        DependencyKind::Conflicts => "conflicts",
+    }
+}
@@ -0,0 +1,202 @@
+//! Line diff of text resources, printed in unified format.
+
+use std::fmt;
+
+/// Edit distance beyond which two texts are shown as fully replaced
+/// instead of searching for a minimal diff.
+const MAX_EDITS: usize = 4000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineChange {
+    Same,
+    Removed,
+    Added,
+}
+
+/// A run of changed lines with the unchanged lines around them.
+#[derive(Debug, Clone)]
+pub struct Hunk {
+    /// 1-based line the hunk starts at in the old text.
+    pub old_start: usize,
+    pub old_len: usize,
+    /// 1-based line the hunk starts at in the new text.
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<(LineChange, String)>,
+}
+
+impl fmt::Display for Hunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // An empty side is numbered after the line it follows, as diff does
+        let start = |start: usize, len: usize| if len == 0 { start - 1 } else { start };
+        writeln!(
+            f,
+            "@@ -{},{} +{},{} @@",
+            start(self.old_start, self.old_len),
+            self.old_len,
+            start(self.new_start, self.new_len),
+            self.new_len
+        )?;
+        for (change, line) in &self.lines {
+            let marker = match change {
+                LineChange::Same => ' ',
+                LineChange::Removed => '-',
+                LineChange::Added => '+',
+            };
+            writeln!(f, "{marker}{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Diffs `old` against `new` line by line and groups the changes into
+/// hunks with `context` unchanged lines around them.
+pub fn diff_lines(old: &str, new: &str, context: usize) -> Vec<Hunk> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = edit_script(&a, &b);
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (change, _))| *change != LineChange::Same)
+        .map(|(i, _)| i)
+        .collect();
+    let old_line = |end: usize| {
+        ops[..end]
+            .iter()
+            .filter(|(c, _)| *c != LineChange::Added)
+            .count()
+    };
+    let new_line = |end: usize| {
+        ops[..end]
+            .iter()
+            .filter(|(c, _)| *c != LineChange::Removed)
+            .count()
+    };
+
+    let mut hunks = Vec::new();
+    let mut c = 0;
+    while c < changes.len() {
+        let start = changes[c].saturating_sub(context);
+        let mut last = changes[c];
+        // Changes closer than twice the context share a hunk
+        while c + 1 < changes.len() && changes[c + 1] - last - 1 <= 2 * context {
+            c += 1;
+            last = changes[c];
+        }
+        let end = (last + context + 1).min(ops.len());
+        let old_start = old_line(start);
+        let new_start = new_line(start);
+        hunks.push(Hunk {
+            old_start: old_start + 1,
+            old_len: old_line(end) - old_start,
+            new_start: new_start + 1,
+            new_len: new_line(end) - new_start,
+            lines: ops[start..end]
+                .iter()
+                .map(|(change, line)| (*change, line.to_string()))
+                .collect(),
+        });
+        c += 1;
+    }
+    hunks
+}
+
+/// A shortest edit script from `a` to `b` (Myers' algorithm).
+fn edit_script<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(LineChange, &'a str)> {
+    // Common leading and trailing lines are cheap to take out first
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops: Vec<(LineChange, &str)> =
+        a[..prefix].iter().map(|l| (LineChange::Same, *l)).collect();
+    match myers(mid_a, mid_b) {
+        Some(middle) => ops.extend(middle),
+        None => {
+            ops.extend(mid_a.iter().map(|l| (LineChange::Removed, *l)));
+            ops.extend(mid_b.iter().map(|l| (LineChange::Added, *l)));
+        }
+    }
+    ops.extend(a[a.len() - suffix..].iter().map(|l| (LineChange::Same, *l)));
+    ops
+}
+
+/// `None` when the texts differ in more than `MAX_EDITS` lines.
+fn myers<'a>(a: &[&'a str], b: &[&'a str]) -> Option<Vec<(LineChange, &'a str)>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (a.len() + b.len()).min(MAX_EDITS) as isize;
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // trace[d] holds the furthest x reached on diagonals -d..=d after round d
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    let at = |k: isize| (k + offset) as usize;
+    let mut done = false;
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                done = true;
+                break;
+            }
+        }
+        trace.push(v[at(-d)..=at(d)].to_vec());
+        if done {
+            break;
+        }
+    }
+    if !done {
+        return None;
+    }
+
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let previous = &trace[d as usize - 1];
+        let get = |k: isize| previous[(k + d - 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            ops.push((LineChange::Same, a[x as usize]));
+        }
+        if x == prev_x {
+            y -= 1;
+            ops.push((LineChange::Added, b[y as usize]));
+        } else {
+            x -= 1;
+            ops.push((LineChange::Removed, a[x as usize]));
+        }
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        ops.push((LineChange::Same, a[x as usize]));
+    }
+    ops.reverse();
+    Some(ops)
+}
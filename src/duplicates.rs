@@ -0,0 +1,133 @@
+//! Jars in one mods folder that declare the same mod, usually two versions
+//! left side by side after an update. Loaders refuse to start with those.
+//!
+//! Copies bundled inside other jars count as well: the loader silently
+//! picks one of them, which is rarely the one the player put in the folder.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::backup::BackupSet;
+use crate::error::Result;
+use crate::metadata::ModMetadata;
+use crate::mods::{self, Candidate};
+
+/// An enabled jar and the mod it mainly declares.
+#[derive(Debug, Clone)]
+pub struct ModJar {
+    pub candidate: Candidate,
+    /// Entry name of the bundled jar inside `candidate` that declares the
+    /// mod, when the copy is bundled.
+    pub bundled: Option<String>,
+    pub metadata: ModMetadata,
+}
+
+/// Jars that declare the same mod id: the one to keep first, then the
+/// other jars of their own, then bundled copies.
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub mod_id: String,
+    pub jars: Vec<ModJar>,
+}
+
+impl DuplicateGroup {
+    /// Of the jars of their own, the one with the newest version, or the
+    /// most recently modified one when the versions cannot be compared.
+    /// A bundled copy is never kept over them, however new: it is often
+    /// only part of the mod, and it goes away with the mod that ships it.
+    pub fn kept(&self) -> &ModJar {
+        &self.jars[0]
+    }
+
+    /// Every copy but the kept one.
+    pub fn others(&self) -> &[ModJar] {
+        &self.jars[1..]
+    }
+
+    /// The other copies that are jars of their own and can be removed.
+    pub fn removable(&self) -> impl Iterator<Item = &ModJar> {
+        self.others().iter().filter(|jar| jar.bundled.is_none())
+    }
+
+    /// Whether `jar` is a newer version than the kept jar, as a bundled
+    /// copy can be. The loader may then pick it instead.
+    pub fn is_newer_than_kept(&self, jar: &ModJar) -> bool {
+        compare_versions(jar, self.kept()) == Some(Ordering::Greater)
+    }
+}
+
+/// Groups the enabled jars of `mods_dir`, and the jars they bundle, by the
+/// id of their main mod. Returns the groups with more than one copy, at
+/// least one of them a jar of its own. Disabled jars are not loaded, so
+/// they never clash.
+pub fn find(mods_dir: &Path) -> Result<Vec<DuplicateGroup>> {
+    let mut by_id: BTreeMap<String, Vec<(ModJar, Option<SystemTime>)>> = BTreeMap::new();
+    for candidate in mods::list_jars(mods_dir)?
+        .into_iter()
+        .filter(|c| !c.disabled)
+    {
+        let Ok(jar) = candidate.metadata() else {
+            continue;
+        };
+        let modified = fs::metadata(&candidate.path)
+            .and_then(|m| m.modified())
+            .ok();
+        let copies = jar.primary().map(|m| (None, m)).into_iter().chain(
+            jar.bundled()
+                .into_iter()
+                .filter_map(|n| Some((Some(n.path.clone()), n.metadata.primary()?))),
+        );
+        for (bundled, metadata) in copies {
+            by_id.entry(metadata.id.clone()).or_default().push((
+                ModJar {
+                    candidate: candidate.clone(),
+                    bundled,
+                    metadata: metadata.clone(),
+                },
+                modified,
+            ));
+        }
+    }
+
+    Ok(by_id
+        .into_iter()
+        .filter(|(_, jars)| jars.len() > 1 && jars.iter().any(|(jar, _)| jar.bundled.is_none()))
+        .map(|(mod_id, mut jars)| {
+            // Jars of their own before bundled copies, most recently modified
+            // first, then move the newest jar of its own to the front
+            jars.sort_by_key(|(jar, modified)| (jar.bundled.is_some(), Reverse(*modified)));
+            let own = jars.iter().filter(|(jar, _)| jar.bundled.is_none()).count();
+            let newest = (1..own).fold(0, |best, i| {
+                if compare_versions(&jars[i].0, &jars[best].0) == Some(Ordering::Greater) {
+                    i
+                } else {
+                    best
+                }
+            });
+            let first = jars.remove(newest);
+            jars.insert(0, first);
+            DuplicateGroup {
+                mod_id,
+                jars: jars.into_iter().map(|(jar, _)| jar).collect(),
+            }
+        })
+        .collect())
+}
+
+fn compare_versions(a: &ModJar, b: &ModJar) -> Option<Ordering> {
+    a.metadata
+        .parsed_version()?
+        .compare(&b.metadata.parsed_version()?)
+}
+
+/// Moves `jars` out of the mods folder and into `backup`.
+pub fn remove(jars: &[PathBuf], backup: &mut BackupSet) -> Result<()> {
+    for jar in jars {
+        backup.add(jar, None)?;
+        fs::remove_file(jar)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::io;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::compat::Mismatch;
+use crate::deps::Problem;
+use crate::zip::ZipError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Zip(#[from] ZipError),
+    #[error("invalid {file}: {reason}")]
+    InvalidMetadata { file: &'static str, reason: String },
+    #[error("{0:?} is not a .jar file")]
+    NotAJar(PathBuf),
+    #[error("{0:?} already exists")]
+    AlreadyExists(PathBuf),
+    #[error("{0:?} is not a valid folder")]
+    NotADirectory(PathBuf),
+    #[error("no jar named '{name}' in {mods_dir:?}")]
+    TargetNotFound { name: String, mods_dir: PathBuf },
+    #[error("'{name}' matches several jars: {}", join_paths(.matches))]
+    AmbiguousTarget { name: String, matches: Vec<PathBuf> },
+    #[error("invalid modpack: {0}")]
+    InvalidPack(String),
+    #[error("invalid plan {0:?}: {1}")]
+    InvalidPlan(PathBuf, String),
+    #[error("invalid profiles file {0:?}: {1}")]
+    InvalidProfiles(PathBuf, String),
+    #[error("no profile named '{0}'")]
+    ProfileNotFound(String),
+    #[error("invalid lockfile {0:?}: {1}")]
+    InvalidLockfile(PathBuf, String),
+    #[error("'{target}' failed, all changes were rolled back: {source}")]
+    RolledBack {
+        target: String,
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("replacement does not look like the same mod: {}", join(.0))]
+    Mismatch(Vec<Mismatch>),
+    #[error("replacement breaks dependencies: {}", join(.0))]
+    Dependencies(Vec<Problem>),
+    #[error("refusing to write {0:?}: {1}")]
+    VerifyFailed(PathBuf, String),
+    #[error("no backup with id '{0}'")]
+    BackupNotFound(String),
+    #[error("broken backup at {0:?}: {1}")]
+    InvalidBackup(PathBuf, String),
+    #[error(
+        "replacement file is larger ({replacement} bytes) than selected mod ({original} bytes)"
+    )]
+    ReplacementTooLarge { replacement: u64, original: u64 },
+}
+
+fn join(items: &[impl ToString]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
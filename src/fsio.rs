@@ -0,0 +1,109 @@
+//! Crash-safe file writes.
+//!
+//! Data goes to a hidden temp file next to the target, is fsynced and read
+//! back, and only then renamed over the target. If anything fails the temp
+//! file is removed and the target is left as it was.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::mods::file_name;
+use crate::zip;
+
+/// Atomically replaces `path` with `data`.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    write_checked(path, data, |_| Ok(()))
+}
+
+/// Like [`write_atomic`], but the written bytes must also pass a full
+/// archive verification, CRCs included, before they are moved into place.
+pub fn write_jar_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    write_checked(path, data, |written| {
+        let report = zip::verify(written).map_err(|err| err.to_string())?;
+        match report.problems.first() {
+            None => Ok(()),
+            Some(problem) => Err(format!(
+                "{} ({} problem(s) in total)",
+                problem,
+                report.problems.len()
+            )),
+        }
+    })
+}
+
+/// Writes a file that must not exist yet and syncs it, and its folder, to
+/// disk before returning.
+pub fn write_new(path: &Path, data: &[u8]) -> Result<()> {
+    write_temp(path, data)?;
+    sync_parent(path);
+    Ok(())
+}
+
+/// Joins a pack-relative path onto `base`, refusing anything that could
+/// escape it.
+pub fn safe_join(base: &Path, relative: &str) -> Result<PathBuf> {
+    let path = Path::new(relative);
+    let escapes = path
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)));
+    if relative.is_empty() || escapes || relative.contains('\\') {
+        return Err(Error::InvalidPack(format!("unsafe path '{relative}'")));
+    }
+    Ok(base.join(path))
+}
+
+fn write_checked(
+    path: &Path,
+    data: &[u8],
+    check: impl FnOnce(&[u8]) -> std::result::Result<(), String>,
+) -> Result<()> {
+    let temp = temp_path(path);
+    let result = write_temp(&temp, data).and_then(|()| {
+        let written = fs::read(&temp)?;
+        if written != data {
+            return Err(Error::VerifyFailed(
+                path.to_path_buf(),
+                "temp file does not match the data written".to_string(),
+            ));
+        }
+        check(&written).map_err(|reason| Error::VerifyFailed(path.to_path_buf(), reason))?;
+        // The temp file was created with default permissions
+        if let Ok(original) = fs::metadata(path) {
+            fs::set_permissions(&temp, original.permissions())?;
+        }
+        fs::rename(&temp, path)?;
+        Ok(())
+    });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+        return result;
+    }
+    sync_parent(path);
+    Ok(())
+}
+
+fn write_temp(temp: &Path, data: &[u8]) -> Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(temp)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// A hidden sibling of `path` that mod loaders will not mistake for a jar.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = format!(".{}.{}.tmp", file_name(path), std::process::id());
+    path.with_file_name(name)
+}
+
+/// Makes the rename itself durable. Not every platform can open a
+/// directory for syncing, so failures are ignored.
+fn sync_parent(path: &Path) {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty())
+        && let Ok(dir) = File::open(parent)
+    {
+        let _ = dir.sync_all();
+    }
+}
@@ -0,0 +1,108 @@
+//! File hashes as the various pack formats record them.
+
+use std::fmt;
+use std::str::FromStr;
+
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgo {
+    Sha1,
+    Sha256,
+    Sha512,
+    /// CurseForge fingerprint, see [`curseforge_fingerprint`].
+    Murmur2,
+}
+
+impl HashAlgo {
+    /// Digest of `data` as pack files write it: lowercase hex, or a
+    /// decimal number for murmur2.
+    pub fn digest(self, data: &[u8]) -> String {
+        match self {
+            Self::Sha1 => sha1_hex(data),
+            Self::Sha256 => sha256_hex(data),
+            Self::Sha512 => sha512_hex(data),
+            Self::Murmur2 => curseforge_fingerprint(data).to_string(),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sha1 => "sha1",
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+            Self::Murmur2 => "murmur2",
+        }
+    }
+}
+
+impl fmt::Display for HashAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl FromStr for HashAlgo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sha1" => Ok(Self::Sha1),
+            "sha256" => Ok(Self::Sha256),
+            "sha512" => Ok(Self::Sha512),
+            "murmur2" => Ok(Self::Murmur2),
+            other => Err(format!("unsupported hash format '{other}'")),
+        }
+    }
+}
+
+pub fn sha1_hex(data: &[u8]) -> String {
+    hex(&Sha1::digest(data))
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+pub fn sha512_hex(data: &[u8]) -> String {
+    hex(&Sha512::digest(data))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// CurseForge's file fingerprint: 32-bit MurmurHash2 with seed 1 over the
+/// file with every tab, newline, carriage return and space removed.
+pub fn curseforge_fingerprint(data: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1_e995;
+
+    let bytes: Vec<u8> = data
+        .iter()
+        .copied()
+        .filter(|b| !matches!(b, 9 | 10 | 13 | 32))
+        .collect();
+    let mut h = 1 ^ bytes.len() as u32;
+
+    let mut chunks = bytes.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> 24;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M) ^ k;
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (i, &b) in tail.iter().enumerate() {
+            h ^= u32::from(b) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^ (h >> 15)
+}
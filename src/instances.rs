@@ -0,0 +1,335 @@
+//! Finds Minecraft instances of the common launchers and their mods folders.
+//!
+//! Only the usual Linux locations are searched: `~/.minecraft`, the
+//! launchers' folders under `$XDG_DATA_HOME` (or `~/.local/share`) and
+//! `$XDG_CONFIG_HOME`, their Flatpak data folders, and `~/curseforge`.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Launcher {
+    Vanilla,
+    Prism,
+    MultiMc,
+    AtLauncher,
+    GdLauncher,
+    CurseForge,
+}
+
+impl fmt::Display for Launcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Vanilla => "Vanilla",
+            Self::Prism => "Prism",
+            Self::MultiMc => "MultiMC",
+            Self::AtLauncher => "ATLauncher",
+            Self::GdLauncher => "GDLauncher",
+            Self::CurseForge => "CurseForge",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub name: String,
+    pub launcher: Launcher,
+    /// The game folder, which holds `mods`, `config` and so on.
+    pub game_dir: PathBuf,
+    pub mods_dir: PathBuf,
+    pub minecraft: Option<String>,
+    /// Loader name and version, e.g. `Fabric 0.15.11`.
+    pub loader: Option<String>,
+}
+
+/// Base folders to search, normally taken from the environment.
+#[derive(Debug, Clone)]
+pub struct SearchRoots {
+    pub home: PathBuf,
+    pub data_home: PathBuf,
+    pub config_home: PathBuf,
+}
+
+impl SearchRoots {
+    pub fn from_env() -> Option<Self> {
+        let home = PathBuf::from(env::var_os("HOME")?);
+        let xdg = |var, default: &str| {
+            env::var_os(var)
+                .map(PathBuf::from)
+                .filter(|p| p.is_absolute())
+                .unwrap_or_else(|| home.join(default))
+        };
+        Some(Self {
+            data_home: xdg("XDG_DATA_HOME", ".local/share"),
+            config_home: xdg("XDG_CONFIG_HOME", ".config"),
+            home,
+        })
+    }
+}
+
+/// All instances found in the usual locations for the current user.
+pub fn discover() -> Vec<Instance> {
+    SearchRoots::from_env()
+        .map(|roots| discover_in(&roots))
+        .unwrap_or_default()
+}
+
+/// All instances under `roots` that have a mods folder.
+pub fn discover_in(roots: &SearchRoots) -> Vec<Instance> {
+    let home = &roots.home;
+    let data = &roots.data_home;
+    let flatpak = |app: &str| home.join(".var/app").join(app).join("data");
+
+    let mut instances = Vec::new();
+    instances.extend(vanilla(&home.join(".minecraft")));
+    for dir in [
+        data.join("PrismLauncher/instances"),
+        flatpak("org.prismlauncher.PrismLauncher").join("PrismLauncher/instances"),
+    ] {
+        instances.extend(each_subdir(&dir, |d| mmc_instance(d, Launcher::Prism)));
+    }
+    for dir in [
+        data.join("multimc/instances"),
+        home.join("MultiMC/instances"),
+    ] {
+        instances.extend(each_subdir(&dir, |d| mmc_instance(d, Launcher::MultiMc)));
+    }
+    for dir in [
+        data.join("ATLauncher/instances"),
+        flatpak("com.atlauncher.ATLauncher").join("instances"),
+    ] {
+        instances.extend(each_subdir(&dir, atlauncher_instance));
+    }
+    for dir in [
+        roots.config_home.join("gdlauncher_next/instances"),
+        data.join("gdlauncher_carbon/data/instances"),
+    ] {
+        instances.extend(each_subdir(&dir, gdlauncher_instance));
+    }
+    instances.extend(each_subdir(
+        &home.join("curseforge/minecraft/Instances"),
+        curseforge_instance,
+    ));
+
+    instances.retain(|i| i.mods_dir.is_dir());
+    instances
+}
+
+fn each_subdir(dir: &Path, read: impl Fn(&Path) -> Option<Instance>) -> Vec<Instance> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_dir())
+        .collect();
+    dirs.sort();
+    dirs.iter().filter_map(|d| read(d)).collect()
+}
+
+fn dir_name(dir: &Path) -> String {
+    crate::mods::file_name(dir)
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+fn vanilla(game_dir: &Path) -> Option<Instance> {
+    game_dir.is_dir().then(|| Instance {
+        name: "Minecraft".to_string(),
+        launcher: Launcher::Vanilla,
+        game_dir: game_dir.to_path_buf(),
+        mods_dir: game_dir.join("mods"),
+        minecraft: None,
+        loader: None,
+    })
+}
+
+/// Prism and MultiMC: `instance.cfg` for the name, `mmc-pack.json` for the
+/// component versions, and the game in `.minecraft` or `minecraft`.
+fn mmc_instance(dir: &Path, launcher: Launcher) -> Option<Instance> {
+    let cfg = fs::read_to_string(dir.join("instance.cfg")).ok()?;
+    let name = cfg
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("name="))
+        .map(str::to_string)
+        .unwrap_or_else(|| dir_name(dir));
+
+    let game_dir = [".minecraft", "minecraft"]
+        .iter()
+        .map(|d| dir.join(d))
+        .find(|d| d.is_dir())
+        .unwrap_or_else(|| dir.join(".minecraft"));
+
+    let mut minecraft = None;
+    let mut loader = None;
+    let components = read_json(&dir.join("mmc-pack.json"))
+        .and_then(|pack| pack.get("components").cloned())
+        .and_then(|c| c.as_array().cloned())
+        .unwrap_or_default();
+    for component in &components {
+        let uid = component.get("uid").and_then(Value::as_str);
+        let version = component
+            .get("version")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        match uid {
+            Some("net.minecraft") => minecraft = version,
+            Some(uid) => {
+                if let Some(loader_name) = loader_for_uid(uid) {
+                    loader = Some(with_version(loader_name, version));
+                }
+            }
+            None => {}
+        }
+    }
+
+    Some(Instance {
+        name,
+        launcher,
+        mods_dir: game_dir.join("mods"),
+        game_dir,
+        minecraft,
+        loader,
+    })
+}
+
+fn loader_for_uid(uid: &str) -> Option<&'static str> {
+    match uid {
+        "net.fabricmc.fabric-loader" => Some("Fabric"),
+        "org.quiltmc.quilt-loader" => Some("Quilt"),
+        "net.minecraftforge" => Some("Forge"),
+        "net.neoforged" => Some("NeoForge"),
+        _ => None,
+    }
+}
+
+fn with_version(loader: &str, version: Option<String>) -> String {
+    match version {
+        Some(version) => format!("{loader} {version}"),
+        None => loader.to_string(),
+    }
+}
+
+/// ATLauncher: `instance.json` holds the Minecraft version as `id` and the
+/// loader under `launcher.loaderVersion`.
+fn atlauncher_instance(dir: &Path) -> Option<Instance> {
+    let json = read_json(&dir.join("instance.json"))?;
+    let launcher = json.get("launcher");
+    let name = launcher
+        .and_then(|l| l.get("name"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| dir_name(dir));
+    let loader = launcher
+        .and_then(|l| l.get("loaderVersion"))
+        .and_then(|lv| {
+            let kind = lv.get("type")?.as_str()?;
+            let version = lv
+                .get("version")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            Some(with_version(kind, version))
+        });
+
+    Some(Instance {
+        name,
+        launcher: Launcher::AtLauncher,
+        game_dir: dir.to_path_buf(),
+        mods_dir: dir.join("mods"),
+        minecraft: json.get("id").and_then(Value::as_str).map(str::to_string),
+        loader,
+    })
+}
+
+/// GDLauncher: the legacy app keeps `config.json` in the instance folder,
+/// the current one keeps `instance.json` and the game in `instance/`.
+fn gdlauncher_instance(dir: &Path) -> Option<Instance> {
+    if let Some(config) = read_json(&dir.join("config.json")) {
+        let loader = config.get("loader");
+        let field = |key| {
+            loader
+                .and_then(|l| l.get(key))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        return Some(Instance {
+            name: dir_name(dir),
+            launcher: Launcher::GdLauncher,
+            game_dir: dir.to_path_buf(),
+            mods_dir: dir.join("mods"),
+            minecraft: field("mcVersion"),
+            loader: field("loaderType")
+                .map(|kind| with_version(&capitalize(&kind), field("loaderVersion"))),
+        });
+    }
+
+    let json = read_json(&dir.join("instance.json"))?;
+    let version = json.pointer("/game_configuration/version/Standard");
+    let modloader = version
+        .and_then(|v| v.get("modloaders"))
+        .and_then(Value::as_array)
+        .and_then(|l| l.first());
+    let game_dir = dir.join("instance");
+    Some(Instance {
+        name: json
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| dir_name(dir)),
+        launcher: Launcher::GdLauncher,
+        mods_dir: game_dir.join("mods"),
+        game_dir,
+        minecraft: version
+            .and_then(|v| v.get("release"))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        loader: modloader.and_then(|m| {
+            let kind = m.get("type_")?.as_str()?;
+            let version = m.get("version").and_then(Value::as_str).map(str::to_string);
+            Some(with_version(kind, version))
+        }),
+    })
+}
+
+/// CurseForge app: `minecraftinstance.json` with `gameVersion` and a
+/// `baseModLoader.name` such as `forge-47.2.0`.
+fn curseforge_instance(dir: &Path) -> Option<Instance> {
+    let json = read_json(&dir.join("minecraftinstance.json"))?;
+    let loader = json
+        .pointer("/baseModLoader/name")
+        .and_then(Value::as_str)
+        .map(|name| match name.split_once('-') {
+            Some((kind, version)) => with_version(&capitalize(kind), Some(version.to_string())),
+            None => capitalize(name),
+        });
+
+    Some(Instance {
+        name: json
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| dir_name(dir)),
+        launcher: Launcher::CurseForge,
+        game_dir: dir.to_path_buf(),
+        mods_dir: dir.join("mods"),
+        minecraft: json
+            .get("gameVersion")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        loader,
+    })
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
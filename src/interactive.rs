@@ -0,0 +1,145 @@
+use dialoguer::{Input, Select};
+use minecraft_mod_replacer::duplicates;
+use minecraft_mod_replacer::instances;
+use minecraft_mod_replacer::metadata;
+use minecraft_mod_replacer::mods::{self, Candidate};
+use minecraft_mod_replacer::{ReplaceOptions, ReplacePlan};
+use rfd::FileDialog;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::commands::{self, Context};
+
+/// Runs the picker. `force` replaces jars that look like different mods or
+/// break dependencies; otherwise those are asked about, and refused under
+/// `--yes`.
+pub fn run(ctx: Context, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Select the replacement file...");
+    let replacement_path = FileDialog::new()
+        .set_title("Select Replacement .jar File")
+        .add_filter("Jar Files", &["jar"])
+        .pick_file()
+        .ok_or("No file selected")?;
+
+    // Validate extension
+    if !mods::is_jar(&replacement_path) {
+        eprintln!("The selected file is not a .jar file.");
+        return Ok(());
+    }
+
+    let replacement_data = fs::read(&replacement_path)?;
+    let replacement_size = replacement_data.len() as u64;
+
+    println!(
+        "Selected replacement file: {}\n{}\nSize: {} bytes\n",
+        replacement_path.display(),
+        commands::describe_mod(metadata::read_bytes(&replacement_data).ok().as_ref()),
+        replacement_size
+    );
+
+    let mods_path = pick_mods_dir()?;
+    let mods_path = mods_path.as_path();
+    if !mods_path.is_dir() {
+        eprintln!("The specified path is not a valid folder: {:?}", mods_path);
+        return Ok(());
+    }
+
+    let groups = duplicates::find(mods_path)?;
+    if !groups.is_empty() {
+        commands::duplicates::print_groups(&groups);
+        commands::duplicates::fix(mods_path, &groups, ctx)?;
+        println!();
+    }
+
+    let entries = mods::find_candidates(mods_path, replacement_size)?;
+    if entries.is_empty() {
+        println!("No suitable .jar mod files found in {:?}", mods_path);
+        return Ok(());
+    }
+
+    println!("Select a mod file to replace:");
+    let options: Vec<String> = entries
+        .iter()
+        .map(|c| candidate_label(c, replacement_size))
+        .collect();
+
+    let selection = Select::new().items(&options).default(0).interact()?;
+    let target = &entries[selection];
+
+    let mut plan = ReplacePlan::from_bytes(
+        &target.path,
+        &replacement_path,
+        replacement_data,
+        target.size,
+        &ReplaceOptions {
+            force,
+            ..ReplaceOptions::default()
+        },
+    )?;
+
+    println!();
+    commands::replace::print_plan(&plan);
+    if ctx.dry_run {
+        commands::replace::print_dry_run(&plan);
+        return Ok(());
+    }
+    let prompt = format!("Replace '{}'?", target.file_name());
+    if !ctx.confirm(&prompt)? {
+        println!("Aborted.");
+        return Ok(());
+    }
+    // Assuming yes is not forcing: under --yes `execute` refuses these
+    if !ctx.yes && !plan.force && !plan.blocking_mismatches().is_empty() {
+        if !ctx.confirm("The replacement looks like a different mod. Replace anyway?")? {
+            println!("Aborted.");
+            return Ok(());
+        }
+        plan.force = true;
+    }
+    if !ctx.yes && !plan.force && !plan.dependency_errors().is_empty() {
+        if !ctx.confirm("The replacement breaks other mods' dependencies. Replace anyway?")? {
+            println!("Aborted.");
+            return Ok(());
+        }
+        plan.force = true;
+    }
+
+    let backup = plan.execute()?;
+    commands::replace::print_replaced(&plan, backup.as_ref());
+
+    Ok(())
+}
+
+/// Offers the detected instances, falling back to typing a path.
+fn pick_mods_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let found = instances::discover();
+    if !found.is_empty() {
+        let mut options: Vec<String> = found.iter().map(commands::instances::label).collect();
+        options.push("Enter a path manually...".to_string());
+        println!("Select a Minecraft instance:");
+        let selection = Select::new().items(&options).default(0).interact()?;
+        if let Some(instance) = found.get(selection) {
+            return Ok(instance.mods_dir.clone());
+        }
+    }
+
+    let mods_path_str: String = Input::<String>::new()
+        .with_prompt("Enter the full path to your Minecraft 'mods' folder")
+        .interact_text()?;
+    Ok(PathBuf::from(mods_path_str))
+}
+
+pub fn candidate_label(candidate: &Candidate, replacement_size: u64) -> String {
+    format!(
+        "{}{} | {} | {} bytes | Δ {} bytes",
+        candidate.file_name(),
+        if candidate.disabled {
+            " [disabled]"
+        } else {
+            ""
+        },
+        commands::describe_mod(candidate.metadata().ok().as_ref()),
+        candidate.size,
+        candidate.delta(replacement_size)
+    )
+}
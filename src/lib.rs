@@ -0,0 +1,28 @@
+//! Replace Minecraft mod jars while keeping the exact original file size.
+
+pub mod backup;
+pub mod batch;
+pub mod cache;
+pub mod compat;
+pub mod curseforge;
+pub mod deps;
+pub mod diff;
+pub mod duplicates;
+pub mod error;
+pub mod fsio;
+pub mod hashes;
+pub mod instances;
+pub mod lockfile;
+pub mod metadata;
+pub mod mods;
+pub mod mrpack;
+pub mod packwiz;
+pub mod profiles;
+pub mod replace;
+pub mod spec;
+pub mod upgrade;
+pub mod version;
+pub mod zip;
+
+pub use error::{Error, Result};
+pub use replace::{PaddingStrategy, ReplaceOptions, ReplaceOutcome, ReplacePlan, replace_mod};
@@ -0,0 +1,166 @@
+//! A record of what a mods folder contains, to notice changes made outside
+//! the tool.
+//!
+//! ```toml
+//! version = 1
+//!
+//! [[jar]]
+//! file = "sodium-fabric-0.5.8+mc1.20.1.jar"
+//! size = 1043513
+//! sha256 = "…"
+//! sha512 = "…"
+//! mod_id = "sodium"
+//! mod_version = "0.5.8+mc1.20.1"
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backup::state_dir;
+use crate::error::{Error, Result};
+use crate::fsio;
+use crate::hashes::{sha256_hex, sha512_hex};
+use crate::metadata;
+use crate::mods::{self, file_name};
+
+/// Name of the lockfile inside the tool's state folder.
+pub const LOCKFILE: &str = "mods.lock";
+
+const FORMAT_VERSION: u32 = 1;
+
+/// Where the lockfile of the instance owning `mods_dir` is kept unless
+/// another file is given.
+pub fn default_path(mods_dir: &Path) -> PathBuf {
+    state_dir(mods_dir).join(LOCKFILE)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub version: u32,
+    #[serde(default, rename = "jar")]
+    pub jars: Vec<LockedJar>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedJar {
+    /// File name in the mods folder, `.disabled` suffix included.
+    pub file: String,
+    pub size: u64,
+    pub sha256: String,
+    pub sha512: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mod_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mod_version: Option<String>,
+}
+
+impl LockedJar {
+    /// `mod_id mod_version`, for reports.
+    pub fn describe(&self) -> Option<String> {
+        let id = self.mod_id.as_deref()?;
+        Some(match &self.mod_version {
+            Some(version) => format!("{id} {version}"),
+            None => id.to_string(),
+        })
+    }
+}
+
+impl Lockfile {
+    /// Records every jar in `mods_dir`, enabled or disabled.
+    pub fn scan(mods_dir: &Path) -> Result<Self> {
+        let mut jars = Vec::new();
+        for candidate in mods::list_jars(mods_dir)? {
+            let data = fs::read(&candidate.path)?;
+            let primary = metadata::read_bytes(&data)
+                .ok()
+                .and_then(|jar| jar.primary().cloned());
+            jars.push(LockedJar {
+                file: file_name(&candidate.path),
+                size: data.len() as u64,
+                sha256: sha256_hex(&data),
+                sha512: sha512_hex(&data),
+                mod_id: primary.as_ref().map(|m| m.id.clone()),
+                mod_version: primary.and_then(|m| m.version),
+            });
+        }
+        Ok(Self {
+            version: FORMAT_VERSION,
+            jars,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let lock: Lockfile = toml::from_str(&text)
+            .map_err(|err| Error::InvalidLockfile(path.to_path_buf(), err.to_string()))?;
+        if lock.version != FORMAT_VERSION {
+            return Err(Error::InvalidLockfile(
+                path.to_path_buf(),
+                format!("unsupported version {}", lock.version),
+            ));
+        }
+        Ok(lock)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let text = toml::to_string_pretty(self)
+            .map_err(|err| Error::InvalidLockfile(path.to_path_buf(), err.to_string()))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fsio::write_atomic(path, text.as_bytes())
+    }
+
+    /// How `current` differs from this lockfile. A jar that is gone under
+    /// one name and present with the same content under another counts as
+    /// renamed, which includes enabling and disabling it.
+    pub fn changes(&self, current: &Lockfile) -> Vec<Change> {
+        let mut changes = Vec::new();
+        let mut removed: Vec<&LockedJar> = Vec::new();
+        for locked in &self.jars {
+            match current.jars.iter().find(|j| j.file == locked.file) {
+                Some(now) if now.sha256 == locked.sha256 => {}
+                Some(now) => changes.push(Change::Modified {
+                    old: locked.clone(),
+                    new: now.clone(),
+                }),
+                None => removed.push(locked),
+            }
+        }
+        let mut added: Vec<&LockedJar> = current
+            .jars
+            .iter()
+            .filter(|now| !self.jars.iter().any(|j| j.file == now.file))
+            .collect();
+
+        for old in removed {
+            match added.iter().position(|new| new.sha256 == old.sha256) {
+                Some(i) => changes.push(Change::Renamed {
+                    old: old.clone(),
+                    new: added.remove(i).clone(),
+                }),
+                None => changes.push(Change::Removed(old.clone())),
+            }
+        }
+        changes.extend(added.into_iter().cloned().map(Change::Added));
+        changes
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added(LockedJar),
+    Removed(LockedJar),
+    /// Same file name, different content.
+    Modified {
+        old: LockedJar,
+        new: LockedJar,
+    },
+    /// Same content, different file name.
+    Renamed {
+        old: LockedJar,
+        new: LockedJar,
+    },
+}
@@ -0,0 +1,84 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{Dependency, DependencyKind, Environment, Loader, ModMetadata, invalid, read_text};
+use crate::error::Result;
+use crate::zip::Archive;
+
+const FILE: &str = "fabric.mod.json";
+
+#[derive(Debug, Deserialize)]
+struct FabricModJson {
+    id: String,
+    version: Option<String>,
+    name: Option<String>,
+    environment: Option<String>,
+    #[serde(default)]
+    depends: serde_json::Map<String, Value>,
+    #[serde(default)]
+    recommends: serde_json::Map<String, Value>,
+    #[serde(default)]
+    breaks: serde_json::Map<String, Value>,
+    #[serde(default)]
+    conflicts: serde_json::Map<String, Value>,
+    #[serde(default)]
+    provides: Vec<String>,
+    #[serde(default)]
+    mixins: Vec<Value>,
+}
+
+pub(super) fn read(archive: &Archive) -> Result<Option<ModMetadata>> {
+    let Some(text) = read_text(archive, FILE)? else {
+        return Ok(None);
+    };
+    let json: FabricModJson = serde_json::from_str(&text).map_err(|err| invalid(FILE, err))?;
+
+    let mut dependencies = Vec::new();
+    for (map, kind) in [
+        (&json.depends, DependencyKind::Required),
+        (&json.recommends, DependencyKind::Recommended),
+        (&json.breaks, DependencyKind::Breaks),
+        (&json.conflicts, DependencyKind::Conflicts),
+    ] {
+        dependencies.extend(map.iter().map(|(id, versions)| Dependency {
+            id: id.clone(),
+            kind,
+            versions: version_predicate(versions),
+        }));
+    }
+
+    Ok(Some(ModMetadata {
+        id: json.id,
+        name: json.name,
+        version: json.version,
+        loader: Loader::Fabric,
+        minecraft: json.depends.get("minecraft").and_then(version_predicate),
+        environment: json
+            .environment
+            .as_deref()
+            .map(Environment::parse)
+            .unwrap_or_default(),
+        dependencies,
+        provides: json.provides,
+        // A mixin entry is a file name or an object naming it in `config`
+        mixins: json
+            .mixins
+            .iter()
+            .filter_map(|m| m.as_str().or_else(|| m.get("config")?.as_str()))
+            .map(str::to_string)
+            .collect(),
+        source: FILE,
+    }))
+}
+
+/// A Fabric version requirement is a string or an array of alternatives.
+fn version_predicate(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(items) => {
+            let alternatives: Vec<&str> = items.iter().filter_map(Value::as_str).collect();
+            (!alternatives.is_empty()).then(|| alternatives.join(" || "))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use super::{Dependency, DependencyKind, Environment, Loader, ModMetadata, invalid, read_text};
+use crate::error::Result;
+use crate::zip::Archive;
+
+pub(super) const MODS_TOML: &str = "META-INF/mods.toml";
+pub(super) const NEOFORGE_MODS_TOML: &str = "META-INF/neoforge.mods.toml";
+const MANIFEST: &str = "META-INF/MANIFEST.MF";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModsToml {
+    #[serde(default)]
+    client_side_only: bool,
+    #[serde(default)]
+    mods: Vec<ModEntry>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, Vec<DependencyEntry>>,
+    /// NeoForge's `[[mixins]]` tables.
+    #[serde(default)]
+    mixins: Vec<MixinEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MixinEntry {
+    config: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModEntry {
+    mod_id: String,
+    version: Option<String>,
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DependencyEntry {
+    mod_id: String,
+    version_range: Option<String>,
+    /// Forge's original flag, replaced by `type` in newer files.
+    mandatory: Option<bool>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+impl DependencyEntry {
+    fn kind(&self) -> DependencyKind {
+        match self.kind.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            Some("optional") => DependencyKind::Optional,
+            Some("incompatible") => DependencyKind::Breaks,
+            Some("discouraged") => DependencyKind::Conflicts,
+            Some(_) => DependencyKind::Required,
+            None if self.mandatory == Some(false) => DependencyKind::Optional,
+            None => DependencyKind::Required,
+        }
+    }
+}
+
+pub(super) fn read(
+    archive: &Archive,
+    file: &'static str,
+    loader: Loader,
+) -> Result<Vec<ModMetadata>> {
+    let Some(text) = read_text(archive, file)? else {
+        return Ok(Vec::new());
+    };
+    let toml: ModsToml = toml::from_str(&text).map_err(|err| invalid(file, err))?;
+    let manifest = read_text(archive, MANIFEST)?;
+    let jar_version = manifest_value(manifest.as_deref(), "Implementation-Version");
+    // Forge takes mixin configs from the manifest, NeoForge from either place
+    let mut mixins: Vec<String> = manifest_value(manifest.as_deref(), "MixinConfigs")
+        .map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    for entry in &toml.mixins {
+        if !mixins.contains(&entry.config) {
+            mixins.push(entry.config.clone());
+        }
+    }
+
+    Ok(toml
+        .mods
+        .into_iter()
+        .map(|entry| {
+            let declared = toml
+                .dependencies
+                .get(&entry.mod_id)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let minecraft = declared
+                .iter()
+                .find(|d| d.mod_id == "minecraft")
+                .and_then(|d| d.version_range.clone());
+            let dependencies = declared
+                .iter()
+                .map(|d| Dependency {
+                    id: d.mod_id.clone(),
+                    kind: d.kind(),
+                    versions: d.version_range.clone(),
+                })
+                .collect();
+            // Gradle fills this placeholder in from the jar manifest
+            let version = match entry.version.as_deref() {
+                Some("${file.jarVersion}") => jar_version.clone(),
+                _ => entry.version,
+            };
+            ModMetadata {
+                id: entry.mod_id,
+                name: entry.display_name,
+                version,
+                loader,
+                minecraft,
+                environment: if toml.client_side_only {
+                    Environment::Client
+                } else {
+                    Environment::Both
+                },
+                dependencies,
+                provides: Vec::new(),
+                mixins: mixins.clone(),
+                source: file,
+            }
+        })
+        .collect())
+}
+
+/// The value of a main attribute in the jar manifest. Long values are
+/// wrapped onto continuation lines that start with a space.
+fn manifest_value(manifest: Option<&str>, key: &str) -> Option<String> {
+    let mut lines = manifest?.lines().map(|line| line.trim_end_matches('\r'));
+    let first = lines.find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))?;
+    let mut value = first.to_string();
+    for line in lines {
+        let Some(rest) = line.strip_prefix(' ') else {
+            break;
+        };
+        value.push_str(rest);
+    }
+    Some(value.trim().to_string())
+}
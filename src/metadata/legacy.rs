@@ -0,0 +1,52 @@
+use serde::Deserialize;
+
+use super::{Environment, Loader, ModMetadata, invalid, read_text};
+use crate::error::Result;
+use crate::zip::Archive;
+
+const FILE: &str = "mcmod.info";
+
+/// `mcmod.info` is either a bare array or, in version 2, wrapped in an object.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum McModInfo {
+    List(Vec<McMod>),
+    V2 {
+        #[serde(rename = "modList")]
+        mod_list: Vec<McMod>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct McMod {
+    modid: String,
+    name: Option<String>,
+    version: Option<String>,
+    mcversion: Option<String>,
+}
+
+pub(super) fn read(archive: &Archive) -> Result<Vec<ModMetadata>> {
+    let Some(text) = read_text(archive, FILE)? else {
+        return Ok(Vec::new());
+    };
+    let info: McModInfo = serde_json::from_str(&text).map_err(|err| invalid(FILE, err))?;
+    let mods = match info {
+        McModInfo::List(mods) | McModInfo::V2 { mod_list: mods } => mods,
+    };
+
+    Ok(mods
+        .into_iter()
+        .map(|m| ModMetadata {
+            id: m.modid,
+            name: m.name,
+            version: m.version,
+            loader: Loader::Forge,
+            minecraft: m.mcversion,
+            environment: Environment::Both,
+            dependencies: Vec::new(),
+            provides: Vec::new(),
+            mixins: Vec::new(),
+            source: FILE,
+        })
+        .collect())
+}
@@ -0,0 +1,248 @@
+//! Mod metadata read from the descriptor files inside a jar.
+//!
+//! Supports `fabric.mod.json`, `quilt.mod.json`, `META-INF/mods.toml`,
+//! `META-INF/neoforge.mods.toml` and the legacy Forge `mcmod.info`.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::version::{RangeSyntax, Version, VersionRange};
+use crate::zip::Archive;
+
+mod fabric;
+mod forge;
+mod legacy;
+mod nested;
+mod quilt;
+
+pub use nested::NestedJar;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Loader {
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+}
+
+impl fmt::Display for Loader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Fabric => "Fabric",
+            Self::Quilt => "Quilt",
+            Self::Forge => "Forge",
+            Self::NeoForge => "NeoForge",
+        })
+    }
+}
+
+/// Which side of the game a mod is meant to run on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Environment {
+    #[default]
+    Both,
+    Client,
+    Server,
+}
+
+impl Environment {
+    fn parse(s: &str) -> Self {
+        match s {
+            "client" => Self::Client,
+            "server" | "dedicated_server" => Self::Server,
+            _ => Self::Both,
+        }
+    }
+}
+
+/// How a mod relates to another one it names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyKind {
+    /// Must be installed, in a matching version.
+    Required,
+    /// Should be installed; the mod still loads without it.
+    Recommended,
+    /// Not needed, but must be in a matching version when installed.
+    Optional,
+    /// Must not be installed in a matching version.
+    Breaks,
+    /// Known to cause trouble in a matching version, but still loads.
+    Conflicts,
+}
+
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    pub id: String,
+    pub kind: DependencyKind,
+    /// The version range exactly as the descriptor declares it; `None`
+    /// accepts any version.
+    pub versions: Option<String>,
+}
+
+/// One mod declared by a jar.
+#[derive(Debug, Clone)]
+pub struct ModMetadata {
+    pub id: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub loader: Loader,
+    /// The Minecraft version range exactly as the descriptor declares it.
+    pub minecraft: Option<String>,
+    pub environment: Environment,
+    pub dependencies: Vec<Dependency>,
+    /// Other mod ids this mod stands in for.
+    pub provides: Vec<String>,
+    /// Mixin configuration files the mod registers.
+    pub mixins: Vec<String>,
+    /// Descriptor file the data came from.
+    pub source: &'static str,
+}
+
+impl ModMetadata {
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+
+    /// The syntax this mod's version ranges are written in.
+    pub fn range_syntax(&self) -> RangeSyntax {
+        match self.loader {
+            Loader::Forge | Loader::NeoForge => RangeSyntax::Maven,
+            Loader::Fabric | Loader::Quilt => RangeSyntax::Fabric,
+        }
+    }
+
+    /// The mod's version, compared the way its loader compares them.
+    pub fn parsed_version(&self) -> Option<Version> {
+        Version::parse(self.version.as_deref()?, self.range_syntax())
+    }
+
+    pub fn minecraft_range(&self) -> Option<VersionRange> {
+        VersionRange::parse(self.minecraft.as_deref()?, self.range_syntax())
+    }
+
+    /// The versions `dependency` accepts. A range that cannot be parsed
+    /// accepts anything rather than reporting false problems.
+    pub fn dependency_range(&self, dependency: &Dependency) -> VersionRange {
+        dependency
+            .versions
+            .as_deref()
+            .and_then(|v| VersionRange::parse(v, self.range_syntax()))
+            .unwrap_or_else(VersionRange::any)
+    }
+
+    /// Whether this mod answers to `id`, directly or through `provides`.
+    pub fn answers_to(&self, id: &str) -> bool {
+        self.id == id || self.provides.iter().any(|p| p == id)
+    }
+}
+
+/// Everything a jar says about itself. Multi-loader jars can carry several
+/// descriptors and a `mods.toml` can declare several mods.
+#[derive(Debug, Clone, Default)]
+pub struct JarMetadata {
+    pub mods: Vec<ModMetadata>,
+    /// Jars bundled inside this one.
+    pub nested: Vec<NestedJar>,
+    /// Why descriptors that are present could not be read. The other
+    /// descriptors of the jar are still used.
+    pub errors: Vec<String>,
+}
+
+impl JarMetadata {
+    /// The mod the jar is mainly about: the first one of the preferred
+    /// descriptor format.
+    pub fn primary(&self) -> Option<&ModMetadata> {
+        self.mods.first()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mods.is_empty()
+    }
+
+    /// Every bundled jar, however deeply nested, parents before children.
+    pub fn bundled(&self) -> Vec<&NestedJar> {
+        let mut all = Vec::new();
+        for nested in &self.nested {
+            all.push(nested);
+            all.extend(nested.metadata.bundled());
+        }
+        all
+    }
+
+    pub fn loaders(&self) -> Vec<Loader> {
+        let mut loaders: Vec<Loader> = Vec::new();
+        for m in &self.mods {
+            if !loaders.contains(&m.loader) {
+                loaders.push(m.loader);
+            }
+        }
+        loaders
+    }
+}
+
+pub fn read_jar(path: &Path) -> Result<JarMetadata> {
+    let data = fs::read(path)?;
+    read_bytes(&data)
+}
+
+pub fn read_bytes(data: &[u8]) -> Result<JarMetadata> {
+    let archive = Archive::parse(data)?;
+    read_archive(&archive)
+}
+
+/// Reads every descriptor present, in order of preference, and the jars
+/// bundled inside. A broken descriptor is recorded in
+/// [`JarMetadata::errors`]; only a jar whose descriptors are all broken is
+/// an error.
+pub fn read_archive(archive: &Archive) -> Result<JarMetadata> {
+    read_archive_at(archive, 0)
+}
+
+fn read_archive_at(archive: &Archive, depth: usize) -> Result<JarMetadata> {
+    let mut mods = Vec::new();
+    let mut errors = Vec::new();
+    let mut keep = |read: Result<Vec<ModMetadata>>| match read {
+        Ok(found) => mods.extend(found),
+        Err(err) => errors.push(err),
+    };
+    keep(quilt::read(archive).map(Vec::from_iter));
+    keep(fabric::read(archive).map(Vec::from_iter));
+    keep(forge::read(
+        archive,
+        forge::NEOFORGE_MODS_TOML,
+        Loader::NeoForge,
+    ));
+    keep(forge::read(archive, forge::MODS_TOML, Loader::Forge));
+    if mods.is_empty() {
+        match legacy::read(archive) {
+            Ok(found) => mods.extend(found),
+            Err(err) => errors.push(err),
+        }
+    }
+    if mods.is_empty() && !errors.is_empty() {
+        return Err(errors.swap_remove(0));
+    }
+    Ok(JarMetadata {
+        mods,
+        nested: nested::read(archive, depth),
+        errors: errors.iter().map(ToString::to_string).collect(),
+    })
+}
+
+fn read_text(archive: &Archive, file: &'static str) -> Result<Option<String>> {
+    let Some(data) = archive.read_file(file)? else {
+        return Ok(None);
+    };
+    let text = String::from_utf8_lossy(&data);
+    // Some descriptors are saved with a byte order mark
+    Ok(Some(text.trim_start_matches('\u{feff}').to_string()))
+}
+
+fn invalid(file: &'static str, err: impl fmt::Display) -> Error {
+    Error::InvalidMetadata {
+        file,
+        reason: err.to_string(),
+    }
+}
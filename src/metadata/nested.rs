@@ -0,0 +1,139 @@
+//! Jars bundled inside a mod jar ("jar-in-jar").
+//!
+//! Fabric lists them under `jars` in `fabric.mod.json` (stored in
+//! `META-INF/jars/`), Quilt under `quilt_loader.jars`, and Forge's JarJar in
+//! `META-INF/jarjar/metadata.json`.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{JarMetadata, read_archive_at, read_text};
+use crate::zip::Archive;
+
+/// How deep bundled jars are followed. Real mods rarely go past two.
+const MAX_DEPTH: usize = 8;
+
+const JARJAR_METADATA: &str = "META-INF/jarjar/metadata.json";
+
+/// A jar bundled inside another one.
+#[derive(Debug, Clone)]
+pub struct NestedJar {
+    /// Entry name inside the enclosing jar.
+    pub path: String,
+    /// `group:artifact` from JarJar metadata, which also covers plain
+    /// libraries without a mod descriptor.
+    pub artifact: Option<String>,
+    /// The artifact version JarJar metadata declares.
+    pub artifact_version: Option<String>,
+    /// What the bundled jar says about itself, including its own bundled jars.
+    pub metadata: JarMetadata,
+    /// Why the bundled jar could not be read, if it could not.
+    pub error: Option<String>,
+}
+
+impl NestedJar {
+    /// The entry's file name, without the folders leading to it.
+    pub fn file_name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FabricJars {
+    #[serde(default)]
+    jars: Vec<FabricJar>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FabricJar {
+    file: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JarJarMetadata {
+    #[serde(default)]
+    jars: Vec<JarJarEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JarJarEntry {
+    identifier: JarJarIdentifier,
+    version: Option<JarJarVersion>,
+    path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JarJarIdentifier {
+    group: String,
+    artifact: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JarJarVersion {
+    artifact_version: Option<String>,
+}
+
+/// Reads the jars `archive` bundles, recursively. Descriptors that cannot
+/// be parsed here were already reported by the mod metadata itself, so they
+/// only yield no bundled jars.
+pub(super) fn read(archive: &Archive, depth: usize) -> Vec<NestedJar> {
+    if depth >= MAX_DEPTH {
+        return Vec::new();
+    }
+    let mut found: Vec<NestedJar> = Vec::new();
+    let mut add = |path: String, artifact: Option<String>, artifact_version: Option<String>| {
+        if found.iter().any(|n| n.path == path) {
+            return;
+        }
+        let (metadata, error) = match read_entry(archive, &path, depth) {
+            Ok(metadata) => (metadata, None),
+            Err(err) => (JarMetadata::default(), Some(err)),
+        };
+        found.push(NestedJar {
+            path,
+            artifact,
+            artifact_version,
+            metadata,
+            error,
+        });
+    };
+
+    if let Ok(Some(text)) = read_text(archive, "fabric.mod.json")
+        && let Ok(json) = serde_json::from_str::<FabricJars>(&text)
+    {
+        for jar in json.jars {
+            add(jar.file, None, None);
+        }
+    }
+    if let Ok(Some(text)) = read_text(archive, "quilt.mod.json")
+        && let Ok(json) = serde_json::from_str::<Value>(&text)
+        && let Some(jars) = json.pointer("/quilt_loader/jars").and_then(Value::as_array)
+    {
+        for jar in jars.iter().filter_map(Value::as_str) {
+            add(jar.to_string(), None, None);
+        }
+    }
+    if let Ok(Some(text)) = read_text(archive, JARJAR_METADATA)
+        && let Ok(json) = serde_json::from_str::<JarJarMetadata>(&text)
+    {
+        for jar in json.jars {
+            let id = jar.identifier;
+            add(
+                jar.path,
+                Some(format!("{}:{}", id.group, id.artifact)),
+                jar.version.and_then(|v| v.artifact_version),
+            );
+        }
+    }
+    found
+}
+
+fn read_entry(archive: &Archive, path: &str, depth: usize) -> Result<JarMetadata, String> {
+    let data = archive
+        .read_file(path)
+        .map_err(|err| err.to_string())?
+        .ok_or("listed but not present")?;
+    let nested = Archive::parse(&data).map_err(|err| err.to_string())?;
+    read_archive_at(&nested, depth + 1).map_err(|err| err.to_string())
+}
@@ -0,0 +1,155 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{Dependency, DependencyKind, Environment, Loader, ModMetadata, invalid, read_text};
+use crate::error::Result;
+use crate::zip::Archive;
+
+const FILE: &str = "quilt.mod.json";
+
+#[derive(Debug, Deserialize)]
+struct QuiltModJson {
+    quilt_loader: QuiltLoader,
+    #[serde(default)]
+    minecraft: QuiltMinecraft,
+    /// A single mixin config or a list of them.
+    mixin: Option<Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct QuiltMinecraft {
+    environment: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuiltLoader {
+    id: String,
+    version: Option<String>,
+    #[serde(default)]
+    metadata: QuiltMetadata,
+    #[serde(default)]
+    depends: Vec<Value>,
+    #[serde(default)]
+    breaks: Vec<Value>,
+    #[serde(default)]
+    provides: Vec<Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct QuiltMetadata {
+    name: Option<String>,
+}
+
+pub(super) fn read(archive: &Archive) -> Result<Option<ModMetadata>> {
+    let Some(text) = read_text(archive, FILE)? else {
+        return Ok(None);
+    };
+    let json: QuiltModJson = serde_json::from_str(&text).map_err(|err| invalid(FILE, err))?;
+    let mixins = match &json.mixin {
+        Some(Value::String(config)) => vec![config.clone()],
+        Some(Value::Array(configs)) => configs
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let loader = json.quilt_loader;
+    let environment = json
+        .minecraft
+        .environment
+        .as_deref()
+        .map(Environment::parse)
+        .unwrap_or_default();
+
+    let minecraft = loader
+        .depends
+        .iter()
+        .find(|dep| dep.get("id").and_then(Value::as_str) == Some("minecraft"))
+        .and_then(|dep| dep.get("versions"))
+        .and_then(versions);
+
+    let mut dependencies: Vec<Dependency> = loader
+        .depends
+        .iter()
+        .filter_map(|dep| dependency(dep, DependencyKind::Required))
+        .collect();
+    dependencies.extend(
+        loader
+            .breaks
+            .iter()
+            .filter_map(|dep| dependency(dep, DependencyKind::Breaks)),
+    );
+    let provides = loader
+        .provides
+        .iter()
+        .filter_map(|p| match p {
+            Value::String(id) => Some(strip_group(id)),
+            _ => p.get("id").and_then(Value::as_str).map(strip_group),
+        })
+        .collect();
+
+    Ok(Some(ModMetadata {
+        id: loader.id,
+        name: loader.metadata.name,
+        version: loader.version,
+        loader: Loader::Quilt,
+        minecraft,
+        environment,
+        dependencies,
+        provides,
+        mixins,
+        source: FILE,
+    }))
+}
+
+/// A dependency is a bare id or an object with `id`, `versions` and
+/// `optional`.
+fn dependency(value: &Value, kind: DependencyKind) -> Option<Dependency> {
+    if let Value::String(id) = value {
+        return Some(Dependency {
+            id: strip_group(id),
+            kind,
+            versions: None,
+        });
+    }
+    let optional = value.get("optional").and_then(Value::as_bool) == Some(true);
+    Some(Dependency {
+        id: strip_group(value.get("id")?.as_str()?),
+        kind: if optional && kind == DependencyKind::Required {
+            DependencyKind::Optional
+        } else {
+            kind
+        },
+        versions: value.get("versions").and_then(versions),
+    })
+}
+
+/// Quilt ids may carry a Maven group, as in `org.quiltmc:qsl`.
+fn strip_group(id: &str) -> String {
+    id.rsplit(':').next().unwrap_or(id).to_string()
+}
+
+/// Quilt accepts a single version string, an array of alternatives, or an
+/// object with `any`/`all` lists.
+fn versions(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(items) => join(items, " || "),
+        Value::Object(map) => map
+            .get("any")
+            .and_then(Value::as_array)
+            .and_then(|items| join(items, " || "))
+            .or_else(|| {
+                map.get("all")
+                    .and_then(Value::as_array)
+                    .and_then(|items| join(items, " "))
+            }),
+        _ => None,
+    }
+}
+
+fn join(items: &[Value], sep: &str) -> Option<String> {
+    let parts: Vec<&str> = items.iter().filter_map(Value::as_str).collect();
+    (!parts.is_empty()).then(|| parts.join(sep))
+}
@@ -0,0 +1,170 @@
+//! Scanning a mods folder for jars that can be replaced.
+//!
+//! Launchers such as Prism, MultiMC and the CurseForge app disable a mod by
+//! renaming `foo.jar` to `foo.jar.disabled`; both forms are treated as mods.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::metadata::{self, JarMetadata};
+
+/// Suffix launchers append to a jar to disable it.
+pub const DISABLED_SUFFIX: &str = ".disabled";
+
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub path: PathBuf,
+    pub size: u64,
+    pub disabled: bool,
+}
+
+impl Candidate {
+    pub fn file_name(&self) -> String {
+        file_name(&self.path)
+    }
+
+    pub fn metadata(&self) -> Result<JarMetadata> {
+        metadata::read_jar(&self.path)
+    }
+
+    /// Absolute size difference to a replacement of `replacement_size` bytes.
+    pub fn delta(&self, replacement_size: u64) -> u64 {
+        self.size.abs_diff(replacement_size)
+    }
+}
+
+/// Lists the jars in `mods_dir` that are at least `replacement_size` bytes,
+/// closest in size first.
+pub fn find_candidates(mods_dir: &Path, replacement_size: u64) -> Result<Vec<Candidate>> {
+    if !mods_dir.is_dir() {
+        return Err(Error::NotADirectory(mods_dir.to_path_buf()));
+    }
+
+    let mut entries: Vec<Candidate> = fs::read_dir(mods_dir)?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if !is_mod_file(&path) {
+                return None;
+            }
+            let size = fs::metadata(&path).ok()?.len();
+            (replacement_size <= size).then_some(Candidate {
+                disabled: is_disabled(&path),
+                path,
+                size,
+            })
+        })
+        .collect();
+
+    entries.sort_by_key(|c| c.delta(replacement_size));
+    Ok(entries)
+}
+
+/// Lists every jar in `mods_dir`, enabled or not, sorted by file name.
+pub fn list_jars(mods_dir: &Path) -> Result<Vec<Candidate>> {
+    let mut entries = find_candidates(mods_dir, 0)?;
+    entries.sort_by_key(|c| enabled_name(&c.path).to_lowercase());
+    Ok(entries)
+}
+
+/// Finds the jar called `name` in `mods_dir`, whether it is enabled or
+/// disabled. The `.jar` and `.disabled` suffixes may be left off.
+pub fn resolve_target(mods_dir: &Path, name: &str) -> Result<PathBuf> {
+    if !mods_dir.is_dir() {
+        return Err(Error::NotADirectory(mods_dir.to_path_buf()));
+    }
+    [
+        name.to_string(),
+        format!("{name}.jar"),
+        format!("{name}{DISABLED_SUFFIX}"),
+        format!("{name}.jar{DISABLED_SUFFIX}"),
+    ]
+    .into_iter()
+    .map(|file| mods_dir.join(file))
+    .find(|path| path.is_file() && is_mod_file(path))
+    .ok_or_else(|| Error::TargetNotFound {
+        name: name.to_string(),
+        mods_dir: mods_dir.to_path_buf(),
+    })
+}
+
+/// Finds the jar for `target`, given either as a file name or as a mod id.
+/// A mod id has to identify exactly one jar.
+pub fn resolve_target_or_id(mods_dir: &Path, target: &str) -> Result<PathBuf> {
+    match resolve_target(mods_dir, target) {
+        Err(Error::TargetNotFound { .. }) => {}
+        found => return found,
+    }
+    let mut matches: Vec<PathBuf> = list_jars(mods_dir)?
+        .into_iter()
+        .filter(|jar| {
+            jar.metadata()
+                .is_ok_and(|meta| meta.mods.iter().any(|m| m.id == target))
+        })
+        .map(|jar| jar.path)
+        .collect();
+    match matches.len() {
+        0 => Err(Error::TargetNotFound {
+            name: target.to_string(),
+            mods_dir: mods_dir.to_path_buf(),
+        }),
+        1 => Ok(matches.remove(0)),
+        _ => Err(Error::AmbiguousTarget {
+            name: target.to_string(),
+            matches,
+        }),
+    }
+}
+
+pub fn is_jar(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "jar")
+}
+
+/// A jar that has been disabled by appending `.disabled`.
+pub fn is_disabled(path: &Path) -> bool {
+    file_name(path)
+        .strip_suffix(DISABLED_SUFFIX)
+        .is_some_and(|name| is_jar(Path::new(name)))
+}
+
+/// An enabled or disabled jar.
+pub fn is_mod_file(path: &Path) -> bool {
+    is_jar(path) || is_disabled(path)
+}
+
+/// The file name the jar has when enabled.
+pub fn enabled_name(path: &Path) -> String {
+    let name = file_name(path);
+    match name.strip_suffix(DISABLED_SUFFIX) {
+        Some(stripped) if is_disabled(path) => stripped.to_string(),
+        _ => name,
+    }
+}
+
+/// Renames a jar to its enabled or disabled form and returns the new path.
+/// Does nothing if it is already in that state.
+pub fn set_enabled(path: &Path, enabled: bool) -> Result<PathBuf> {
+    if !is_mod_file(path) {
+        return Err(Error::NotAJar(path.to_path_buf()));
+    }
+    let name = enabled_name(path);
+    let new_path = if enabled {
+        path.with_file_name(&name)
+    } else {
+        path.with_file_name(format!("{name}{DISABLED_SUFFIX}"))
+    };
+    if new_path == path {
+        return Ok(new_path);
+    }
+    if new_path.exists() {
+        return Err(Error::AlreadyExists(new_path));
+    }
+    fs::rename(path, &new_path)?;
+    Ok(new_path)
+}
+
+pub fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
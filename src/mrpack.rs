@@ -0,0 +1,442 @@
+//! Modrinth `.mrpack` modpacks: a `modrinth.index.json` listing files by
+//! hash, plus `overrides/` copied over the instance as-is.
+//!
+//! Installing takes the listed files from a local [`FileCache`] instead of
+//! downloading them.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backup::{BackupSet, state_dir};
+use crate::cache::FileCache;
+use crate::error::{Error, Result};
+use crate::fsio;
+use crate::hashes::{HashAlgo, sha1_hex, sha512_hex};
+use crate::instances::Instance;
+use crate::metadata::{self, Environment};
+use crate::mods::{self, file_name};
+use crate::zip::{Archive, ZipWriter};
+
+pub const INDEX_FILE: &str = "modrinth.index.json";
+const OVERRIDES: &str = "overrides/";
+const CLIENT_OVERRIDES: &str = "client-overrides/";
+const SERVER_OVERRIDES: &str = "server-overrides/";
+/// Where the index of the last imported pack is kept, so an export can
+/// reuse its download URLs.
+const SAVED_INDEX: &str = "mrpack-index.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Index {
+    pub format_version: u32,
+    pub game: String,
+    pub version_id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub files: Vec<IndexFile>,
+    /// `minecraft` plus the loader, e.g. `fabric-loader`.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexFile {
+    /// Path relative to the instance's game folder.
+    pub path: String,
+    pub hashes: Hashes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<Env>,
+    #[serde(default)]
+    pub downloads: Vec<String>,
+    pub file_size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hashes {
+    pub sha1: String,
+    pub sha512: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Env {
+    pub client: Requirement,
+    pub server: Requirement,
+}
+
+impl Env {
+    fn for_environment(environment: Environment) -> Self {
+        use Requirement::{Required, Unsupported};
+        match environment {
+            Environment::Both => Self {
+                client: Required,
+                server: Required,
+            },
+            Environment::Client => Self {
+                client: Required,
+                server: Unsupported,
+            },
+            Environment::Server => Self {
+                client: Unsupported,
+                server: Required,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Requirement {
+    Required,
+    Optional,
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Client,
+    Server,
+}
+
+impl IndexFile {
+    pub fn wanted_on(&self, side: Side) -> bool {
+        let requirement = match (self.env, side) {
+            (None, _) => return true,
+            (Some(env), Side::Client) => env.client,
+            (Some(env), Side::Server) => env.server,
+        };
+        requirement != Requirement::Unsupported
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Override {
+    /// Path relative to the instance's game folder.
+    pub path: String,
+    pub side: Option<Side>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pack {
+    pub index: Index,
+    pub overrides: Vec<Override>,
+}
+
+impl Pack {
+    pub fn read(path: &Path) -> Result<Self> {
+        let data = fs::read(path)?;
+        let archive = Archive::parse(&data)?;
+        let index_data = archive
+            .read_file(INDEX_FILE)?
+            .ok_or_else(|| Error::InvalidPack(format!("no {INDEX_FILE}")))?;
+        let index: Index = serde_json::from_slice(&index_data)
+            .map_err(|err| Error::InvalidPack(format!("{INDEX_FILE}: {err}")))?;
+
+        let mut overrides = Vec::new();
+        for entry in archive.entries.iter().filter(|e| !e.is_dir()) {
+            let (side, path) = if let Some(p) = entry.name.strip_prefix(OVERRIDES) {
+                (None, p)
+            } else if let Some(p) = entry.name.strip_prefix(CLIENT_OVERRIDES) {
+                (Some(Side::Client), p)
+            } else if let Some(p) = entry.name.strip_prefix(SERVER_OVERRIDES) {
+                (Some(Side::Server), p)
+            } else {
+                continue;
+            };
+            overrides.push(Override {
+                path: path.to_string(),
+                side,
+                data: archive.read(entry)?,
+            });
+        }
+        Ok(Self { index, overrides })
+    }
+
+    /// Works out every file to write into `game_dir`, taking indexed files
+    /// from `cache`. Nothing is written yet.
+    pub fn plan_install(
+        &self,
+        game_dir: &Path,
+        cache: &FileCache,
+        side: Side,
+    ) -> Result<InstallPlan> {
+        let mut plan = InstallPlan {
+            game_dir: game_dir.to_path_buf(),
+            index: self.index.clone(),
+            files: Vec::new(),
+            missing: Vec::new(),
+        };
+
+        for file in self.index.files.iter().filter(|f| f.wanted_on(side)) {
+            let target = fsio::safe_join(game_dir, &file.path)?;
+            let data = match cache.read(HashAlgo::Sha512, &file.hashes.sha512)? {
+                Some(data) => Some(data),
+                None => cache.read(HashAlgo::Sha1, &file.hashes.sha1)?,
+            };
+            match data {
+                Some(data) => plan.push(target, data, FileSource::Cache),
+                None => plan.missing.push(file.path.clone()),
+            }
+        }
+
+        // Side-specific overrides are applied after, and win over, the common ones
+        let mut overrides: Vec<&Override> = self
+            .overrides
+            .iter()
+            .filter(|o| o.side.is_none_or(|s| s == side))
+            .collect();
+        overrides.sort_by_key(|o| o.side.is_some());
+        for o in overrides {
+            plan.push(
+                fsio::safe_join(game_dir, &o.path)?,
+                o.data.clone(),
+                FileSource::Override,
+            );
+        }
+        Ok(plan)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileSource {
+    Cache,
+    Override,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileChange {
+    New,
+    Replaced,
+    Unchanged,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlannedFile {
+    pub path: PathBuf,
+    pub source: FileSource,
+    pub change: FileChange,
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InstallPlan {
+    pub game_dir: PathBuf,
+    pub index: Index,
+    pub files: Vec<PlannedFile>,
+    /// Indexed paths whose file is not in the cache.
+    pub missing: Vec<String>,
+}
+
+impl InstallPlan {
+    fn push(&mut self, path: PathBuf, data: Vec<u8>, source: FileSource) {
+        let change = match fs::read(&path) {
+            Ok(existing) if existing == data => FileChange::Unchanged,
+            Ok(_) => FileChange::Replaced,
+            Err(_) => FileChange::New,
+        };
+        // A later entry for the same path wins
+        self.files.retain(|f| f.path != path);
+        self.files.push(PlannedFile {
+            path,
+            source,
+            change,
+            data,
+        });
+    }
+
+    pub fn mods_dir(&self) -> PathBuf {
+        self.game_dir.join("mods")
+    }
+
+    /// Writes every changed file, backing up the ones it overwrites, and
+    /// remembers the index for later exports. If anything fails, new files
+    /// are removed again and overwritten ones restored from `backup`.
+    pub fn apply(&self, backup: &mut BackupSet) -> Result<()> {
+        let mut created: Vec<&Path> = Vec::new();
+        let result = self
+            .files
+            .iter()
+            .try_for_each(|file| {
+                self.write(file, backup, &mut created)
+                    .map_err(|err| (file_name(&file.path), err))
+            })
+            .and_then(|()| {
+                self.save_index()
+                    .map_err(|err| (SAVED_INDEX.to_string(), err))
+            });
+
+        if let Err((target, err)) = result {
+            // Best effort: the original error is what matters
+            for path in created.into_iter().rev() {
+                let _ = fs::remove_file(path);
+            }
+            backup.rollback()?;
+            return Err(Error::RolledBack {
+                target,
+                source: Box::new(err),
+            });
+        }
+        Ok(())
+    }
+
+    fn write<'a>(
+        &self,
+        file: &'a PlannedFile,
+        backup: &mut BackupSet,
+        created: &mut Vec<&'a Path>,
+    ) -> Result<()> {
+        match file.change {
+            FileChange::Unchanged => return Ok(()),
+            FileChange::Replaced => {
+                backup.add(&file.path, None)?;
+            }
+            FileChange::New => {}
+        }
+        if let Some(parent) = file.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fsio::write_atomic(&file.path, &file.data)?;
+        if file.change == FileChange::New {
+            created.push(&file.path);
+        }
+        Ok(())
+    }
+
+    fn save_index(&self) -> Result<()> {
+        let saved = state_dir(&self.mods_dir()).join(SAVED_INDEX);
+        if let Some(parent) = saved.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(&self.index)
+            .map_err(|err| Error::InvalidPack(err.to_string()))?;
+        fsio::write_atomic(&saved, &json)
+    }
+}
+
+/// The index saved by the last import into the instance owning `mods_dir`.
+pub fn saved_index(mods_dir: &Path) -> Option<Index> {
+    let data = fs::read(state_dir(mods_dir).join(SAVED_INDEX)).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub name: String,
+    pub version_id: String,
+    pub summary: Option<String>,
+    pub dependencies: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExportSummary {
+    /// Jars listed in the index with a download URL.
+    pub indexed: Vec<String>,
+    /// Jars without a known URL, stored under `overrides/mods/`.
+    pub embedded: Vec<String>,
+}
+
+/// Packs the enabled jars of `mods_dir` into an `.mrpack`.
+///
+/// A jar goes into the index when `known` (usually the index of the pack
+/// the instance was installed from) has a download URL for its hash.
+/// Anything else is embedded under `overrides/mods/`, as the Modrinth app
+/// does for files it cannot link to.
+pub fn export(
+    mods_dir: &Path,
+    options: &ExportOptions,
+    known: Option<&Index>,
+) -> Result<(Vec<u8>, ExportSummary)> {
+    if !options.dependencies.contains_key("minecraft") {
+        return Err(Error::InvalidPack(
+            "a Minecraft version is required".to_string(),
+        ));
+    }
+
+    let mut index = Index {
+        format_version: 1,
+        game: "minecraft".to_string(),
+        version_id: options.version_id.clone(),
+        name: options.name.clone(),
+        summary: options.summary.clone(),
+        files: Vec::new(),
+        dependencies: options.dependencies.clone(),
+    };
+    let mut summary = ExportSummary::default();
+    let mut embedded = Vec::new();
+
+    for jar in mods::list_jars(mods_dir)?
+        .into_iter()
+        .filter(|j| !j.disabled)
+    {
+        let data = fs::read(&jar.path)?;
+        let name = jar.file_name();
+        let sha512 = sha512_hex(&data);
+        let known_file = known
+            .and_then(|k| k.files.iter().find(|f| f.hashes.sha512 == sha512))
+            .filter(|f| !f.downloads.is_empty());
+
+        let Some(known_file) = known_file else {
+            summary.embedded.push(name.clone());
+            embedded.push((format!("{OVERRIDES}mods/{name}"), data));
+            continue;
+        };
+
+        let env = known_file.env.or_else(|| {
+            metadata::read_bytes(&data)
+                .ok()
+                .and_then(|m| m.primary().map(|m| Env::for_environment(m.environment)))
+        });
+        index.files.push(IndexFile {
+            path: format!("mods/{name}"),
+            hashes: Hashes {
+                sha1: sha1_hex(&data),
+                sha512,
+            },
+            env,
+            downloads: known_file.downloads.clone(),
+            file_size: data.len() as u64,
+        });
+        summary.indexed.push(name);
+    }
+
+    let mut writer = ZipWriter::new();
+    let json =
+        serde_json::to_vec_pretty(&index).map_err(|err| Error::InvalidPack(err.to_string()))?;
+    writer.add_file(INDEX_FILE, &json)?;
+    for (path, data) in &embedded {
+        writer.add_file(path, data)?;
+    }
+    Ok((writer.finish()?, summary))
+}
+
+/// The `dependencies` of a pack made from `instance`: its Minecraft version
+/// and, when it names one with a version, its loader.
+pub fn instance_dependencies(instance: &Instance) -> BTreeMap<String, String> {
+    let mut deps = BTreeMap::new();
+    if let Some(minecraft) = &instance.minecraft {
+        deps.insert("minecraft".to_string(), minecraft.clone());
+    }
+    if let Some((name, version)) = instance.loader.as_deref().and_then(|l| l.split_once(' '))
+        && let Some(id) = loader_id(name)
+    {
+        deps.insert(id.to_string(), version.to_string());
+    }
+    deps
+}
+
+/// The `.mrpack` dependency id of a loader, e.g. `fabric-loader`.
+pub fn loader_id(loader: &str) -> Option<&'static str> {
+    match loader.to_ascii_lowercase().as_str() {
+        "fabric" | "fabric-loader" => Some("fabric-loader"),
+        "quilt" | "quilt-loader" => Some("quilt-loader"),
+        "forge" => Some("forge"),
+        "neoforge" => Some("neoforge"),
+        _ => None,
+    }
+}
@@ -0,0 +1,432 @@
+//! packwiz packs: a `pack.toml` pointing at an `index.toml`, which lists
+//! every file of the pack by hash. Mods are usually `.pw.toml` metafiles
+//! naming the jar, its download and its hash.
+//!
+//! ```toml
+//! # mods/sodium.pw.toml
+//! name = "Sodium"
+//! filename = "sodium-fabric-0.5.8+mc1.20.1.jar"
+//! side = "client"
+//!
+//! [download]
+//! url = "https://cdn.modrinth.com/..."
+//! hash-format = "sha1"
+//! hash = "..."
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::backup::BackupSet;
+use crate::cache::FileCache;
+use crate::error::{Error, Result};
+use crate::fsio;
+use crate::hashes::HashAlgo;
+use crate::mods::{self, file_name};
+use crate::mrpack::Side;
+
+/// Hash formats packwiz allows that this tool cannot compute. Files hashed
+/// with them are reported as unverifiable instead of failing the pack.
+const UNSUPPORTED_FORMATS: &[&str] = &["md5"];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackToml {
+    pub name: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub pack_format: Option<String>,
+    pub index: IndexRef,
+    /// `minecraft` plus the loader, e.g. `fabric`.
+    #[serde(default)]
+    pub versions: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct IndexRef {
+    pub file: String,
+    pub hash_format: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct IndexToml {
+    pub hash_format: String,
+    #[serde(default)]
+    pub files: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct IndexEntry {
+    pub file: String,
+    pub hash: String,
+    /// Overrides the index-wide hash format for this file.
+    #[serde(default)]
+    pub hash_format: Option<String>,
+    /// Installs the file under another path.
+    #[serde(default)]
+    pub alias: Option<String>,
+    #[serde(default)]
+    pub metafile: bool,
+    /// Only written when missing, never overwritten.
+    #[serde(default)]
+    pub preserve: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ModToml {
+    pub name: String,
+    pub filename: String,
+    #[serde(default)]
+    pub side: Option<String>,
+    pub download: Download,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Download {
+    #[serde(default)]
+    pub url: Option<String>,
+    pub hash_format: String,
+    pub hash: String,
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+/// One file the pack installs, with the hash the installed copy must have.
+#[derive(Debug, Clone)]
+pub struct PackFile {
+    /// Path relative to the instance's game folder.
+    pub path: String,
+    /// Mod name, for files declared through a metafile.
+    pub name: Option<String>,
+    /// `None` when the file is wanted on both sides.
+    pub side: Option<Side>,
+    /// `None` when the pack uses a hash format this tool cannot compute.
+    pub hash_format: Option<HashAlgo>,
+    pub hash: String,
+    pub url: Option<String>,
+    /// The file inside the pack itself, for files that are not metafiles.
+    pub source: Option<PathBuf>,
+    pub preserve: bool,
+}
+
+impl PackFile {
+    pub fn wanted_on(&self, side: Side) -> bool {
+        self.side.is_none_or(|s| s == side)
+    }
+
+    /// Whether `data` has the hash the pack declares. `None` when the hash
+    /// format is not supported.
+    fn matches(&self, data: &[u8]) -> Option<bool> {
+        let format = self.hash_format?;
+        Some(format.digest(data).eq_ignore_ascii_case(&self.hash))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Pack {
+    pub pack: PackToml,
+    pub files: Vec<PackFile>,
+}
+
+impl Pack {
+    /// Loads `pack.toml`, its index and every metafile, checking each
+    /// against the hash that refers to it.
+    pub fn load(pack_toml: &Path) -> Result<Self> {
+        let pack: PackToml = parse_toml(pack_toml, &fs::read(pack_toml)?)?;
+        let root = pack_toml.parent().unwrap_or(Path::new("."));
+
+        let index_path = fsio::safe_join(root, &pack.index.file)?;
+        let index_data = fs::read(&index_path)?;
+        check_hash(
+            &index_path,
+            &index_data,
+            &pack.index.hash_format,
+            &pack.index.hash,
+        )?;
+        let index: IndexToml = parse_toml(&index_path, &index_data)?;
+        let index_dir = index_path.parent().unwrap_or(root);
+
+        let mut files = Vec::new();
+        for entry in &index.files {
+            let format = entry.hash_format.as_deref().unwrap_or(&index.hash_format);
+            let source = fsio::safe_join(index_dir, &entry.file)?;
+
+            if !entry.metafile {
+                files.push(PackFile {
+                    path: entry.alias.clone().unwrap_or_else(|| entry.file.clone()),
+                    name: None,
+                    side: None,
+                    hash_format: parse_algo(&source, format)?,
+                    hash: entry.hash.clone(),
+                    url: None,
+                    source: Some(source),
+                    preserve: entry.preserve,
+                });
+                continue;
+            }
+
+            let data = fs::read(&source)?;
+            check_hash(&source, &data, format, &entry.hash)?;
+            let meta: ModToml = parse_toml(&source, &data)?;
+            let dir = match entry.file.rsplit_once('/') {
+                Some((dir, _)) => format!("{dir}/"),
+                None => String::new(),
+            };
+            let side = match meta.side.as_deref() {
+                None | Some("both") | Some("") => None,
+                Some("client") => Some(Side::Client),
+                Some("server") => Some(Side::Server),
+                Some(other) => {
+                    return Err(Error::InvalidPack(format!(
+                        "{}: unknown side '{other}'",
+                        source.display()
+                    )));
+                }
+            };
+            files.push(PackFile {
+                path: format!("{dir}{}", meta.filename),
+                name: Some(meta.name),
+                side,
+                hash_format: parse_algo(&source, &meta.download.hash_format)?,
+                hash: meta.download.hash,
+                url: meta.download.url,
+                source: None,
+                preserve: entry.preserve,
+            });
+        }
+        Ok(Self { pack, files })
+    }
+
+    /// Every hash format a cache needs to be indexed by for this pack.
+    pub fn hash_formats(&self) -> Vec<HashAlgo> {
+        let mut formats: Vec<HashAlgo> = Vec::new();
+        for format in self.files.iter().filter_map(|f| f.hash_format) {
+            if !formats.contains(&format) {
+                formats.push(format);
+            }
+        }
+        formats
+    }
+}
+
+fn parse_toml<T: serde::de::DeserializeOwned>(path: &Path, data: &[u8]) -> Result<T> {
+    let text = String::from_utf8_lossy(data);
+    toml::from_str(&text).map_err(|err| Error::InvalidPack(format!("{}: {err}", path.display())))
+}
+
+/// The hash format named `format`, or `None` for one packwiz allows but
+/// this tool cannot compute.
+fn parse_algo(path: &Path, format: &str) -> Result<Option<HashAlgo>> {
+    if UNSUPPORTED_FORMATS.contains(&format.to_ascii_lowercase().as_str()) {
+        return Ok(None);
+    }
+    format
+        .parse()
+        .map(Some)
+        .map_err(|err| Error::InvalidPack(format!("{}: {err}", path.display())))
+}
+
+/// Checks pack metadata against the hash that refers to it. A hash in an
+/// unsupported format cannot be checked and is trusted.
+fn check_hash(path: &Path, data: &[u8], format: &str, expected: &str) -> Result<()> {
+    let Some(algo) = parse_algo(path, format)? else {
+        return Ok(());
+    };
+    if !algo.digest(data).eq_ignore_ascii_case(expected) {
+        return Err(Error::InvalidPack(format!(
+            "{} does not match its {format} in the pack",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    Ok,
+    /// Present with another hash.
+    Modified,
+    Missing,
+    /// A `preserve` file that differs from the pack, which is expected.
+    Preserved,
+    /// Present, but hashed in a format this tool cannot compute.
+    Unverifiable,
+}
+
+#[derive(Debug, Clone)]
+pub struct Status {
+    pub game_dir: PathBuf,
+    pub files: Vec<(PackFile, FileStatus)>,
+    /// Enabled jars in the mods folder that the pack does not list.
+    pub extra: Vec<PathBuf>,
+}
+
+/// Compares the files `pack` declares for `side` against `game_dir`.
+pub fn status(pack: &Pack, game_dir: &Path, side: Side) -> Result<Status> {
+    let mut files = Vec::new();
+    let mut listed = BTreeSet::new();
+    for file in pack.files.iter().filter(|f| f.wanted_on(side)) {
+        let path = fsio::safe_join(game_dir, &file.path)?;
+        let status = match fs::read(&path).map(|data| file.matches(&data)) {
+            Err(_) => FileStatus::Missing,
+            Ok(None) => FileStatus::Unverifiable,
+            Ok(Some(true)) => FileStatus::Ok,
+            Ok(Some(false)) if file.preserve => FileStatus::Preserved,
+            Ok(Some(false)) => FileStatus::Modified,
+        };
+        listed.insert(path);
+        files.push((file.clone(), status));
+    }
+
+    let mods_dir = game_dir.join("mods");
+    let mut extra = Vec::new();
+    if mods_dir.is_dir() {
+        // Disabled jars are not loaded, so they are the user's business
+        for jar in mods::list_jars(&mods_dir)? {
+            if !jar.disabled && !listed.contains(&jar.path) {
+                extra.push(jar.path);
+            }
+        }
+    }
+    Ok(Status {
+        game_dir: game_dir.to_path_buf(),
+        files,
+        extra,
+    })
+}
+
+impl Status {
+    /// Whether a sync would have nothing to do. Unverifiable files cannot
+    /// be synced, so they do not count against it.
+    pub fn is_clean(&self) -> bool {
+        self.extra.is_empty()
+            && self.files.iter().all(|(_, s)| {
+                matches!(
+                    s,
+                    FileStatus::Ok | FileStatus::Preserved | FileStatus::Unverifiable
+                )
+            })
+    }
+
+    /// Works out how to make the game folder match the pack: missing and
+    /// modified files come from the pack itself or from `cache`, and extra
+    /// jars are moved out. Nothing is written yet.
+    pub fn plan_sync(&self, cache: &FileCache) -> Result<SyncPlan> {
+        let mut plan = SyncPlan {
+            writes: Vec::new(),
+            removals: self.extra.clone(),
+            unavailable: Vec::new(),
+        };
+        for (file, status) in &self.files {
+            if !matches!(status, FileStatus::Missing | FileStatus::Modified) {
+                continue;
+            }
+            // Files that cannot be verified are not written either
+            let data = match (&file.source, file.hash_format) {
+                (_, None) => None,
+                (Some(source), Some(_)) => fs::read(source)
+                    .ok()
+                    .filter(|d| file.matches(d) == Some(true)),
+                (None, Some(format)) => cache.read(format, &file.hash)?,
+            };
+            match data {
+                Some(data) => plan.writes.push(SyncWrite {
+                    path: fsio::safe_join(&self.game_dir, &file.path)?,
+                    replaces: *status == FileStatus::Modified,
+                    data,
+                }),
+                None => plan.unavailable.push(file.path.clone()),
+            }
+        }
+        Ok(plan)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncWrite {
+    pub path: PathBuf,
+    /// Whether an existing file is overwritten.
+    pub replaces: bool,
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncPlan {
+    pub writes: Vec<SyncWrite>,
+    /// Jars to move out of the mods folder.
+    pub removals: Vec<PathBuf>,
+    /// Pack paths whose content is neither in the pack nor in the cache,
+    /// or cannot be verified.
+    pub unavailable: Vec<String>,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty() && self.removals.is_empty()
+    }
+
+    /// Writes and removes files, saving everything overwritten or removed
+    /// into `backup` first. If anything fails, new files are removed again
+    /// and the rest restored from `backup`.
+    pub fn apply(&self, backup: &mut BackupSet) -> Result<()> {
+        let mut created: Vec<&Path> = Vec::new();
+        let result = self
+            .writes
+            .iter()
+            .try_for_each(|write| {
+                Self::write(write, backup, &mut created)
+                    .map_err(|err| (file_name(&write.path), err))
+            })
+            .and_then(|()| {
+                self.removals.iter().try_for_each(|path| {
+                    backup
+                        .add(path, None)
+                        .and_then(|_| fs::remove_file(path).map_err(Error::from))
+                        .map_err(|err| (file_name(path), err))
+                })
+            });
+
+        if let Err((target, err)) = result {
+            // Best effort: the original error is what matters
+            for path in created.into_iter().rev() {
+                let _ = fs::remove_file(path);
+            }
+            backup.rollback()?;
+            return Err(Error::RolledBack {
+                target,
+                source: Box::new(err),
+            });
+        }
+        Ok(())
+    }
+
+    fn write<'a>(
+        write: &'a SyncWrite,
+        backup: &mut BackupSet,
+        created: &mut Vec<&'a Path>,
+    ) -> Result<()> {
+        if write.replaces {
+            backup.add(&write.path, None)?;
+        }
+        if let Some(parent) = write.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fsio::write_atomic(&write.path, &write.data)?;
+        if !write.replaces {
+            created.push(&write.path);
+        }
+        Ok(())
+    }
+}
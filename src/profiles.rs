@@ -0,0 +1,324 @@
+//! Named sets of mods for one instance, switched in one step.
+//!
+//! ```toml
+//! store = "../mod-store"     # optional, folder of jars to install from,
+//!                            # relative to this file
+//! common = ["fabric-api"]    # wanted in every profile
+//!
+//! [profiles.performance]
+//! mods = ["sodium", "lithium", "ferritecore"]
+//!
+//! [profiles.shaders]
+//! mods = ["sodium", "iris-mc1.20.1-1.6.4.jar"]
+//! ```
+//!
+//! Mods are named by mod id or by file name. Switching enables the jars a
+//! profile names, disables every other jar, and copies jars the folder does
+//! not have from the store.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::backup::state_dir;
+use crate::deps::{self, ModSet, Problem};
+use crate::error::{Error, Result};
+use crate::fsio;
+use crate::metadata::{self, JarMetadata};
+use crate::mods::{self, Candidate, file_name};
+
+/// Name of the profiles file inside the tool's state folder.
+pub const PROFILES_FILE: &str = "profiles.toml";
+
+/// Where the profiles of the instance owning `mods_dir` are kept unless
+/// another file is given.
+pub fn default_path(mods_dir: &Path) -> PathBuf {
+    state_dir(mods_dir).join(PROFILES_FILE)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfilesFile {
+    pub store: Option<PathBuf>,
+    #[serde(default)]
+    pub common: Vec<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub description: Option<String>,
+    /// Mod ids or file names of the jars the profile contains.
+    #[serde(default)]
+    pub mods: Vec<String>,
+}
+
+impl ProfilesFile {
+    /// Loads a profiles file, resolving the store against the file's folder.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let mut file: ProfilesFile = toml::from_str(&text)
+            .map_err(|err| Error::InvalidProfiles(path.to_path_buf(), err.to_string()))?;
+        if let Some(store) = &mut file.store {
+            *store = path.parent().unwrap_or(Path::new("")).join(&*store);
+        }
+        Ok(file)
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Profile> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| Error::ProfileNotFound(name.to_string()))
+    }
+
+    /// Everything profile `name` wants, the common mods first.
+    pub fn wanted(&self, name: &str) -> Result<Vec<String>> {
+        let mut wanted: Vec<String> = Vec::new();
+        for entry in self.common.iter().chain(&self.get(name)?.mods) {
+            if !wanted.contains(entry) {
+                wanted.push(entry.clone());
+            }
+        }
+        Ok(wanted)
+    }
+
+    /// Works out what switching `mods_dir` to profile `name` takes. `store`
+    /// overrides the store named in the file. Nothing is changed.
+    pub fn plan_switch(
+        &self,
+        name: &str,
+        mods_dir: &Path,
+        store: Option<&Path>,
+    ) -> Result<SwitchPlan> {
+        let wanted = self.wanted(name)?;
+        let jars = read_jars(mods::list_jars(mods_dir)?);
+        let store_jars = match store.or(self.store.as_deref()) {
+            Some(store) => read_jars(
+                mods::list_jars(store)?
+                    .into_iter()
+                    .filter(|c| !c.disabled)
+                    .collect(),
+            ),
+            None => Vec::new(),
+        };
+
+        let mut plan = SwitchPlan {
+            profile: name.to_string(),
+            actions: Vec::new(),
+            unresolved: Vec::new(),
+            conflicts: Vec::new(),
+        };
+        let mut keep = vec![false; jars.len()];
+        for entry in &wanted {
+            let matching: Vec<usize> = (0..jars.len())
+                .filter(|&i| jars[i].matches(entry))
+                .collect();
+            let enabled: Vec<usize> = matching
+                .iter()
+                .copied()
+                .filter(|&i| !jars[i].candidate.disabled)
+                .collect();
+            if !enabled.is_empty() {
+                enabled.iter().for_each(|&i| keep[i] = true);
+            } else if let Some(i) = newest(&jars, &matching) {
+                keep[i] = true;
+            } else {
+                let candidates: Vec<usize> = (0..store_jars.len())
+                    .filter(|&i| store_jars[i].matches(entry))
+                    .collect();
+                match newest(&store_jars, &candidates) {
+                    Some(i) => {
+                        let from = store_jars[i].candidate.path.clone();
+                        let to = mods_dir.join(file_name(&from));
+                        let planned = |action: &SwitchAction| matches!(action, SwitchAction::Install { to: t, .. } if *t == to);
+                        // Another entry already named this store jar
+                        if plan.actions.iter().any(planned)
+                            || plan.conflicts.iter().any(|c| c.to == to)
+                        {
+                            continue;
+                        }
+                        if to.exists() {
+                            plan.conflicts.push(Conflict {
+                                entry: entry.clone(),
+                                from,
+                                to,
+                            });
+                        } else {
+                            plan.actions.push(SwitchAction::Install { from, to });
+                        }
+                    }
+                    None => plan.unresolved.push(entry.clone()),
+                }
+            }
+        }
+
+        for (jar, keep) in jars.iter().zip(keep) {
+            let path = jar.candidate.path.clone();
+            match (keep, jar.candidate.disabled) {
+                (true, true) => plan.actions.push(SwitchAction::Enable(path)),
+                (false, false) => plan.actions.push(SwitchAction::Disable(path)),
+                _ => {}
+            }
+        }
+        Ok(plan)
+    }
+}
+
+/// A jar and what it declares, for matching against profile entries.
+struct Jar {
+    candidate: Candidate,
+    metadata: Option<JarMetadata>,
+}
+
+impl Jar {
+    /// Whether `entry`, a mod id or file name, names this jar.
+    fn matches(&self, entry: &str) -> bool {
+        let name = mods::enabled_name(&self.candidate.path);
+        name == entry
+            || name.strip_suffix(".jar") == Some(entry)
+            || self
+                .metadata
+                .as_ref()
+                .is_some_and(|m| m.mods.iter().any(|m| m.id == entry))
+    }
+}
+
+fn read_jars(candidates: Vec<Candidate>) -> Vec<Jar> {
+    candidates
+        .into_iter()
+        .map(|candidate| Jar {
+            metadata: candidate.metadata().ok(),
+            candidate,
+        })
+        .collect()
+}
+
+/// The jar among `indices` with the highest version, or the first one when
+/// versions cannot be compared.
+fn newest(jars: &[Jar], indices: &[usize]) -> Option<usize> {
+    let version = |i: usize| {
+        jars[i]
+            .metadata
+            .as_ref()
+            .and_then(|m| m.primary())
+            .and_then(|m| m.parsed_version())
+    };
+    indices.iter().copied().reduce(|best, i| {
+        match version(i)
+            .zip(version(best))
+            .and_then(|(a, b)| a.compare(&b))
+        {
+            Some(Ordering::Greater) => i,
+            _ => best,
+        }
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwitchAction {
+    Enable(PathBuf),
+    Disable(PathBuf),
+    /// Copy a jar from the store into the mods folder.
+    Install {
+        from: PathBuf,
+        to: PathBuf,
+    },
+}
+
+/// A store jar that cannot be installed because a file of the same name,
+/// which does not match the profile entry, is in the way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub entry: String,
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchPlan {
+    pub profile: String,
+    pub actions: Vec<SwitchAction>,
+    /// Profile entries that match no jar in the folder or the store.
+    pub unresolved: Vec<String>,
+    /// Store jars whose file name is already taken in the mods folder.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl SwitchPlan {
+    /// Whether the folder already matches the profile.
+    pub fn is_current(&self) -> bool {
+        self.actions.is_empty() && self.unresolved.is_empty() && self.conflicts.is_empty()
+    }
+
+    /// Dependency problems the folder would have after the switch that it
+    /// does not have now.
+    pub fn dependency_problems(&self, mods_dir: &Path) -> Result<Vec<Problem>> {
+        let before = ModSet::scan(mods_dir)?;
+        let mut after = before.clone();
+        for action in &self.actions {
+            match action {
+                SwitchAction::Enable(path) => {
+                    if let Ok(jar) = metadata::read_jar(path) {
+                        after.swap(&path.with_file_name(mods::enabled_name(path)), Some(&jar));
+                    }
+                }
+                SwitchAction::Disable(path) => after.swap(path, None),
+                SwitchAction::Install { from, to } => {
+                    if let Ok(jar) = metadata::read_jar(from) {
+                        after.swap(to, Some(&jar));
+                    }
+                }
+            }
+        }
+        Ok(deps::introduced(&before, &after))
+    }
+
+    /// Carries out every action. If one fails, those already done are
+    /// undone: renames are reversed and installed jars removed. A plan with
+    /// conflicts is refused before anything is changed.
+    pub fn apply(&self) -> Result<()> {
+        if let Some(conflict) = self.conflicts.first() {
+            return Err(Error::AlreadyExists(conflict.to.clone()));
+        }
+        let mut done: Vec<(&SwitchAction, PathBuf)> = Vec::new();
+        for action in &self.actions {
+            let result = match action {
+                SwitchAction::Enable(path) => mods::set_enabled(path, true),
+                SwitchAction::Disable(path) => mods::set_enabled(path, false),
+                SwitchAction::Install { from, to } => fs::read(from)
+                    .map_err(Error::from)
+                    .and_then(|data| fsio::write_jar_atomic(to, &data))
+                    .map(|()| to.clone()),
+            };
+            match result {
+                Ok(path) => done.push((action, path)),
+                Err(err) => {
+                    for (action, path) in done.into_iter().rev() {
+                        // Best effort: the original error is what matters
+                        let _ = match action {
+                            SwitchAction::Enable(_) => mods::set_enabled(&path, false).map(drop),
+                            SwitchAction::Disable(_) => mods::set_enabled(&path, true).map(drop),
+                            SwitchAction::Install { .. } => {
+                                fs::remove_file(&path).map_err(Error::from)
+                            }
+                        };
+                    }
+                    let target = match action {
+                        SwitchAction::Enable(path) | SwitchAction::Disable(path) => path,
+                        SwitchAction::Install { to, .. } => to,
+                    };
+                    return Err(Error::RolledBack {
+                        target: file_name(target),
+                        source: Box::new(err),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
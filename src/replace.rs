@@ -0,0 +1,292 @@
+//! Planning and performing a size-preserving jar replacement.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backup::{BackupSet, BackupStore};
+use crate::compat::{self, Mismatch, VersionDelta};
+use crate::deps::{self, ModSet, Problem};
+use crate::error::{Error, Result};
+use crate::fsio;
+use crate::metadata::{self, JarMetadata};
+use crate::mods::is_jar;
+use crate::zip::{self, VerifyReport, ZipError};
+
+/// How the replacement is grown to the size of the original.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingStrategy {
+    /// Sizes already match.
+    None,
+    /// Padding goes into the End of Central Directory comment.
+    ZipComment,
+    /// Null bytes appended after the archive. May upset strict ZIP parsers.
+    Append,
+}
+
+impl fmt::Display for PaddingStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::None => "no padding",
+            Self::ZipComment => "ZIP comment",
+            Self::Append => "simple append",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplaceOptions {
+    /// Fall back to appending null bytes when the ZIP comment cannot hold the padding.
+    pub allow_append: bool,
+    /// Copy the original into the backup store before overwriting it.
+    pub backup: bool,
+    /// Replace even when the replacement looks like a different mod.
+    pub force: bool,
+}
+
+impl Default for ReplaceOptions {
+    fn default() -> Self {
+        Self {
+            allow_append: true,
+            backup: true,
+            force: false,
+        }
+    }
+}
+
+/// Everything needed to overwrite `target` with `replacement`, computed up front.
+#[derive(Debug, Clone)]
+pub struct ReplacePlan {
+    pub target: PathBuf,
+    pub replacement: PathBuf,
+    pub original_size: u64,
+    pub replacement_size: u64,
+    pub strategy: PaddingStrategy,
+    /// Why the ZIP comment could not be used, when `strategy` is `Append`.
+    pub comment_error: Option<ZipError>,
+    /// What the jars say about themselves, if they could be read.
+    pub target_metadata: Option<JarMetadata>,
+    pub replacement_metadata: Option<JarMetadata>,
+    /// Ways in which the replacement differs from the target.
+    pub mismatches: Vec<Mismatch>,
+    /// Whether the replacement is a newer or older version of the same mod.
+    pub version_change: Option<VersionDelta>,
+    /// Dependency problems in the mods folder that the replacement would
+    /// cause.
+    pub dependency_problems: Vec<Problem>,
+    pub backup: bool,
+    pub force: bool,
+    output: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplaceOutcome {
+    pub plan: ReplacePlan,
+    /// Where the original was saved, unless backups were turned off.
+    pub backup: Option<BackupSet>,
+}
+
+impl ReplacePlan {
+    pub fn new(target: &Path, replacement: &Path, options: &ReplaceOptions) -> Result<Self> {
+        Self::read(target, replacement, options, None)
+    }
+
+    /// Like [`ReplacePlan::new`], judging dependencies against `mods`, the
+    /// already scanned folder of `target`, instead of scanning it again.
+    pub fn with_mod_set(
+        target: &Path,
+        replacement: &Path,
+        options: &ReplaceOptions,
+        mods: &ModSet,
+    ) -> Result<Self> {
+        Self::read(target, replacement, options, Some(mods))
+    }
+
+    /// Builds a plan from replacement bytes that are already in memory.
+    pub fn from_bytes(
+        target: &Path,
+        replacement: &Path,
+        data: Vec<u8>,
+        original_size: u64,
+        options: &ReplaceOptions,
+    ) -> Result<Self> {
+        Self::build(target, replacement, data, original_size, options, None)
+    }
+
+    fn read(
+        target: &Path,
+        replacement: &Path,
+        options: &ReplaceOptions,
+        mods: Option<&ModSet>,
+    ) -> Result<Self> {
+        if !is_jar(replacement) {
+            return Err(Error::NotAJar(replacement.to_path_buf()));
+        }
+        let data = fs::read(replacement)?;
+        let original_size = fs::metadata(target)?.len();
+        Self::build(target, replacement, data, original_size, options, mods)
+    }
+
+    /// `mods` is the target's folder, or `None` to scan it here.
+    fn build(
+        target: &Path,
+        replacement: &Path,
+        data: Vec<u8>,
+        original_size: u64,
+        options: &ReplaceOptions,
+        mods: Option<&ModSet>,
+    ) -> Result<Self> {
+        let replacement_size = data.len() as u64;
+        if replacement_size > original_size {
+            return Err(Error::ReplacementTooLarge {
+                replacement: replacement_size,
+                original: original_size,
+            });
+        }
+
+        let target_metadata = metadata::read_jar(target).ok();
+        let replacement_metadata = metadata::read_bytes(&data).ok();
+        let mismatches = compat::compare(target_metadata.as_ref(), replacement_metadata.as_ref());
+        let version_change = match (&target_metadata, &replacement_metadata) {
+            (Some(t), Some(r)) => compat::version_change(t, r),
+            _ => None,
+        };
+        // A replacement without metadata cannot be judged
+        let dependency_problems = match &replacement_metadata {
+            Some(jar) => {
+                let mods_dir = target.parent().unwrap_or(Path::new("."));
+                let scanned = match mods {
+                    Some(_) => None,
+                    None => ModSet::scan(mods_dir).ok(),
+                };
+                match mods.or(scanned.as_ref()) {
+                    Some(before) => {
+                        let mut after = before.clone();
+                        after.swap(target, Some(jar));
+                        deps::introduced(before, &after)
+                    }
+                    None => Vec::new(),
+                }
+            }
+            None => Vec::new(),
+        };
+
+        let padding_needed = (original_size - replacement_size) as usize;
+        let (strategy, comment_error, output) = if padding_needed == 0 {
+            (PaddingStrategy::None, None, data)
+        } else {
+            match zip::pad_zip_file(data.clone(), padding_needed) {
+                Ok(padded) => (PaddingStrategy::ZipComment, None, padded),
+                Err(err) if options.allow_append => (
+                    PaddingStrategy::Append,
+                    Some(err),
+                    zip::append_padding(data, padding_needed)?,
+                ),
+                Err(err) => return Err(err.into()),
+            }
+        };
+
+        Ok(Self {
+            target: target.to_path_buf(),
+            replacement: replacement.to_path_buf(),
+            original_size,
+            replacement_size,
+            strategy,
+            comment_error,
+            target_metadata,
+            replacement_metadata,
+            mismatches,
+            version_change,
+            dependency_problems,
+            backup: options.backup,
+            force: options.force,
+            output,
+        })
+    }
+
+    pub fn padding(&self) -> u64 {
+        self.original_size - self.replacement_size
+    }
+
+    /// Mismatches that stop the replacement unless it is forced.
+    pub fn blocking_mismatches(&self) -> Vec<&Mismatch> {
+        self.mismatches.iter().filter(|m| m.is_blocking()).collect()
+    }
+
+    /// Dependency problems that stop the replacement unless it is forced.
+    pub fn dependency_errors(&self) -> Vec<&Problem> {
+        self.dependency_problems
+            .iter()
+            .filter(|p| p.is_error())
+            .collect()
+    }
+
+    /// Whether `execute` would refuse to go on.
+    pub fn is_blocked(&self) -> bool {
+        let clean = self.blocking_mismatches().is_empty() && self.dependency_errors().is_empty();
+        !self.force && !clean
+    }
+
+    /// Runs the same verification the write will, on the bytes in memory.
+    pub fn verify_output(&self) -> std::result::Result<VerifyReport, ZipError> {
+        zip::verify(&self.output)
+    }
+
+    /// The bytes that will be written over the target.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// Writes the replacement, first backing up the original into a new
+    /// backup set unless backups were turned off.
+    pub fn execute(&self) -> Result<Option<BackupSet>> {
+        self.check()?;
+        if !self.backup {
+            self.write()?;
+            return Ok(None);
+        }
+        let mods_dir = self.target.parent().unwrap_or(Path::new("."));
+        let mut backup = BackupStore::for_mods_dir(mods_dir).begin("replace")?;
+        self.execute_into(&mut backup)?;
+        Ok(Some(backup))
+    }
+
+    /// Backs up the original into an existing set, then writes the replacement.
+    pub fn execute_into(&self, backup: &mut BackupSet) -> Result<()> {
+        self.check()?;
+        backup.add(&self.target, Some(&self.replacement))?;
+        self.write()
+    }
+
+    /// Refuses to go on when the jars look unrelated or the replacement
+    /// breaks other mods' dependencies, unless forced.
+    fn check(&self) -> Result<()> {
+        if self.force {
+            return Ok(());
+        }
+        let blocking = self.blocking_mismatches();
+        if !blocking.is_empty() {
+            return Err(Error::Mismatch(blocking.into_iter().cloned().collect()));
+        }
+        let broken = self.dependency_errors();
+        if !broken.is_empty() {
+            return Err(Error::Dependencies(broken.into_iter().cloned().collect()));
+        }
+        Ok(())
+    }
+
+    fn write(&self) -> Result<()> {
+        fsio::write_jar_atomic(&self.target, &self.output)
+    }
+}
+
+/// Overwrites `target` with `replacement`, padded to the original size.
+pub fn replace_mod(
+    target: &Path,
+    replacement: &Path,
+    options: &ReplaceOptions,
+) -> Result<ReplaceOutcome> {
+    let plan = ReplacePlan::new(target, replacement, options)?;
+    let backup = plan.execute()?;
+    Ok(ReplaceOutcome { plan, backup })
+}
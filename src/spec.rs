@@ -0,0 +1,333 @@
+//! The mods an instance should have, as a team keeps them in version
+//! control, and what it takes to make a mods folder match.
+//!
+//! ```toml
+//! name = "Team server"        # optional
+//!
+//! [[mod]]
+//! id = "sodium"
+//! version = "0.5.8+mc1.20.1"  # optional
+//! sha512 = "…"                # sha1, sha256 or sha512, optional
+//! file = "sodium.jar"         # optional name to install the jar under
+//! ```
+//!
+//! A jar satisfies an entry when it has one of the entry's hashes, or,
+//! for entries without hashes, when it declares the mod id in the given
+//! version. Running the same spec twice changes nothing the second time.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::backup::BackupSet;
+use crate::cache::FileCache;
+use crate::error::{Error, Result};
+use crate::fsio;
+use crate::hashes::HashAlgo;
+use crate::metadata::{self, JarMetadata};
+use crate::mods::{self, file_name};
+use crate::version::Version;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackSpec {
+    pub name: Option<String>,
+    #[serde(default, rename = "mod")]
+    pub mods: Vec<SpecEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpecEntry {
+    pub id: String,
+    pub version: Option<String>,
+    pub sha1: Option<String>,
+    pub sha256: Option<String>,
+    pub sha512: Option<String>,
+    /// File name to install the jar under, instead of the cached file's.
+    pub file: Option<String>,
+}
+
+impl SpecEntry {
+    /// The hashes the entry pins, strongest first.
+    pub fn hashes(&self) -> Vec<(HashAlgo, &str)> {
+        [
+            (HashAlgo::Sha512, &self.sha512),
+            (HashAlgo::Sha256, &self.sha256),
+            (HashAlgo::Sha1, &self.sha1),
+        ]
+        .into_iter()
+        .filter_map(|(algo, hash)| Some((algo, hash.as_deref()?)))
+        .collect()
+    }
+
+    /// `id version`, for reports.
+    pub fn describe(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{} {version}", self.id),
+            None => self.id.clone(),
+        }
+    }
+
+    fn matches(&self, data: &[u8], jar: Option<&JarMetadata>) -> bool {
+        let hashes = self.hashes();
+        if !hashes.is_empty() {
+            return hashes
+                .iter()
+                .any(|(algo, hash)| algo.digest(data).eq_ignore_ascii_case(hash));
+        }
+        jar.is_some_and(|jar| {
+            jar.mods.iter().any(|m| {
+                m.id == self.id
+                    && self.version.as_ref().is_none_or(|wanted| {
+                        m.version.as_ref() == Some(wanted)
+                            || m.parsed_version()
+                                .zip(Version::parse(wanted, m.range_syntax()))
+                                .is_some_and(|(have, want)| have == want)
+                    })
+            })
+        })
+    }
+}
+
+impl PackSpec {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let spec: PackSpec = toml::from_str(&text)
+            .map_err(|err| Error::InvalidPlan(path.to_path_buf(), err.to_string()))?;
+        for entry in &spec.mods {
+            if let Some(file) = &entry.file
+                && (!mods::is_jar(Path::new(file)) || file.contains(['/', '\\']))
+            {
+                return Err(Error::InvalidPlan(
+                    path.to_path_buf(),
+                    format!("'{file}' for {} is not a plain .jar file name", entry.id),
+                ));
+            }
+        }
+        Ok(spec)
+    }
+
+    /// The hash formats the spec pins, for opening a cache.
+    pub fn hash_formats(&self) -> Vec<HashAlgo> {
+        let mut algos: Vec<HashAlgo> = Vec::new();
+        for (algo, _) in self.mods.iter().flat_map(SpecEntry::hashes) {
+            if !algos.contains(&algo) {
+                algos.push(algo);
+            }
+        }
+        algos
+    }
+
+    /// Works out what makes `mods_dir` match the spec. Missing jars are
+    /// looked up in `cache`, by hash or, for entries without one, by mod id
+    /// and version. Nothing is changed.
+    pub fn reconcile(&self, mods_dir: &Path, cache: Option<&FileCache>) -> Result<Reconcile> {
+        let jars: Vec<(mods::Candidate, Vec<u8>, Option<JarMetadata>)> = mods::list_jars(mods_dir)?
+            .into_iter()
+            .map(|candidate| {
+                let data = fs::read(&candidate.path)?;
+                let jar = metadata::read_bytes(&data).ok();
+                Ok((candidate, data, jar))
+            })
+            .collect::<Result<_>>()?;
+
+        let mut plan = Reconcile::default();
+        let mut used = vec![false; jars.len()];
+        let mut pending: Vec<&SpecEntry> = Vec::new();
+        for entry in &self.mods {
+            // An enabled jar first, a disabled one to enable after that
+            let found = [false, true].into_iter().find_map(|disabled| {
+                (0..jars.len()).find(|&i| {
+                    !used[i]
+                        && jars[i].0.disabled == disabled
+                        && entry.matches(&jars[i].1, jars[i].2.as_ref())
+                })
+            });
+            match found {
+                Some(i) => {
+                    used[i] = true;
+                    if jars[i].0.disabled {
+                        plan.enables.push(jars[i].0.path.clone());
+                    } else {
+                        plan.satisfied += 1;
+                    }
+                }
+                None => pending.push(entry),
+            }
+        }
+
+        // Enabled jars the spec does not ask for leave the folder
+        plan.removals = jars
+            .iter()
+            .zip(&used)
+            .filter(|((candidate, _, _), used)| !candidate.disabled && !**used)
+            .map(|((candidate, _, _), _)| candidate.path.clone())
+            .collect();
+
+        // Names the plan already gives a jar: enabled jars and earlier installs
+        let mut planned: Vec<PathBuf> = plan
+            .enables
+            .iter()
+            .map(|path| path.with_file_name(mods::enabled_name(path)))
+            .collect();
+        for entry in pending {
+            let Some(cache) = cache else {
+                plan.unresolved
+                    .push((entry.describe(), "no cache to install from".to_string()));
+                continue;
+            };
+            let Some((source, data)) = find_in_cache(entry, cache)? else {
+                plan.unresolved
+                    .push((entry.describe(), "not in the cache".to_string()));
+                continue;
+            };
+            let name = entry.file.clone().unwrap_or_else(|| file_name(&source));
+            let path = mods_dir.join(&name);
+            if planned.contains(&path) {
+                plan.unresolved.push((
+                    entry.describe(),
+                    format!("{name} is planned for another entry"),
+                ));
+                continue;
+            }
+            let replaces = path.exists();
+            if replaces {
+                // Only a jar that is on its way out may be overwritten
+                match plan.removals.iter().position(|p| *p == path) {
+                    Some(i) => {
+                        plan.removals.remove(i);
+                    }
+                    None => {
+                        plan.unresolved
+                            .push((entry.describe(), format!("{name} is already taken")));
+                        continue;
+                    }
+                }
+            }
+            planned.push(path.clone());
+            plan.installs.push(Install {
+                id: entry.describe(),
+                source,
+                path,
+                replaces,
+                data,
+            });
+        }
+        Ok(plan)
+    }
+}
+
+/// A cached jar for `entry`, checked against its hash. Entries without
+/// hashes take the first cached jar that declares the mod in the version.
+fn find_in_cache(entry: &SpecEntry, cache: &FileCache) -> Result<Option<(PathBuf, Vec<u8>)>> {
+    for (algo, hash) in entry.hashes() {
+        if let Some(data) = cache.read(algo, hash)? {
+            let path = cache.find(algo, hash).map(|f| f.path.clone());
+            return Ok(path.map(|path| (path, data)));
+        }
+    }
+    if !entry.hashes().is_empty() {
+        return Ok(None);
+    }
+    for file in cache.files() {
+        if !mods::is_jar(&file.path) {
+            continue;
+        }
+        let data = fs::read(&file.path)?;
+        if entry.matches(&data, metadata::read_bytes(&data).ok().as_ref()) {
+            return Ok(Some((file.path.clone(), data)));
+        }
+    }
+    Ok(None)
+}
+
+#[derive(Debug, Clone)]
+pub struct Install {
+    /// The spec entry, as `id version`.
+    pub id: String,
+    /// The cached jar.
+    pub source: PathBuf,
+    pub path: PathBuf,
+    /// Whether a jar of the same name is overwritten.
+    pub replaces: bool,
+    data: Vec<u8>,
+}
+
+/// What makes a mods folder match a spec.
+#[derive(Debug, Clone, Default)]
+pub struct Reconcile {
+    /// Entries already satisfied by an enabled jar.
+    pub satisfied: usize,
+    /// Disabled jars that satisfy an entry.
+    pub enables: Vec<PathBuf>,
+    pub installs: Vec<Install>,
+    /// Enabled jars the spec does not ask for.
+    pub removals: Vec<PathBuf>,
+    /// Entries that cannot be satisfied, with the reason.
+    pub unresolved: Vec<(String, String)>,
+}
+
+impl Reconcile {
+    pub fn is_empty(&self) -> bool {
+        self.enables.is_empty() && self.installs.is_empty() && self.removals.is_empty()
+    }
+
+    /// Removes extras into `backup`, enables and installs jars. Overwritten
+    /// jars are saved into `backup` too. If a step fails, the ones already
+    /// done are undone: new jars are removed, enabled jars disabled again
+    /// and everything else restored from `backup`.
+    pub fn apply(&self, backup: &mut BackupSet) -> Result<()> {
+        let mut enabled = Vec::new();
+        let mut installed = Vec::new();
+        if let Err((target, err)) = self.run(backup, &mut enabled, &mut installed) {
+            // Best effort: the original error is what matters
+            for path in installed.into_iter().rev() {
+                let _ = fs::remove_file(path);
+            }
+            for path in enabled.into_iter().rev() {
+                let _ = mods::set_enabled(&path, false);
+            }
+            backup.rollback()?;
+            return Err(Error::RolledBack {
+                target,
+                source: Box::new(err),
+            });
+        }
+        Ok(())
+    }
+
+    /// Carries out the steps, recording the renamed and newly written jars.
+    /// Fails with the file name of the step that went wrong.
+    fn run<'a>(
+        &'a self,
+        backup: &mut BackupSet,
+        enabled: &mut Vec<PathBuf>,
+        installed: &mut Vec<&'a Path>,
+    ) -> std::result::Result<(), (String, Error)> {
+        for path in &self.removals {
+            backup
+                .add(path, None)
+                .and_then(|_| fs::remove_file(path).map_err(Error::from))
+                .map_err(|err| (file_name(path), err))?;
+        }
+        for path in &self.enables {
+            let now = mods::set_enabled(path, true).map_err(|err| (file_name(path), err))?;
+            enabled.push(now);
+        }
+        for install in &self.installs {
+            let fail = |err| (file_name(&install.path), err);
+            if install.replaces {
+                backup
+                    .add(&install.path, Some(&install.source))
+                    .map_err(fail)?;
+            }
+            fsio::write_jar_atomic(&install.path, &install.data).map_err(fail)?;
+            if !install.replaces {
+                installed.push(&install.path);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+//! Pairs a folder of newer jars, such as a downloads folder, with the
+//! installed jars of the same mods.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::batch::{PlanEntry, PlanFile};
+use crate::compat::{self, VersionChange};
+use crate::error::Result;
+use crate::metadata::{JarMetadata, ModMetadata};
+use crate::mods::{self, Candidate, file_name};
+
+/// An installed jar and the new jar of the same mod.
+#[derive(Debug, Clone)]
+pub struct UpgradePair {
+    pub mod_id: String,
+    pub installed: Candidate,
+    pub update: Candidate,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub change: VersionChange,
+}
+
+impl UpgradePair {
+    /// Whether the new jar can be padded to the installed jar's size.
+    pub fn fits(&self) -> bool {
+        self.update.size <= self.installed.size
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Suggestions {
+    /// Upgrades first, then same versions, versions that cannot be
+    /// compared, and downgrades.
+    pub pairs: Vec<UpgradePair>,
+    /// New jars whose mod is not installed, or that have no readable
+    /// metadata.
+    pub unmatched: Vec<PathBuf>,
+    /// New jars left out because the folder has a newer jar of the same mod.
+    pub superseded: Vec<PathBuf>,
+    /// Mod ids installed as several enabled jars, which cannot be paired.
+    pub ambiguous: Vec<(String, Vec<PathBuf>)>,
+}
+
+struct Jar {
+    candidate: Candidate,
+    metadata: JarMetadata,
+}
+
+impl Jar {
+    fn primary(&self) -> &ModMetadata {
+        // Only jars with a primary mod are kept
+        &self.metadata.mods[0]
+    }
+}
+
+fn read(dir: &Path) -> Result<(Vec<Jar>, Vec<PathBuf>)> {
+    let mut jars = Vec::new();
+    let mut unreadable = Vec::new();
+    for candidate in mods::list_jars(dir)? {
+        match candidate.metadata() {
+            Ok(metadata) if !metadata.is_empty() => jars.push(Jar {
+                candidate,
+                metadata,
+            }),
+            _ => unreadable.push(candidate.path),
+        }
+    }
+    Ok((jars, unreadable))
+}
+
+/// Reads the mod ids of the jars in `from` and pairs each with the
+/// installed jar in `mods_dir` that declares the same mod. Of several new
+/// jars of one mod, only the newest is paired.
+pub fn suggest(mods_dir: &Path, from: &Path) -> Result<Suggestions> {
+    let (installed, _) = read(mods_dir)?;
+    let (updates, mut unmatched) = read(from)?;
+    let mut suggestions = Suggestions::default();
+
+    let mut by_id: BTreeMap<String, Vec<Jar>> = BTreeMap::new();
+    for jar in updates.into_iter().filter(|j| !j.candidate.disabled) {
+        by_id.entry(jar.primary().id.clone()).or_default().push(jar);
+    }
+
+    for (mod_id, mut candidates) in by_id {
+        let newest = (1..candidates.len()).fold(0, |best, i| {
+            let newer = candidates[i]
+                .primary()
+                .parsed_version()
+                .zip(candidates[best].primary().parsed_version())
+                .and_then(|(a, b)| a.compare(&b));
+            if newer == Some(Ordering::Greater) {
+                i
+            } else {
+                best
+            }
+        });
+        let update = candidates.swap_remove(newest);
+        suggestions
+            .superseded
+            .extend(candidates.into_iter().map(|j| j.candidate.path));
+
+        let mut matches: Vec<&Jar> = installed
+            .iter()
+            .filter(|j| j.primary().id == mod_id)
+            .collect();
+        // A disabled copy next to an enabled one is not what gets updated
+        if matches.iter().any(|j| !j.candidate.disabled) {
+            matches.retain(|j| !j.candidate.disabled);
+        }
+        let old = match matches.as_slice() {
+            [] => {
+                unmatched.push(update.candidate.path);
+                continue;
+            }
+            [old] => *old,
+            _ => {
+                suggestions.ambiguous.push((
+                    mod_id,
+                    matches.iter().map(|j| j.candidate.path.clone()).collect(),
+                ));
+                continue;
+            }
+        };
+
+        suggestions.pairs.push(UpgradePair {
+            change: compat::version_change(&old.metadata, &update.metadata)
+                .map(|delta| delta.change)
+                .unwrap_or(VersionChange::Incomparable),
+            from: old.primary().version.clone(),
+            to: update.primary().version.clone(),
+            mod_id,
+            installed: old.candidate.clone(),
+            update: update.candidate,
+        });
+    }
+
+    suggestions.pairs.sort_by_key(|pair| {
+        (
+            match pair.change {
+                VersionChange::Upgrade => 0,
+                VersionChange::Same => 1,
+                VersionChange::Incomparable => 2,
+                VersionChange::Downgrade => 3,
+            },
+            pair.mod_id.clone(),
+        )
+    });
+    unmatched.sort();
+    suggestions.unmatched = unmatched;
+    Ok(suggestions)
+}
+
+/// A batch plan replacing the installed jar of each pair with its new jar.
+pub fn plan(pairs: &[&UpgradePair]) -> PlanFile {
+    PlanFile {
+        mods_dir: None,
+        replacements: pairs
+            .iter()
+            .map(|pair| PlanEntry {
+                target: file_name(&pair.installed.path),
+                replacement: pair.update.path.clone(),
+                force: false,
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,678 @@
+//! Version numbers and version ranges as mod loaders declare them.
+//!
+//! Fabric and Quilt use predicates such as `>=1.20.1 <1.21` or `1.20.x`
+//! over semantic versions, falling back to plain string equality for
+//! anything that is not one. Forge and NeoForge use Maven ranges such as
+//! `[1.20.1,1.21)` over Maven's `ComparableVersion` ordering, where
+//! `1.0` equals `1` and `1-alpha` sorts before `1-SNAPSHOT`.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// The syntax a range string is written in, which also decides how the
+/// versions in it compare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeSyntax {
+    /// Fabric and Quilt predicates over semantic versions.
+    Fabric,
+    /// Maven ranges used by `mods.toml`.
+    Maven,
+}
+
+/// A parsed version. Versions only compare with versions of the same
+/// scheme.
+#[derive(Debug, Clone)]
+pub struct Version {
+    raw: String,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone)]
+enum Kind {
+    Semantic(Semantic),
+    /// A Fabric version that is not semantic. Fabric only compares these
+    /// for equality.
+    Plain,
+    Maven(Vec<Item>),
+}
+
+impl Version {
+    /// Parses `s` the way the loader using `syntax` would. Build metadata
+    /// after `+` never takes part in comparisons.
+    pub fn parse(s: &str, syntax: RangeSyntax) -> Option<Self> {
+        let raw = s.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        let kind = match syntax {
+            RangeSyntax::Fabric => Semantic::parse(raw).map_or(Kind::Plain, Kind::Semantic),
+            RangeSyntax::Maven => Kind::Maven(parse_maven_items(raw)),
+        };
+        Some(Self {
+            raw: raw.to_string(),
+            kind,
+        })
+    }
+
+    fn semantic(core: Vec<u64>, pre: Option<String>) -> Self {
+        let mut raw = core
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(".");
+        if let Some(pre) = &pre {
+            raw.push('-');
+            raw.push_str(pre);
+        }
+        Self {
+            raw,
+            kind: Kind::Semantic(Semantic { core, pre }),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// Whether this is a semantic version with a pre-release tag.
+    pub fn is_prerelease(&self) -> bool {
+        matches!(&self.kind, Kind::Semantic(s) if s.pre.is_some())
+    }
+
+    /// Orders two versions, or `None` when they cannot be compared: they
+    /// use different schemes, or one is a non-semantic Fabric version and
+    /// they are not equal.
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (&self.kind, &other.kind) {
+            (Kind::Semantic(a), Kind::Semantic(b)) => Some(a.cmp(b)),
+            (Kind::Maven(a), Kind::Maven(b)) => Some(compare_lists(a, b)),
+            (Kind::Plain, Kind::Plain) if self.raw == other.raw => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.compare(other) == Some(Ordering::Equal)
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.compare(other)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// A Fabric semantic version: dot-separated numbers, then an optional
+/// `-` pre-release. An empty pre-release, as in `0.15-`, sorts below every
+/// other pre-release of the same release.
+#[derive(Debug, Clone)]
+struct Semantic {
+    core: Vec<u64>,
+    pre: Option<String>,
+}
+
+impl Semantic {
+    fn parse(s: &str) -> Option<Self> {
+        let s = match s.split_once('+') {
+            Some((version, build)) => {
+                valid_identifiers(build).then_some(())?;
+                version
+            }
+            None => s,
+        };
+        let (core, pre) = match s.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (s, None),
+        };
+        if let Some(pre) = pre {
+            valid_identifiers(pre).then_some(())?;
+        }
+        let core = core
+            .split('.')
+            .map(|part| {
+                if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                part.parse().ok()
+            })
+            .collect::<Option<Vec<u64>>>()?;
+        Some(Self {
+            core,
+            pre: pre.map(str::to_string),
+        })
+    }
+
+    /// The version that starts the next release after the first `depth`
+    /// components, below all of its pre-releases: `1.20.4` at depth 2 is
+    /// `1.21-`.
+    fn next(&self, depth: usize) -> Version {
+        let mut core: Vec<u64> = self.core.iter().copied().take(depth).collect();
+        core.resize(depth.max(1), 0);
+        *core.last_mut().unwrap() += 1;
+        Version::semantic(core, Some(String::new()))
+    }
+}
+
+/// Dot-separated `[0-9A-Za-z-]` identifiers; the empty string is allowed.
+fn valid_identifiers(s: &str) -> bool {
+    s.is_empty()
+        || s.split('.')
+            .all(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-'))
+}
+
+impl Ord for Semantic {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.core.len().max(other.core.len());
+        for i in 0..len {
+            let a = self.core.get(i).copied().unwrap_or(0);
+            let b = other.core.get(i).copied().unwrap_or(0);
+            match a.cmp(&b) {
+                Ordering::Equal => {}
+                unequal => return unequal,
+            }
+        }
+        // A pre-release sorts before the release it leads up to
+        match (&self.pre, &other.pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => compare_prerelease(a, b),
+        }
+    }
+}
+
+// Equality has to agree with `Ord`, which pads the shorter core with zeros
+impl PartialEq for Semantic {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Semantic {}
+
+impl PartialOrd for Semantic {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Numeric identifiers compare as numbers and below alphanumeric ones,
+/// which compare as ASCII. A longer list wins when one is a prefix of the
+/// other.
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let numeric = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let mut a = a.split('.').filter(|s| !s.is_empty());
+    let mut b = b.split('.').filter(|s| !s.is_empty());
+    loop {
+        let ord = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match (numeric(x), numeric(y)) {
+                (true, true) => compare_digits(x, y),
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => x.cmp(y),
+            },
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+}
+
+/// Compares decimal strings of any length by value.
+fn compare_digits(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// One part of a Maven version. Numbers keep their digits so that any
+/// length compares correctly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Item {
+    Int(String),
+    Str(String),
+    List(Vec<Item>),
+}
+
+/// Maven's well-known qualifiers, lowest first. The empty string is the
+/// release itself; unknown qualifiers sort after all of them.
+const QUALIFIERS: [&str; 7] = ["alpha", "beta", "milestone", "rc", "snapshot", "", "sp"];
+const RELEASE_QUALIFIER: usize = 5;
+
+impl Item {
+    fn string(s: &str, followed_by_digit: bool) -> Self {
+        let s = match s {
+            "a" if followed_by_digit => "alpha",
+            "b" if followed_by_digit => "beta",
+            "m" if followed_by_digit => "milestone",
+            "ga" | "final" | "release" => "",
+            "cr" => "rc",
+            other => other,
+        };
+        Self::Str(s.to_string())
+    }
+
+    fn number(s: &str) -> Self {
+        let digits = s.trim_start_matches('0');
+        Self::Int(if digits.is_empty() { "0" } else { digits }.to_string())
+    }
+
+    fn is_null(&self) -> bool {
+        match self {
+            Self::Int(n) => n == "0",
+            Self::Str(s) => s.is_empty(),
+            Self::List(items) => items.is_empty(),
+        }
+    }
+}
+
+/// Sort key of a qualifier: its index among the known ones, or after them
+/// all in ASCII order.
+fn qualifier_key(s: &str) -> String {
+    match QUALIFIERS.iter().position(|q| *q == s) {
+        Some(i) => i.to_string(),
+        None => format!("{}-{s}", QUALIFIERS.len()),
+    }
+}
+
+/// Splits a Maven version into items the way `ComparableVersion` does:
+/// `.` separates items, `-` and every switch between digits and letters
+/// start a nested list, and trailing null items are dropped.
+fn parse_maven_items(version: &str) -> Vec<Item> {
+    fn item(digits: bool, s: &str, followed_by_digit: bool) -> Item {
+        if digits {
+            Item::number(s)
+        } else {
+            Item::string(s, followed_by_digit)
+        }
+    }
+
+    let version = version.to_lowercase();
+    let chars: Vec<char> = version.chars().collect();
+    // Each open list is a stack frame; closed lists are folded into their parent
+    let mut stack: Vec<Vec<Item>> = vec![Vec::new()];
+    let mut digits = false;
+    let mut start = 0;
+    let text = |from: usize, to: usize| chars[from..to].iter().collect::<String>();
+
+    for (i, &c) in chars.iter().enumerate() {
+        let list = stack.last_mut().unwrap();
+        match c {
+            '.' | '-' => {
+                if i == start {
+                    list.push(Item::Int("0".to_string()));
+                } else {
+                    list.push(item(digits, &text(start, i), false));
+                }
+                start = i + 1;
+                if c == '-' {
+                    stack.push(Vec::new());
+                }
+            }
+            c if c.is_ascii_digit() => {
+                if !digits && i > start {
+                    list.push(item(false, &text(start, i), true));
+                    start = i;
+                    stack.push(Vec::new());
+                }
+                digits = true;
+            }
+            _ => {
+                if digits && i > start {
+                    list.push(item(true, &text(start, i), false));
+                    start = i;
+                    stack.push(Vec::new());
+                }
+                digits = false;
+            }
+        }
+    }
+    if chars.len() > start {
+        stack
+            .last_mut()
+            .unwrap()
+            .push(item(digits, &text(start, chars.len()), false));
+    }
+
+    while stack.len() > 1 {
+        let mut list = stack.pop().unwrap();
+        normalize(&mut list);
+        stack.last_mut().unwrap().push(Item::List(list));
+    }
+    let mut root = stack.pop().unwrap();
+    normalize(&mut root);
+    root
+}
+
+/// Drops null items from the end of a list, stopping at the first
+/// non-null item that is not itself a list.
+fn normalize(list: &mut Vec<Item>) {
+    let mut i = list.len();
+    while i > 0 {
+        i -= 1;
+        if list[i].is_null() {
+            list.remove(i);
+        } else if !matches!(list[i], Item::List(_)) {
+            break;
+        }
+    }
+}
+
+fn compare_lists(a: &[Item], b: &[Item]) -> Ordering {
+    let len = a.len().max(b.len());
+    for i in 0..len {
+        let ord = match (a.get(i), b.get(i)) {
+            (Some(x), y) => compare_items(x, y),
+            (None, Some(y)) => compare_items(y, None).reverse(),
+            (None, None) => Ordering::Equal,
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
+
+/// `ComparableVersion`'s item ordering; `None` stands for a missing item,
+/// which behaves like `0`, the release qualifier or an empty list.
+fn compare_items(a: &Item, b: Option<&Item>) -> Ordering {
+    match (a, b) {
+        (Item::Int(n), None) => {
+            if n == "0" {
+                Ordering::Equal
+            } else {
+                Ordering::Greater
+            }
+        }
+        (Item::Int(x), Some(Item::Int(y))) => compare_digits(x, y),
+        (Item::Int(_), Some(_)) => Ordering::Greater,
+
+        (Item::Str(s), None) => qualifier_key(s).cmp(&RELEASE_QUALIFIER.to_string()),
+        (Item::Str(_), Some(Item::Int(_))) => Ordering::Less,
+        (Item::Str(x), Some(Item::Str(y))) => qualifier_key(x).cmp(&qualifier_key(y)),
+        (Item::Str(_), Some(Item::List(_))) => Ordering::Less,
+
+        (Item::List(items), None) => match items.first() {
+            Some(first) => compare_items(first, None),
+            None => Ordering::Equal,
+        },
+        (Item::List(_), Some(Item::Int(_))) => Ordering::Less,
+        (Item::List(_), Some(Item::Str(_))) => Ordering::Greater,
+        (Item::List(x), Some(Item::List(y))) => compare_lists(x, y),
+    }
+}
+
+/// One end of an [`Interval`]; `inclusive` tells whether the version itself
+/// is part of the interval.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bound {
+    pub version: Version,
+    pub inclusive: bool,
+}
+
+/// A contiguous stretch of versions; a missing end is unbounded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interval {
+    pub min: Option<Bound>,
+    pub max: Option<Bound>,
+}
+
+impl Interval {
+    fn exact(version: Version) -> Self {
+        Self {
+            min: Some(Bound {
+                version: version.clone(),
+                inclusive: true,
+            }),
+            max: Some(Bound {
+                version,
+                inclusive: true,
+            }),
+        }
+    }
+
+    fn at_least(version: Version, inclusive: bool) -> Self {
+        Self {
+            min: Some(Bound { version, inclusive }),
+            max: None,
+        }
+    }
+
+    fn below(version: Version, inclusive: bool) -> Self {
+        Self {
+            min: None,
+            max: Some(Bound { version, inclusive }),
+        }
+    }
+
+    fn between(min: Version, max: Version) -> Self {
+        Self {
+            min: Some(Bound {
+                version: min,
+                inclusive: true,
+            }),
+            max: Some(Bound {
+                version: max,
+                inclusive: false,
+            }),
+        }
+    }
+
+    fn intersect(&self, other: &Self) -> Self {
+        let min = match (&self.min, &other.min) {
+            (Some(a), Some(b)) => Some(tighter(a, b, Ordering::Greater)),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+        let max = match (&self.max, &other.max) {
+            (Some(a), Some(b)) => Some(tighter(a, b, Ordering::Less)),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+        Self { min, max }
+    }
+
+    /// Whether no version fits. Bounds that cannot be compared are
+    /// assumed to leave room.
+    pub fn is_empty(&self) -> bool {
+        match (&self.min, &self.max) {
+            (Some(min), Some(max)) => match min.version.compare(&max.version) {
+                Some(Ordering::Greater) => true,
+                Some(Ordering::Equal) => !(min.inclusive && max.inclusive),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether `version` fits. A bound it cannot be compared with is not
+    /// met, the way Fabric treats non-semantic versions.
+    pub fn contains(&self, version: &Version) -> bool {
+        let above_min = self
+            .min
+            .as_ref()
+            .is_none_or(|min| match version.compare(&min.version) {
+                Some(Ordering::Greater) => true,
+                Some(Ordering::Equal) => min.inclusive,
+                _ => false,
+            });
+        let below_max = self
+            .max
+            .as_ref()
+            .is_none_or(|max| match version.compare(&max.version) {
+                Some(Ordering::Less) => true,
+                Some(Ordering::Equal) => max.inclusive,
+                _ => false,
+            });
+        above_min && below_max
+    }
+}
+
+/// Picks the stricter of two bounds; `prefer` is the ordering of the
+/// stricter version (greater for minimums, less for maximums).
+fn tighter(a: &Bound, b: &Bound, prefer: Ordering) -> Bound {
+    match a.version.compare(&b.version) {
+        Some(Ordering::Equal) => Bound {
+            version: a.version.clone(),
+            inclusive: a.inclusive && b.inclusive,
+        },
+        Some(ord) if ord != prefer => b.clone(),
+        _ => a.clone(),
+    }
+}
+
+/// A union of intervals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRange {
+    pub intervals: Vec<Interval>,
+}
+
+impl VersionRange {
+    pub fn any() -> Self {
+        Self {
+            intervals: vec![Interval::default()],
+        }
+    }
+
+    pub fn parse(s: &str, syntax: RangeSyntax) -> Option<Self> {
+        match syntax {
+            RangeSyntax::Fabric => parse_fabric(s),
+            RangeSyntax::Maven => parse_maven(s),
+        }
+    }
+
+    pub fn contains(&self, version: &Version) -> bool {
+        self.intervals.iter().any(|i| i.contains(version))
+    }
+
+    /// Whether some version satisfies both ranges.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.intervals
+            .iter()
+            .any(|a| other.intervals.iter().any(|b| !a.intersect(b).is_empty()))
+    }
+}
+
+fn parse_fabric(s: &str) -> Option<VersionRange> {
+    let mut intervals = Vec::new();
+    for alternative in s.split("||") {
+        let mut interval = Interval::default();
+        for constraint in alternative.split_whitespace() {
+            interval = interval.intersect(&parse_fabric_constraint(constraint)?);
+        }
+        intervals.push(interval);
+    }
+    Some(VersionRange { intervals })
+}
+
+fn parse_fabric_constraint(c: &str) -> Option<Interval> {
+    let version = |s: &str| Version::parse(s, RangeSyntax::Fabric);
+    let semantic = |s: &str| Semantic::parse(s.trim());
+
+    for (op, make) in [
+        (
+            ">=",
+            (|v| Interval::at_least(v, true)) as fn(Version) -> Interval,
+        ),
+        ("<=", |v| Interval::below(v, true)),
+        (">", |v| Interval::at_least(v, false)),
+        ("<", |v| Interval::below(v, false)),
+    ] {
+        if let Some(rest) = c.strip_prefix(op) {
+            return version(rest).map(make);
+        }
+    }
+    // `~1.20.1` stays below the next minor release, `^1.20.1` below the
+    // next major one
+    for (op, depth) in [('~', 2), ('^', 1)] {
+        if let Some(rest) = c.strip_prefix(op) {
+            let v = semantic(rest)?;
+            let upper = v.next(depth);
+            return Some(Interval::between(version(rest)?, upper));
+        }
+    }
+
+    let c = c.strip_prefix('=').unwrap_or(c);
+    // `1.20.x` matches every 1.20 release and pre-release
+    let mut prefix = c;
+    while let Some(rest) = ["x", "X", "*"].iter().find_map(|w| {
+        prefix
+            .strip_suffix(w)
+            .and_then(|p| p.strip_suffix('.').or(p.is_empty().then_some("")))
+    }) {
+        prefix = rest;
+    }
+    if prefix.len() != c.len() {
+        if prefix.is_empty() {
+            return Some(Interval::default());
+        }
+        let v = semantic(prefix).filter(|v| v.pre.is_none())?;
+        let depth = v.core.len();
+        let lower = Version::semantic(v.core.clone(), Some(String::new()));
+        return Some(Interval::between(lower, v.next(depth)));
+    }
+    version(c).map(Interval::exact)
+}
+
+fn parse_maven(s: &str) -> Option<VersionRange> {
+    let s = s.trim();
+    if s.is_empty() || s == "*" {
+        return Some(VersionRange::any());
+    }
+    // A bare version is a soft requirement that any version satisfies
+    if !s.starts_with(['[', '(']) {
+        return Some(VersionRange::any());
+    }
+
+    let mut intervals = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        if !rest.starts_with(['[', '(']) {
+            return None;
+        }
+        let end = rest.find([']', ')'])?;
+        let (range, tail) = rest.split_at(end + 1);
+        intervals.push(parse_maven_interval(range)?);
+        rest = tail.trim_start_matches([',', ' ']);
+    }
+    Some(VersionRange { intervals })
+}
+
+fn parse_maven_interval(range: &str) -> Option<Interval> {
+    let min_inclusive = range.starts_with('[');
+    let max_inclusive = range.ends_with(']');
+    let body = &range[1..range.len() - 1];
+
+    let Some((min, max)) = body.split_once(',') else {
+        // Only `[1.0]` pins a single version
+        if !(min_inclusive && max_inclusive) {
+            return None;
+        }
+        return Version::parse(body, RangeSyntax::Maven).map(Interval::exact);
+    };
+    let bound = |s: &str, inclusive| -> Option<Option<Bound>> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Some(None);
+        }
+        Some(Some(Bound {
+            version: Version::parse(s, RangeSyntax::Maven)?,
+            inclusive,
+        }))
+    };
+    Some(Interval {
+        min: bound(min, min_inclusive)?,
+        max: bound(max, max_inclusive)?,
+    })
+}
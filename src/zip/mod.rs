@@ -0,0 +1,106 @@
+//! ZIP reading and writing, plus the padding trick used to grow a jar to an exact size
+//! without breaking it.
+
+use thiserror::Error;
+
+mod reader;
+mod verify;
+mod writer;
+
+pub use reader::{
+    Archive, CompressionMethod, Entry, Eocd, FLAG_DATA_DESCRIPTOR, FLAG_ENCRYPTED, LocalHeader,
+    Zip64Eocd,
+};
+pub use verify::{Problem, VerifyReport, verify};
+pub use writer::ZipWriter;
+
+/// Maximum comment size in ZIP format.
+pub const MAX_COMMENT_LEN: usize = 65535;
+
+#[derive(Debug, Clone, Error)]
+pub enum ZipError {
+    #[error("cannot pad {0} bytes using the ZIP comment field (limit is {MAX_COMMENT_LEN})")]
+    PaddingTooLarge(usize),
+    #[error(
+        "total comment length {current} + {padding} would exceed the ZIP limit of {MAX_COMMENT_LEN} bytes"
+    )]
+    CommentOverflow { current: usize, padding: usize },
+    #[error("could not find a valid EOCD record in file of {0} bytes")]
+    EocdNotFound(usize),
+    #[error("truncated {0}")]
+    Truncated(String),
+    #[error("bad {what} signature at offset {offset}")]
+    BadSignature { what: &'static str, offset: u64 },
+    #[error("central directory declares {declared} entries but holds {found}")]
+    EntryCountMismatch { declared: u64, found: u64 },
+    #[error(
+        "ZIP64 archives cannot be padded by appending bytes; Java rejects data after their comment"
+    )]
+    Zip64Append,
+    #[error("local header of '{name}' does not match the central directory ({field})")]
+    LocalHeaderMismatch { name: String, field: &'static str },
+    #[error("cannot write archive: {0}")]
+    Write(String),
+    #[error("'{0}' is encrypted")]
+    Encrypted(String),
+    #[error("'{name}' uses unsupported compression method {method}")]
+    UnsupportedMethod { name: String, method: u16 },
+    #[error("'{name}' failed to decompress: {reason}")]
+    Inflate { name: String, reason: String },
+    #[error("'{name}' is {actual} bytes uncompressed, expected {expected}")]
+    SizeMismatch {
+        name: String,
+        expected: u64,
+        actual: u64,
+    },
+    #[error("'{name}' has CRC32 {actual:08x}, expected {expected:08x}")]
+    CrcMismatch {
+        name: String,
+        expected: u32,
+        actual: u32,
+    },
+}
+
+/// Grows the archive comment by `padding_size` bytes so the jar ends up
+/// exactly that much larger while staying a valid ZIP file.
+///
+/// Anything already sitting after the EOCD record is folded into the
+/// comment, so the declared comment always runs to the end of the file.
+pub fn pad_zip_file(mut data: Vec<u8>, padding_size: usize) -> Result<Vec<u8>, ZipError> {
+    if padding_size > MAX_COMMENT_LEN {
+        return Err(ZipError::PaddingTooLarge(padding_size));
+    }
+
+    let archive = Archive::parse(&data)?;
+    let eocd_start = archive.eocd.offset;
+    let current_comment_len = archive.trailing_len();
+
+    let new_comment_len = current_comment_len + padding_size;
+    if new_comment_len > MAX_COMMENT_LEN {
+        return Err(ZipError::CommentOverflow {
+            current: current_comment_len,
+            padding: padding_size,
+        });
+    }
+
+    let new_comment_len_bytes = (new_comment_len as u16).to_le_bytes();
+    data[eocd_start + 20] = new_comment_len_bytes[0];
+    data[eocd_start + 21] = new_comment_len_bytes[1];
+    data.extend(vec![b'#'; padding_size]);
+
+    Ok(data)
+}
+
+/// Appends `padding_size` null bytes after the archive.
+///
+/// Lenient readers find the EOCD record by scanning backwards and accept
+/// this, but Java only tolerates bytes after the comment when it can
+/// re-validate the classic central directory fields, which ZIP64 archives
+/// leave saturated. Those are refused.
+pub fn append_padding(mut data: Vec<u8>, padding_size: usize) -> Result<Vec<u8>, ZipError> {
+    if Archive::parse(&data)?.is_zip64() {
+        return Err(ZipError::Zip64Append);
+    }
+    data.extend(vec![0u8; padding_size]);
+    Ok(data)
+}
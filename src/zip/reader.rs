@@ -0,0 +1,493 @@
+//! Parses a ZIP archive through its central directory.
+//!
+//! The End of Central Directory record is only accepted when the central
+//! directory it points to actually parses, so a stray `PK\x05\x06` inside
+//! compressed data or a comment cannot be mistaken for it.
+
+use std::fmt;
+use std::io::Read;
+
+use flate2::read::DeflateDecoder;
+
+use super::ZipError;
+
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const CENTRAL_SIGNATURE: u32 = 0x0201_4b50;
+const LOCAL_SIGNATURE: u32 = 0x0403_4b50;
+const ZIP64_EOCD_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+
+pub(crate) const EOCD_LEN: usize = 22;
+const CENTRAL_LEN: usize = 46;
+const LOCAL_LEN: usize = 30;
+const ZIP64_LOCATOR_LEN: usize = 20;
+const ZIP64_EOCD_LEN: usize = 56;
+
+/// Deflate cannot expand its input by more than this factor.
+const MAX_DEFLATE_RATIO: u64 = 1032;
+
+/// Flag bit 3: sizes and CRC follow the data in a data descriptor.
+pub const FLAG_DATA_DESCRIPTOR: u16 = 1 << 3;
+/// Flag bit 0: the entry is encrypted.
+pub const FLAG_ENCRYPTED: u16 = 1;
+/// Flag bit 11: the name is UTF-8 rather than CP437.
+const FLAG_UTF8: u16 = 1 << 11;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionMethod {
+    Stored,
+    Deflated,
+    Other(u16),
+}
+
+impl From<u16> for CompressionMethod {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => Self::Stored,
+            8 => Self::Deflated,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl fmt::Display for CompressionMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stored => f.pad("stored"),
+            Self::Deflated => f.pad("deflated"),
+            Self::Other(n) => f.pad(&format!("method {n}")),
+        }
+    }
+}
+
+/// The End of Central Directory record. For ZIP64 archives the counts,
+/// sizes and offsets come from the ZIP64 record rather than the classic one.
+#[derive(Debug, Clone, Copy)]
+pub struct Eocd {
+    /// Offset of the classic record.
+    pub offset: usize,
+    pub entries: u64,
+    pub cd_size: u64,
+    pub cd_offset: u64,
+    pub comment_len: u16,
+    pub zip64: Option<Zip64Eocd>,
+}
+
+/// Where the ZIP64 end of central directory record and its locator live.
+#[derive(Debug, Clone, Copy)]
+pub struct Zip64Eocd {
+    pub record_offset: u64,
+    pub locator_offset: u64,
+}
+
+/// The local file header that precedes an entry's data.
+#[derive(Debug, Clone)]
+pub struct LocalHeader {
+    pub flags: u16,
+    pub method: CompressionMethod,
+    pub crc32: u32,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+}
+
+/// A central directory entry.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub name: String,
+    pub raw_name: Vec<u8>,
+    pub flags: u16,
+    pub method: CompressionMethod,
+    pub crc32: u32,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    pub local_header_offset: u64,
+    /// Where the entry's (possibly compressed) data starts.
+    pub data_offset: u64,
+    pub local: LocalHeader,
+}
+
+impl Entry {
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Archive<'a> {
+    data: &'a [u8],
+    pub eocd: Eocd,
+    pub entries: Vec<Entry>,
+}
+
+impl<'a> Archive<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, ZipError> {
+        let search_start = data.len().saturating_sub(EOCD_LEN + super::MAX_COMMENT_LEN);
+        let mut last_err = None;
+        for offset in (search_start..=data.len().saturating_sub(EOCD_LEN)).rev() {
+            if read_u32(data, offset) != Some(EOCD_SIGNATURE) {
+                continue;
+            }
+            match Self::parse_at(data, offset) {
+                Ok(archive) => return Ok(archive),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.unwrap_or(ZipError::EocdNotFound(data.len())))
+    }
+
+    fn parse_at(data: &'a [u8], offset: usize) -> Result<Self, ZipError> {
+        let eocd = read_eocd(data, offset)?;
+        let entries = read_central_directory(data, &eocd)?;
+        Ok(Self {
+            data,
+            eocd,
+            entries,
+        })
+    }
+
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// The entry's data exactly as stored, still compressed.
+    pub fn raw_data(&self, entry: &Entry) -> Result<&'a [u8], ZipError> {
+        let start = entry.data_offset as usize;
+        start
+            .checked_add(entry.compressed_size as usize)
+            .and_then(|end| self.data.get(start..end))
+            .ok_or_else(|| ZipError::Truncated(format!("data of '{}'", entry.name)))
+    }
+
+    pub fn is_zip64(&self) -> bool {
+        self.eocd.zip64.is_some()
+    }
+
+    /// Decompresses the entry and checks its size and CRC32.
+    pub fn read(&self, entry: &Entry) -> Result<Vec<u8>, ZipError> {
+        if entry.flags & FLAG_ENCRYPTED != 0 {
+            return Err(ZipError::Encrypted(entry.name.clone()));
+        }
+        let raw = self.raw_data(entry)?;
+        let data = match entry.method {
+            CompressionMethod::Stored => raw.to_vec(),
+            CompressionMethod::Deflated => inflate(raw, entry, self.data.len())?,
+            CompressionMethod::Other(method) => {
+                return Err(ZipError::UnsupportedMethod {
+                    name: entry.name.clone(),
+                    method,
+                });
+            }
+        };
+
+        if data.len() as u64 != entry.uncompressed_size {
+            return Err(ZipError::SizeMismatch {
+                name: entry.name.clone(),
+                expected: entry.uncompressed_size,
+                actual: data.len() as u64,
+            });
+        }
+        let crc = crc32fast::hash(&data);
+        if crc != entry.crc32 {
+            return Err(ZipError::CrcMismatch {
+                name: entry.name.clone(),
+                expected: entry.crc32,
+                actual: crc,
+            });
+        }
+        Ok(data)
+    }
+
+    /// Reads the entry called `name`, if there is one.
+    pub fn read_file(&self, name: &str) -> Result<Option<Vec<u8>>, ZipError> {
+        self.find(name).map(|entry| self.read(entry)).transpose()
+    }
+
+    /// Bytes from the end of the EOCD record to the end of the file,
+    /// counting both the declared comment and anything appended after it.
+    pub fn trailing_len(&self) -> usize {
+        self.data.len() - self.eocd.offset - EOCD_LEN
+    }
+}
+
+/// Inflates an entry's data. The declared size is only trusted as far as
+/// the data could back it: the buffer never starts larger than the raw data
+/// can inflate to or the archive itself, and inflating stops one byte past
+/// the declared size so an entry that lies about it fails the size check.
+fn inflate(raw: &[u8], entry: &Entry, archive_len: usize) -> Result<Vec<u8>, ZipError> {
+    let capacity = entry
+        .uncompressed_size
+        .min((raw.len() as u64).saturating_mul(MAX_DEFLATE_RATIO))
+        .min(archive_len as u64);
+    let mut out = Vec::with_capacity(capacity as usize);
+    let mut decoder = DeflateDecoder::new(raw);
+    let mut chunk = [0u8; 16 * 1024];
+    let limit = entry.uncompressed_size.saturating_add(1);
+    while (out.len() as u64) < limit {
+        let want = (limit - out.len() as u64).min(chunk.len() as u64) as usize;
+        let n = decoder
+            .read(&mut chunk[..want])
+            .map_err(|err| ZipError::Inflate {
+                name: entry.name.clone(),
+                reason: err.to_string(),
+            })?;
+        if n == 0 {
+            break;
+        }
+        out.extend_from_slice(&chunk[..n]);
+    }
+    Ok(out)
+}
+
+fn read_eocd(data: &[u8], offset: usize) -> Result<Eocd, ZipError> {
+    let truncated = || ZipError::Truncated("end of central directory".to_string());
+    let u16_at = |i: usize| read_u16(data, offset + i).ok_or_else(truncated);
+    let u32_at = |i: usize| read_u32(data, offset + i).ok_or_else(truncated);
+    let comment_len = u16_at(20)?;
+    if offset + EOCD_LEN + comment_len as usize > data.len() {
+        return Err(ZipError::Truncated("archive comment".to_string()));
+    }
+
+    let mut eocd = Eocd {
+        offset,
+        entries: u64::from(u16_at(10)?),
+        cd_size: u64::from(u32_at(12)?),
+        cd_offset: u64::from(u32_at(16)?),
+        comment_len,
+        zip64: None,
+    };
+    // The central directory has to end before whichever record comes first
+    let mut cd_limit = offset as u64;
+
+    if let Some(locator_offset) = offset.checked_sub(ZIP64_LOCATOR_LEN)
+        && read_u32(data, locator_offset) == Some(ZIP64_LOCATOR_SIGNATURE)
+    {
+        let record_offset = read_u64(data, locator_offset + 8).ok_or_else(truncated)?;
+        read_zip64_eocd(data, record_offset, locator_offset, &mut eocd)?;
+        eocd.zip64 = Some(Zip64Eocd {
+            record_offset,
+            locator_offset: locator_offset as u64,
+        });
+        cd_limit = record_offset;
+    }
+
+    if eocd.cd_offset.saturating_add(eocd.cd_size) > cd_limit {
+        return Err(ZipError::Truncated("central directory".to_string()));
+    }
+    Ok(eocd)
+}
+
+fn read_zip64_eocd(
+    data: &[u8],
+    record_offset: u64,
+    locator_offset: usize,
+    eocd: &mut Eocd,
+) -> Result<(), ZipError> {
+    let pos = usize::try_from(record_offset)
+        .ok()
+        .filter(|&pos| {
+            pos.checked_add(ZIP64_EOCD_LEN)
+                .is_some_and(|end| end <= locator_offset)
+        })
+        .ok_or_else(|| ZipError::Truncated("ZIP64 end of central directory".to_string()))?;
+    if read_u32(data, pos) != Some(ZIP64_EOCD_SIGNATURE) {
+        return Err(ZipError::BadSignature {
+            what: "ZIP64 end of central directory",
+            offset: record_offset,
+        });
+    }
+    let u64_at = |i| read_u64(data, pos + i).unwrap_or(0);
+    eocd.entries = u64_at(32);
+    eocd.cd_size = u64_at(40);
+    eocd.cd_offset = u64_at(48);
+    Ok(())
+}
+
+fn read_central_directory(data: &[u8], eocd: &Eocd) -> Result<Vec<Entry>, ZipError> {
+    let end = (eocd.cd_offset + eocd.cd_size) as usize;
+    let mut pos = eocd.cd_offset as usize;
+    let mut entries = Vec::new();
+
+    while pos < end {
+        let entry = read_central_entry(data, pos, end)?;
+        pos += CENTRAL_LEN
+            + entry.raw_name.len()
+            + usize::from(read_u16(data, pos + 30).unwrap_or(0))
+            + usize::from(read_u16(data, pos + 32).unwrap_or(0));
+        entries.push(entry);
+    }
+
+    if pos != end || entries.len() as u64 != eocd.entries {
+        return Err(ZipError::EntryCountMismatch {
+            declared: eocd.entries,
+            found: entries.len() as u64,
+        });
+    }
+    Ok(entries)
+}
+
+fn read_central_entry(data: &[u8], pos: usize, end: usize) -> Result<Entry, ZipError> {
+    let truncated = || ZipError::Truncated(format!("central directory entry at {pos}"));
+    if pos + CENTRAL_LEN > end {
+        return Err(truncated());
+    }
+    if read_u32(data, pos) != Some(CENTRAL_SIGNATURE) {
+        return Err(ZipError::BadSignature {
+            what: "central directory entry",
+            offset: pos as u64,
+        });
+    }
+    let u16_at = |i| read_u16(data, pos + i).ok_or_else(truncated);
+    let u32_at = |i| read_u32(data, pos + i).ok_or_else(truncated);
+
+    let flags = u16_at(8)?;
+    let name_len = usize::from(u16_at(28)?);
+    let extra_len = usize::from(u16_at(30)?);
+    let comment_len = usize::from(u16_at(32)?);
+    let name_start = pos + CENTRAL_LEN;
+    if name_start + name_len + extra_len + comment_len > end {
+        return Err(truncated());
+    }
+    let raw_name = data[name_start..name_start + name_len].to_vec();
+    let name = decode_name(&raw_name, flags);
+    let extra = &data[name_start + name_len..name_start + name_len + extra_len];
+
+    // Fields saturated to 0xFFFFFFFF are stored in the ZIP64 extra field, in this order
+    let mut uncompressed_size = u64::from(u32_at(24)?);
+    let mut compressed_size = u64::from(u32_at(20)?);
+    let mut local_header_offset = u64::from(u32_at(42)?);
+    let mut zip64 = zip64_extra(extra).unwrap_or_default().into_iter();
+    for field in [
+        &mut uncompressed_size,
+        &mut compressed_size,
+        &mut local_header_offset,
+    ] {
+        if *field == u64::from(u32::MAX) {
+            *field = zip64
+                .next()
+                .ok_or_else(|| ZipError::Truncated(format!("ZIP64 extra field of '{name}'")))?;
+        }
+    }
+
+    let (local, data_offset) = read_local_header(data, local_header_offset, &raw_name, &name)?;
+    if data_offset
+        .checked_add(compressed_size)
+        .is_none_or(|end| end > data.len() as u64)
+    {
+        return Err(ZipError::Truncated(format!("data of '{name}'")));
+    }
+
+    Ok(Entry {
+        name,
+        raw_name,
+        flags,
+        method: u16_at(10)?.into(),
+        crc32: u32_at(16)?,
+        compressed_size,
+        uncompressed_size,
+        local_header_offset,
+        data_offset,
+        local,
+    })
+}
+
+/// Reads the local header at `offset`, returning it with the offset of the
+/// entry data that follows.
+fn read_local_header(
+    data: &[u8],
+    offset: u64,
+    raw_name: &[u8],
+    name: &str,
+) -> Result<(LocalHeader, u64), ZipError> {
+    let truncated = || ZipError::Truncated(format!("local header of '{name}'"));
+    let pos = usize::try_from(offset).map_err(|_| truncated())?;
+    if read_u32(data, pos) != Some(LOCAL_SIGNATURE) {
+        return Err(ZipError::BadSignature {
+            what: "local file header",
+            offset,
+        });
+    }
+    let u16_at = |i| read_u16(data, pos + i).ok_or_else(truncated);
+    let u32_at = |i| read_u32(data, pos + i).ok_or_else(truncated);
+
+    let name_len = usize::from(u16_at(26)?);
+    let extra_len = usize::from(u16_at(28)?);
+    let name_start = pos.checked_add(LOCAL_LEN).ok_or_else(truncated)?;
+    let name_end = name_start.checked_add(name_len).ok_or_else(truncated)?;
+    let data_start = name_end.checked_add(extra_len).ok_or_else(truncated)?;
+    let local_name = data.get(name_start..name_end).ok_or_else(truncated)?;
+    if local_name != raw_name {
+        return Err(ZipError::LocalHeaderMismatch {
+            name: name.to_string(),
+            field: "name",
+        });
+    }
+
+    let extra = data.get(name_end..data_start).ok_or_else(truncated)?;
+
+    let mut local = LocalHeader {
+        flags: u16_at(6)?,
+        method: u16_at(8)?.into(),
+        crc32: u32_at(14)?,
+        compressed_size: u64::from(u32_at(18)?),
+        uncompressed_size: u64::from(u32_at(22)?),
+    };
+    // Local ZIP64 extra fields always carry both sizes
+    let max = u64::from(u32::MAX);
+    if (local.compressed_size == max || local.uncompressed_size == max)
+        && let Some(&[uncompressed, compressed, ..]) = zip64_extra(extra).as_deref()
+    {
+        local.uncompressed_size = uncompressed;
+        local.compressed_size = compressed;
+    }
+    Ok((local, data_start as u64))
+}
+
+/// The 64-bit values in the ZIP64 extended information extra field.
+fn zip64_extra(extra: &[u8]) -> Option<Vec<u64>> {
+    let mut pos = 0;
+    while pos + 4 <= extra.len() {
+        let id = read_u16(extra, pos)?;
+        let len = usize::from(read_u16(extra, pos + 2)?);
+        let body = extra.get(pos + 4..pos + 4 + len)?;
+        if id == ZIP64_EXTRA_ID {
+            return Some(
+                body.chunks_exact(8)
+                    .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+                    .collect(),
+            );
+        }
+        pos += 4 + len;
+    }
+    None
+}
+
+fn decode_name(raw: &[u8], flags: u16) -> String {
+    if flags & FLAG_UTF8 != 0 || raw.is_ascii() {
+        String::from_utf8_lossy(raw).into_owned()
+    } else {
+        // CP437 names are rare in jars; keep ASCII and mark the rest
+        raw.iter()
+            .map(|&b| if b.is_ascii() { b as char } else { '\u{fffd}' })
+            .collect()
+    }
+}
+
+pub(crate) fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+    data.get(pos..pos.checked_add(2)?)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+pub(crate) fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    data.get(pos..pos.checked_add(4)?)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+pub(crate) fn read_u64(data: &[u8], pos: usize) -> Option<u64> {
+    data.get(pos..pos.checked_add(8)?)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+}
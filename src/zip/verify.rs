@@ -0,0 +1,80 @@
+//! Full integrity check of an archive: headers, decompression and CRCs.
+
+use std::fmt;
+
+use super::{Archive, Entry, FLAG_DATA_DESCRIPTOR, ZipError};
+
+/// Something wrong with a single entry.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub entry: String,
+    pub error: ZipError,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    pub entries: usize,
+    pub problems: Vec<Problem>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Checks every central directory entry against its local header, then
+/// decompresses it and compares the CRC32.
+///
+/// Errors only when the archive structure itself cannot be parsed; problems
+/// with individual entries are collected in the report.
+pub fn verify(data: &[u8]) -> Result<VerifyReport, ZipError> {
+    let archive = Archive::parse(data)?;
+    let mut report = VerifyReport {
+        entries: archive.entries.len(),
+        problems: Vec::new(),
+    };
+
+    for entry in &archive.entries {
+        let result = check_local_header(entry).and_then(|()| archive.read(entry).map(|_| ()));
+        if let Err(error) = result {
+            report.problems.push(Problem {
+                entry: entry.name.clone(),
+                error,
+            });
+        }
+    }
+    Ok(report)
+}
+
+fn check_local_header(entry: &Entry) -> Result<(), ZipError> {
+    let local = &entry.local;
+    let mismatch = |field| ZipError::LocalHeaderMismatch {
+        name: entry.name.clone(),
+        field,
+    };
+
+    if local.method != entry.method {
+        return Err(mismatch("compression method"));
+    }
+    // With a data descriptor the local header may leave CRC and sizes as zero
+    if local.flags & FLAG_DATA_DESCRIPTOR != 0 {
+        return Ok(());
+    }
+    if local.crc32 != entry.crc32 {
+        return Err(mismatch("crc32"));
+    }
+    if local.compressed_size != entry.compressed_size {
+        return Err(mismatch("compressed size"));
+    }
+    if local.uncompressed_size != entry.uncompressed_size {
+        return Err(mismatch("uncompressed size"));
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! Builds a new ZIP archive in memory.
+
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+
+use super::ZipError;
+
+/// 1980-01-01 00:00, so the same input always gives the same archive.
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = (1 << 5) | 1;
+const FLAG_UTF8: u16 = 1 << 11;
+const VERSION: u16 = 20;
+
+#[derive(Debug, Default)]
+pub struct ZipWriter {
+    out: Vec<u8>,
+    central: Vec<u8>,
+    entries: usize,
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, deflated unless that would not make it smaller.
+    pub fn add_file(&mut self, name: &str, data: &[u8]) -> Result<(), ZipError> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(data)
+            .map_err(|err| ZipError::Write(err.to_string()))?;
+        let deflated = encoder
+            .finish()
+            .map_err(|err| ZipError::Write(err.to_string()))?;
+        let (method, body) = if deflated.len() < data.len() {
+            (8u16, deflated.as_slice())
+        } else {
+            (0u16, data)
+        };
+
+        let crc = crc32fast::hash(data);
+        let offset = self.checked_u32(self.out.len() as u64)?;
+        let compressed = self.checked_u32(body.len() as u64)?;
+        let uncompressed = self.checked_u32(data.len() as u64)?;
+        let name_len = u16::try_from(name.len())
+            .map_err(|_| ZipError::Write(format!("name too long: {name}")))?;
+
+        let mut local = Vec::with_capacity(30 + name.len());
+        local.extend(0x0403_4b50u32.to_le_bytes());
+        local.extend(VERSION.to_le_bytes());
+        local.extend(FLAG_UTF8.to_le_bytes());
+        local.extend(method.to_le_bytes());
+        local.extend(DOS_TIME.to_le_bytes());
+        local.extend(DOS_DATE.to_le_bytes());
+        local.extend(crc.to_le_bytes());
+        local.extend(compressed.to_le_bytes());
+        local.extend(uncompressed.to_le_bytes());
+        local.extend(name_len.to_le_bytes());
+        local.extend(0u16.to_le_bytes());
+        local.extend(name.as_bytes());
+        self.out.extend(local);
+        self.out.extend(body);
+
+        let c = &mut self.central;
+        c.extend(0x0201_4b50u32.to_le_bytes());
+        c.extend(VERSION.to_le_bytes());
+        c.extend(VERSION.to_le_bytes());
+        c.extend(FLAG_UTF8.to_le_bytes());
+        c.extend(method.to_le_bytes());
+        c.extend(DOS_TIME.to_le_bytes());
+        c.extend(DOS_DATE.to_le_bytes());
+        c.extend(crc.to_le_bytes());
+        c.extend(compressed.to_le_bytes());
+        c.extend(uncompressed.to_le_bytes());
+        c.extend(name_len.to_le_bytes());
+        // extra length, comment length, disk, internal and external attributes
+        c.extend([0u8; 2 + 2 + 2 + 2 + 4]);
+        c.extend(offset.to_le_bytes());
+        c.extend(name.as_bytes());
+
+        self.entries += 1;
+        Ok(())
+    }
+
+    /// Writes the central directory and returns the finished archive.
+    pub fn finish(mut self) -> Result<Vec<u8>, ZipError> {
+        // All ones in a count, size or offset tells readers to look for ZIP64
+        let entries = u16::try_from(self.entries)
+            .ok()
+            .filter(|&n| n != u16::MAX)
+            .ok_or_else(|| ZipError::Write("too many entries for a classic ZIP".to_string()))?;
+        let cd_offset = self.checked_u32(self.out.len() as u64)?;
+        let cd_size = self.checked_u32(self.central.len() as u64)?;
+
+        self.out.append(&mut self.central);
+        self.out.extend(0x0605_4b50u32.to_le_bytes());
+        self.out.extend([0u8; 4]);
+        self.out.extend(entries.to_le_bytes());
+        self.out.extend(entries.to_le_bytes());
+        self.out.extend(cd_size.to_le_bytes());
+        self.out.extend(cd_offset.to_le_bytes());
+        self.out.extend(0u16.to_le_bytes());
+        Ok(self.out)
+    }
+
+    /// `value` as a classic ZIP field, which cannot hold the ZIP64 marker.
+    fn checked_u32(&self, value: u64) -> Result<u32, ZipError> {
+        u32::try_from(value)
+            .ok()
+            .filter(|&v| v != u32::MAX)
+            .ok_or_else(|| ZipError::Write("archive too large for a classic ZIP".to_string()))
+    }
+}
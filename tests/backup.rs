@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::PathBuf;
+
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::fsio;
+
+/// A fresh scratch folder for one test.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mod-replacer-test-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn backups_sort_by_timestamp_then_counter() {
+    let root = scratch("backup-order");
+    for id in [
+        "20260101-000000-2",
+        "20260101-000001",
+        "20260101-000000",
+        "20260101-000000-10",
+        "20260101-000000-1",
+    ] {
+        let dir = root.join(id);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("manifest.json"),
+            r#"{"operation":"replace","created":0,"entries":[]}"#,
+        )
+        .unwrap();
+    }
+
+    let ids: Vec<String> = BackupStore::new(&root)
+        .list()
+        .unwrap()
+        .into_iter()
+        .map(|set| set.id.clone())
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            "20260101-000001",
+            "20260101-000000-10",
+            "20260101-000000-2",
+            "20260101-000000-1",
+            "20260101-000000",
+        ]
+    );
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn atomic_writes_keep_the_file_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = scratch("write-mode");
+    let path = dir.join("run.sh");
+    fs::write(&path, b"old").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o750)).unwrap();
+
+    fsio::write_atomic(&path, b"new").unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"new");
+    assert_eq!(
+        fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+        0o750
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn backups_started_together_get_their_own_folders() {
+    let root = scratch("backup-ids");
+    let store = BackupStore::new(&root);
+    let original = root.join("a.jar");
+    fs::write(&original, b"first").unwrap();
+
+    let mut first = store.begin("one").unwrap();
+    let mut second = store.begin("two").unwrap();
+    assert_ne!(first.id, second.id);
+    first.add(&original, None).unwrap();
+    second.add(&original, None).unwrap();
+
+    let operations: Vec<String> = store
+        .list()
+        .unwrap()
+        .iter()
+        .map(|set| set.manifest.operation.clone())
+        .collect();
+    assert_eq!(operations, ["two", "one"]);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn empty_backups_leave_no_folder() {
+    let root = scratch("backup-empty");
+    let store = BackupStore::new(&root);
+    let dir = {
+        let set = store.begin("nothing").unwrap();
+        assert!(set.dir.is_dir());
+        set.dir.clone()
+    };
+    assert!(!dir.exists());
+    assert!(store.list().unwrap().is_empty());
+    fs::remove_dir_all(root).unwrap();
+}
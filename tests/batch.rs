@@ -0,0 +1,128 @@
+mod common;
+
+use std::fs;
+
+use minecraft_mod_replacer::ReplaceOptions;
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::batch::{self, PlanEntry, PlanFile};
+use minecraft_mod_replacer::{Error, deps::ProblemKind};
+
+use common::{fabric_jar, fabric_json, forge_jar, heavy, scratch, write};
+
+fn entry(target: &str, replacement: std::path::PathBuf, force: bool) -> PlanEntry {
+    PlanEntry {
+        target: target.to_string(),
+        replacement,
+        force,
+    }
+}
+
+#[test]
+fn apply_puts_earlier_jars_back_when_a_later_one_fails() {
+    let dir = scratch("batch-rollback");
+    let mods = dir.join("mods");
+    let a_old = heavy(&[("fabric.mod.json", fabric_json("a", "1.0.0", "").as_bytes())]);
+    let b_old = heavy(&[("fabric.mod.json", fabric_json("b", "1.0.0", "").as_bytes())]);
+    write(&mods.join("a-1.0.0.jar"), &a_old);
+    write(&mods.join("b-1.0.0.jar"), &b_old);
+    write(&dir.join("new/a-1.1.0.jar"), &fabric_jar("a", "1.1.0", ""));
+    write(&dir.join("new/b-1.1.0.jar"), &fabric_jar("b", "1.1.0", ""));
+
+    let plan = PlanFile {
+        mods_dir: None,
+        replacements: vec![
+            entry("a-1.0.0.jar", dir.join("new/a-1.1.0.jar"), false),
+            entry("b-1.0.0.jar", dir.join("new/b-1.1.0.jar"), false),
+        ],
+    };
+    let batch = plan.prepare(&mods, &ReplaceOptions::default()).unwrap();
+    assert_eq!(batch.plans.len(), 2);
+    assert!(!batch.is_blocked());
+    // The second target disappears between planning and applying
+    fs::remove_file(mods.join("b-1.0.0.jar")).unwrap();
+
+    let err = batch::apply(&batch, &BackupStore::for_mods_dir(&mods)).unwrap_err();
+    assert!(
+        matches!(&err, Error::RolledBack { target, .. } if target == "b-1.0.0.jar"),
+        "{err}"
+    );
+    assert_eq!(fs::read(mods.join("a-1.0.0.jar")).unwrap(), a_old);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn problems_no_replacement_is_behind_are_reported_apart() {
+    let dir = scratch("batch-unassigned");
+    let mods = dir.join("mods");
+    // A Forge mod missing a library is not checked while the folder is
+    // mostly Fabric
+    write(
+        &mods.join("x-1.0.0.jar"),
+        &heavy(&[("fabric.mod.json", fabric_json("x", "1.0.0", "").as_bytes())]),
+    );
+    write(
+        &mods.join("f-1.0.0.jar"),
+        &forge_jar(
+            "f",
+            "1.0.0",
+            "[[dependencies.f]]\nmodId = \"lib\"\nmandatory = true\nversionRange = \"[1,)\"\n",
+        ),
+    );
+    write(&dir.join("new/x-forge.jar"), &forge_jar("x", "1.0.0", ""));
+
+    let plan = PlanFile {
+        mods_dir: None,
+        replacements: vec![entry("x-1.0.0.jar", dir.join("new/x-forge.jar"), true)],
+    };
+    let batch = plan.prepare(&mods, &ReplaceOptions::default()).unwrap();
+    let [only] = batch.plans.as_slice() else {
+        panic!("{:?}", batch.plans)
+    };
+    assert!(only.dependency_problems.is_empty());
+    assert!(!only.is_blocked());
+    let [problem] = batch.problems.as_slice() else {
+        panic!("{:?}", batch.problems)
+    };
+    assert_eq!(problem.mod_id, "f");
+    assert_eq!(problem.kind, ProblemKind::Missing);
+    assert!(batch.is_blocked());
+
+    let err = batch::apply(&batch, &BackupStore::for_mods_dir(&mods)).unwrap_err();
+    assert!(matches!(err, Error::Dependencies(_)), "{err}");
+    assert!(fs::read(mods.join("x-1.0.0.jar")).unwrap().len() > 4096);
+
+    let forced = plan
+        .prepare(
+            &mods,
+            &ReplaceOptions {
+                force: true,
+                ..ReplaceOptions::default()
+            },
+        )
+        .unwrap();
+    assert!(!forced.is_blocked());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn the_same_jar_cannot_be_targeted_twice() {
+    let dir = scratch("batch-twice");
+    let mods = dir.join("mods");
+    write(
+        &mods.join("a-1.0.0.jar"),
+        &heavy(&[("fabric.mod.json", fabric_json("a", "1.0.0", "").as_bytes())]),
+    );
+    write(&dir.join("new/a-1.1.0.jar"), &fabric_jar("a", "1.1.0", ""));
+    let plan = PlanFile {
+        mods_dir: None,
+        replacements: vec![
+            entry("a-1.0.0.jar", dir.join("new/a-1.1.0.jar"), false),
+            entry("a", dir.join("new/a-1.1.0.jar"), false),
+        ],
+    };
+    assert!(matches!(
+        plan.prepare(&mods, &ReplaceOptions::default()),
+        Err(Error::InvalidPlan(..))
+    ));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,102 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use minecraft_mod_replacer::zip::ZipWriter;
+
+/// A fresh scratch folder for one test.
+pub fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mod-replacer-test-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A jar holding `files`.
+pub fn jar(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = ZipWriter::new();
+    for (name, data) in files {
+        writer.add_file(name, data).unwrap();
+    }
+    writer.finish().unwrap()
+}
+
+/// A Fabric mod jar. `extra` is spliced into `fabric.mod.json` after the
+/// version, as in `"depends": {"a": "*"}`.
+pub fn fabric_jar(id: &str, version: &str, extra: &str) -> Vec<u8> {
+    let json = fabric_json(id, version, extra);
+    jar(&[
+        ("fabric.mod.json", json.as_bytes()),
+        ("assets/readme.txt", id.as_bytes()),
+    ])
+}
+
+/// A Forge mod jar declaring one mod in `META-INF/mods.toml`. `extra` is
+/// appended to the file, for `[[dependencies.<id>]]` tables.
+pub fn forge_jar(id: &str, version: &str, extra: &str) -> Vec<u8> {
+    let toml = format!(
+        "modLoader = \"javafml\"\nloaderVersion = \"[47,)\"\n\n[[mods]]\nmodId = \"{id}\"\nversion = \"{version}\"\n\n{extra}"
+    );
+    jar(&[("META-INF/mods.toml", toml.as_bytes())])
+}
+
+pub fn write(path: &Path, data: &[u8]) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(path, data).unwrap();
+}
+
+/// `len` bytes that do not compress, to make a jar larger than another.
+pub fn filler(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_u32;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+/// `jar` with an incompressible file added, so that lighter jars of the
+/// same mod fit in its place.
+pub fn heavy(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let filler = filler(4096);
+    let mut all = files.to_vec();
+    all.push(("filler.bin", &filler));
+    jar(&all)
+}
+
+/// `fabric.mod.json` text for `id` at `version`, with `extra` spliced in.
+pub fn fabric_json(id: &str, version: &str, extra: &str) -> String {
+    let mut json = format!(r#"{{"schemaVersion": 1, "id": "{id}", "version": "{version}""#);
+    if !extra.is_empty() {
+        json.push_str(", ");
+        json.push_str(extra);
+    }
+    json.push('}');
+    json
+}
+
+/// A Fabric mod jar that bundles `nested` under `META-INF/jars/`, listed
+/// in its `fabric.mod.json`.
+pub fn host_jar(id: &str, version: &str, nested: &[(&str, &[u8])]) -> Vec<u8> {
+    let listed: Vec<String> = nested
+        .iter()
+        .map(|(name, _)| format!(r#"{{"file": "META-INF/jars/{name}"}}"#))
+        .collect();
+    let json = fabric_json(id, version, &format!(r#""jars": [{}]"#, listed.join(", ")));
+    let paths: Vec<String> = nested
+        .iter()
+        .map(|(name, _)| format!("META-INF/jars/{name}"))
+        .collect();
+    let mut files: Vec<(&str, &[u8])> = vec![("fabric.mod.json", json.as_bytes())];
+    for (path, (_, data)) in paths.iter().zip(nested) {
+        files.push((path, data));
+    }
+    jar(&files)
+}
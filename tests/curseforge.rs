@@ -0,0 +1,194 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use minecraft_mod_replacer::curseforge::{
+    self, EntryStatus, KnownFile, MANIFEST_FILE, OverrideStatus, Pack,
+};
+use minecraft_mod_replacer::hashes::{HashAlgo, curseforge_fingerprint};
+
+use common::{jar, scratch, write};
+
+#[test]
+fn fingerprints_match_murmur2_with_seed_one() {
+    // Reference values of 32-bit MurmurHash2, seed 1, covering every tail length
+    for (data, expected) in [
+        (&b""[..], 1_540_447_798),
+        (b"a", 626_045_324),
+        (b"abc", 1_621_425_345),
+        (b"abcd", 3_376_380_438),
+        (b"helloworld", 2_824_650_221),
+    ] {
+        assert_eq!(
+            curseforge_fingerprint(data),
+            expected,
+            "{}",
+            String::from_utf8_lossy(data)
+        );
+    }
+    assert_eq!(HashAlgo::Murmur2.digest(b"abc"), "1621425345");
+}
+
+#[test]
+fn fingerprints_ignore_whitespace() {
+    assert_eq!(
+        curseforge_fingerprint(b"hello world"),
+        curseforge_fingerprint(b"helloworld")
+    );
+    assert_eq!(
+        curseforge_fingerprint(b" a\tb\r\nc\n"),
+        curseforge_fingerprint(b"abc")
+    );
+    // Other control bytes count
+    assert_ne!(
+        curseforge_fingerprint(b"a\x0bbc"),
+        curseforge_fingerprint(b"abc")
+    );
+}
+
+fn known(project_id: u64, file_id: u64, file_name: &str, data: &[u8]) -> KnownFile {
+    KnownFile {
+        project_id,
+        file_id,
+        file_name: file_name.to_string(),
+        fingerprint: curseforge_fingerprint(data),
+    }
+}
+
+fn manifest(files: &[(u64, u64)]) -> String {
+    let files: Vec<String> = files
+        .iter()
+        .map(|(p, f)| format!(r#"{{"projectID": {p}, "fileID": {f}, "required": true}}"#))
+        .collect();
+    format!(
+        r#"{{"minecraft": {{"version": "1.20.1", "modLoaders": [{{"id": "forge-47.2.0", "primary": true}}]}},
+            "manifestType": "minecraftModpack", "name": "Demo", "files": [{}]}}"#,
+        files.join(", ")
+    )
+}
+
+#[test]
+fn status_sorts_jars_into_entries_overrides_and_extras() {
+    let dir = scratch("curseforge-status");
+    let mods = dir.join("mods");
+    let file = |name: &str, data: &[u8]| {
+        write(&mods.join(name), data);
+        mods.join(name)
+    };
+    let a = file("a.jar", b"project one, file ten");
+    let b = file("b-new.jar", b"project two, file twenty-one");
+    let c = file("c.jar", b"project three, edited by hand");
+    let e51 = file("e51.jar", b"project five, file fifty-one");
+    let e52 = file("e52.jar", b"project five, file fifty-two");
+    let renamed = file("renamed.jar", b"override o");
+    let p = file("p.jar", b"override p, another build");
+    let x = file("x.jar", b"something else");
+
+    let known_files = [
+        known(1, 10, "a.jar", b"project one, file ten"),
+        known(2, 21, "b-new.jar", b"project two, file twenty-one"),
+        known(3, 30, "c.jar", b"project three, file thirty"),
+        known(5, 51, "e51.jar", b"project five, file fifty-one"),
+        known(5, 52, "e52.jar", b"project five, file fifty-two"),
+    ];
+    let pack_data = jar(&[
+        (
+            MANIFEST_FILE,
+            manifest(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (5, 51)]).as_bytes(),
+        ),
+        ("overrides/mods/o.jar", b"override\to"),
+        ("overrides/mods/p.jar", b"override p"),
+        ("overrides/mods/q.jar", b"override q"),
+        ("overrides/config/demo.toml", b"a = 1"),
+    ]);
+    write(&dir.join("pack.zip"), &pack_data);
+    let pack = Pack::read(&dir.join("pack.zip")).unwrap();
+    assert_eq!(pack.manifest.minecraft.version, "1.20.1");
+    assert_eq!(pack.override_mods.len(), 3);
+
+    let jars = curseforge::scan(&mods, &known_files).unwrap();
+    assert_eq!(jars.len(), 8);
+    let status = curseforge::status(&pack, jars, &known_files);
+
+    let entries: Vec<(u64, String)> = status
+        .entries
+        .iter()
+        .map(|(file, status)| (file.file_id, describe(status)))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            (10, format!("present {}", a.display())),
+            (20, format!("different {} Some(21)", b.display())),
+            (30, format!("different {} None", c.display())),
+            (40, "missing".to_string()),
+            // The exact jar of 51 is not taken for 50
+            (50, format!("different {} Some(52)", e52.display())),
+            (51, format!("present {}", e51.display())),
+        ]
+    );
+
+    let overrides: Vec<(&str, Option<&PathBuf>)> = status
+        .overrides
+        .iter()
+        .map(|(name, status)| {
+            (
+                name.as_str(),
+                match status {
+                    OverrideStatus::Present(path) => Some(path),
+                    OverrideStatus::Different(path) => Some(path),
+                    OverrideStatus::Missing => None,
+                },
+            )
+        })
+        .collect();
+    assert_eq!(
+        overrides,
+        [
+            ("o.jar", Some(&renamed)),
+            ("p.jar", Some(&p)),
+            ("q.jar", None)
+        ]
+    );
+    assert!(matches!(status.overrides[0].1, OverrideStatus::Present(_)));
+    assert!(matches!(
+        status.overrides[1].1,
+        OverrideStatus::Different(_)
+    ));
+
+    let extra: Vec<&PathBuf> = status.extra.iter().map(|j| &j.candidate.path).collect();
+    assert_eq!(extra, [&x]);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+fn describe(status: &EntryStatus) -> String {
+    match status {
+        EntryStatus::Present(path) => format!("present {}", path.display()),
+        EntryStatus::Different {
+            path,
+            installed_file_id,
+        } => format!("different {} {installed_file_id:?}", path.display()),
+        EntryStatus::Missing => "missing".to_string(),
+    }
+}
+
+#[test]
+fn reads_known_files_from_the_instance_file() {
+    let dir = scratch("curseforge-known");
+    let json = br#"{"installedAddons": [
+        {"addonID": 1, "installedFile": {"id": 10, "fileName": "a.jar", "packageFingerprint": 1540447798}},
+        {"addonID": 2, "installedFile": {"id": 20, "fileName": "b.jar"}},
+        {"addonID": 3}
+    ]}"#;
+    write(&dir.join("minecraftinstance.json"), json);
+    fs::create_dir_all(dir.join("mods")).unwrap();
+    let path = curseforge::instance_file(&dir.join("mods")).unwrap();
+    let files = curseforge::read_known_files(&path).unwrap();
+    let [only] = files.as_slice() else {
+        panic!("{files:?}")
+    };
+    assert_eq!((only.project_id, only.file_id), (1, 10));
+    assert_eq!(only.fingerprint, curseforge_fingerprint(b""));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,278 @@
+mod common;
+
+use std::path::PathBuf;
+
+use minecraft_mod_replacer::deps::{self, ModSet, Problem, ProblemKind};
+use minecraft_mod_replacer::metadata::{self, JarMetadata};
+
+use common::{fabric_jar, fabric_json, forge_jar, jar};
+
+fn fabric(id: &str, version: &str, extra: &str) -> (JarMetadata, PathBuf) {
+    let data = fabric_jar(id, version, extra);
+    (
+        metadata::read_bytes(&data).unwrap(),
+        PathBuf::from(format!("mods/{id}-{version}.jar")),
+    )
+}
+
+fn forge(id: &str, version: &str, extra: &str) -> (JarMetadata, PathBuf) {
+    let data = forge_jar(id, version, extra);
+    (
+        metadata::read_bytes(&data).unwrap(),
+        PathBuf::from(format!("mods/{id}-{version}.jar")),
+    )
+}
+
+/// `(mod id, dependency id, kind)` of each problem.
+fn summary(problems: &[Problem]) -> Vec<(&str, &str, &ProblemKind)> {
+    problems
+        .iter()
+        .map(|p| (p.mod_id.as_str(), p.dependency.id.as_str(), &p.kind))
+        .collect()
+}
+
+#[test]
+fn required_dependencies_must_be_present_in_range() {
+    let set = ModSet::from_jars(vec![
+        fabric(
+            "a",
+            "1.0.0",
+            r#""depends": {"lib": ">=2.0.0", "minecraft": "1.20.1", "fabricloader": ">=0.15", "java": ">=17"}"#,
+        ),
+        fabric("lib", "1.5.0", ""),
+        fabric("b", "1.0.0", r#""depends": {"missing": "*"}"#),
+        fabric("c", "1.0.0", r#""depends": {"lib": "1.x"}"#),
+    ]);
+    let problems = set.problems();
+    assert_eq!(
+        summary(&problems),
+        [
+            (
+                "a",
+                "lib",
+                &ProblemKind::WrongVersion("lib 1.5.0".to_string())
+            ),
+            ("b", "missing", &ProblemKind::Missing),
+        ]
+    );
+    assert!(problems.iter().all(Problem::is_error));
+    assert_eq!(
+        problems[0].provider,
+        Some(PathBuf::from("mods/lib-1.5.0.jar"))
+    );
+    assert_eq!(
+        problems[0].to_string(),
+        "a (a-1.0.0.jar): requires lib >=2.0.0, but lib 1.5.0 is installed"
+    );
+}
+
+#[test]
+fn recommendations_and_conflicts_only_warn() {
+    let set = ModSet::from_jars(vec![
+        fabric(
+            "a",
+            "1.0.0",
+            r#""recommends": {"extra": "*"}, "conflicts": {"other": "<2"}"#,
+        ),
+        fabric("other", "1.0.0", ""),
+    ]);
+    let problems = set.problems();
+    assert_eq!(
+        summary(&problems),
+        [
+            ("a", "extra", &ProblemKind::Recommended),
+            (
+                "a",
+                "other",
+                &ProblemKind::Conflicts("other 1.0.0".to_string())
+            ),
+        ]
+    );
+    assert!(!problems.iter().any(Problem::is_error));
+}
+
+#[test]
+fn breaks_only_matching_versions() {
+    let breaks = r#""breaks": {"other": "<2.0.0"}"#;
+    let old = ModSet::from_jars(vec![
+        fabric("a", "1.0.0", breaks),
+        fabric("other", "1.9.0", ""),
+    ]);
+    let problems = old.problems();
+    assert_eq!(
+        summary(&problems),
+        [(
+            "a",
+            "other",
+            &ProblemKind::Breaks("other 1.9.0".to_string())
+        )]
+    );
+    assert!(problems[0].is_error());
+
+    let new = ModSet::from_jars(vec![
+        fabric("a", "1.0.0", breaks),
+        fabric("other", "2.0.0", ""),
+    ]);
+    assert!(new.problems().is_empty());
+    // Breaking something that is not installed is fine
+    assert!(
+        ModSet::from_jars(vec![fabric("a", "1.0.0", breaks)])
+            .problems()
+            .is_empty()
+    );
+}
+
+#[test]
+fn provided_ids_satisfy_dependencies() {
+    let set = ModSet::from_jars(vec![
+        fabric("a", "1.0.0", r#""depends": {"api": "*"}"#),
+        fabric("impl", "3.0.0", r#""provides": ["api"]"#),
+        // A mod that depends on what it provides itself is fine too
+        fabric(
+            "self",
+            "1.0.0",
+            r#""depends": {"self_api": "*"}, "provides": ["self_api"]"#,
+        ),
+    ]);
+    assert!(set.problems().is_empty(), "{:?}", set.problems());
+}
+
+#[test]
+fn forge_ranges_use_maven_syntax() {
+    let dep = |range: &str| {
+        format!(
+            "[[dependencies.a]]\nmodId = \"lib\"\nmandatory = true\nversionRange = \"{range}\"\n"
+        )
+    };
+    for (range, ok) in [
+        ("[1.0,2.0)", true),
+        ("[1.0]", false),
+        ("[2,)", false),
+        ("[1.5,)", true),
+    ] {
+        let set = ModSet::from_jars(vec![
+            forge("a", "1.0", &dep(range)),
+            forge("lib", "1.5", ""),
+        ]);
+        assert_eq!(set.problems().is_empty(), ok, "{range}");
+    }
+}
+
+#[test]
+fn only_the_majority_loader_family_is_checked() {
+    // A multi-loader jar whose Forge side needs something the Fabric side does not
+    let both = jar(&[
+        ("fabric.mod.json", fabric_json("multi", "1.0.0", "").as_bytes()),
+        (
+            "META-INF/mods.toml",
+            b"[[mods]]\nmodId = \"multi\"\nversion = \"1.0.0\"\n\n[[dependencies.multi]]\nmodId = \"forgelib\"\nmandatory = true\n",
+        ),
+    ]);
+    let multi = (
+        metadata::read_bytes(&both).unwrap(),
+        PathBuf::from("mods/multi.jar"),
+    );
+
+    let fabric_folder = ModSet::from_jars(vec![multi.clone(), fabric("x", "1.0.0", "")]);
+    assert!(fabric_folder.problems().is_empty());
+
+    let forge_folder = ModSet::from_jars(vec![multi, forge("y", "1.0", ""), forge("z", "1.0", "")]);
+    assert_eq!(
+        summary(&forge_folder.problems()),
+        [("multi", "forgelib", &ProblemKind::Missing)]
+    );
+}
+
+#[test]
+fn fabric_mods_do_not_satisfy_forge_dependencies() {
+    let set = ModSet::from_jars(vec![
+        forge(
+            "a",
+            "1.0",
+            "[[dependencies.a]]\nmodId = \"lib\"\nmandatory = true\n",
+        ),
+        forge("b", "1.0", ""),
+        fabric("lib", "1.0.0", ""),
+    ]);
+    assert_eq!(
+        summary(&set.problems()),
+        [("a", "lib", &ProblemKind::Missing)]
+    );
+}
+
+#[test]
+fn bundled_copies_count_and_the_newest_copy_wins() {
+    let lib_old = fabric_jar("lib", "1.0.0", "");
+    let lib_new = fabric_jar("lib", "2.0.0", "");
+    let host = |lib: &[u8]| {
+        let json = fabric_json(
+            "host",
+            "1.0.0",
+            r#""jars": [{"file": "META-INF/jars/lib.jar"}]"#,
+        );
+        metadata::read_bytes(&jar(&[
+            ("fabric.mod.json", json.as_bytes()),
+            ("META-INF/jars/lib.jar", lib),
+        ]))
+        .unwrap()
+    };
+    let needs_two = fabric("a", "1.0.0", r#""depends": {"lib": ">=2.0.0"}"#);
+
+    // Only a bundled copy, new enough
+    let set = ModSet::from_jars(vec![
+        needs_two.clone(),
+        (host(&lib_new), "mods/host.jar".into()),
+    ]);
+    assert!(set.problems().is_empty());
+
+    // The loader keeps the newer bundled copy over an older standalone jar
+    let set = ModSet::from_jars(vec![
+        needs_two.clone(),
+        fabric("lib", "1.0.0", ""),
+        (host(&lib_new), "mods/host.jar".into()),
+    ]);
+    assert!(set.problems().is_empty());
+
+    // And the newer standalone jar over an older bundled copy
+    let set = ModSet::from_jars(vec![
+        needs_two,
+        fabric("lib", "2.0.0", ""),
+        (host(&lib_old), "mods/host.jar".into()),
+    ]);
+    assert!(set.problems().is_empty());
+}
+
+#[test]
+fn introduced_ignores_problems_that_were_there_before() {
+    let before = ModSet::from_jars(vec![
+        fabric(
+            "a",
+            "1.0.0",
+            r#""depends": {"gone": "*", "lib": ">=1.0.0"}"#,
+        ),
+        fabric("lib", "1.0.0", ""),
+    ]);
+    let mut after = before.clone();
+    let downgraded = fabric("lib", "0.9.0", "").0;
+    after.swap(&PathBuf::from("mods/lib-1.0.0.jar"), Some(&downgraded));
+
+    let new = deps::introduced(&before, &after);
+    assert_eq!(
+        summary(&new),
+        [(
+            "a",
+            "lib",
+            &ProblemKind::WrongVersion("lib 0.9.0".to_string())
+        )]
+    );
+    assert!(new[0].involves(&PathBuf::from("mods/lib-1.0.0.jar"), &before));
+    assert!(!new[0].involves(&PathBuf::from("mods/other.jar"), &before));
+
+    // Removing the library is a new problem of a different kind
+    let mut removed = before.clone();
+    removed.swap(&PathBuf::from("mods/lib-1.0.0.jar"), None);
+    assert_eq!(
+        summary(&deps::introduced(&before, &removed)),
+        [("a", "lib", &ProblemKind::Missing)]
+    );
+}
@@ -0,0 +1,121 @@
+use minecraft_mod_replacer::diff::{Hunk, LineChange, diff_lines};
+
+fn unified(hunks: &[Hunk]) -> String {
+    hunks.iter().map(|h| h.to_string()).collect()
+}
+
+fn numbered(prefix: &str, range: std::ops::Range<usize>) -> String {
+    range.map(|i| format!("{prefix}{i}\n")).collect()
+}
+
+#[test]
+fn identical_texts_have_no_hunks() {
+    assert!(diff_lines("a\nb\n", "a\nb\n", 3).is_empty());
+    assert!(diff_lines("", "", 3).is_empty());
+}
+
+#[test]
+fn text_added_to_an_empty_file_starts_at_line_zero() {
+    let hunks = diff_lines("", "a\nb\n", 3);
+    assert_eq!(unified(&hunks), "@@ -0,0 +1,2 @@\n+a\n+b\n");
+
+    let hunks = diff_lines("a\nb\n", "", 3);
+    assert_eq!(unified(&hunks), "@@ -1,2 +0,0 @@\n-a\n-b\n");
+}
+
+#[test]
+fn pure_insertion_is_numbered_after_the_line_it_follows() {
+    let hunks = diff_lines("a\nb\nc\n", "a\nb\nnew\nc\n", 0);
+    assert_eq!(unified(&hunks), "@@ -2,0 +3,1 @@\n+new\n");
+
+    let hunks = diff_lines("a\nb\nc\n", "a\nb\nnew\nc\n", 1);
+    assert_eq!(unified(&hunks), "@@ -2,2 +2,3 @@\n b\n+new\n c\n");
+}
+
+#[test]
+fn changes_within_twice_the_context_share_a_hunk() {
+    let old = numbered("line", 1..21);
+    // Lines 5 and 10 change: four unchanged lines between them
+    let near = old
+        .replace("line5\n", "five\n")
+        .replace("line10\n", "ten\n");
+    let hunks = diff_lines(&old, &near, 2);
+    assert_eq!(hunks.len(), 1);
+    assert_eq!(
+        (hunks[0].old_start, hunks[0].old_len),
+        (hunks[0].new_start, hunks[0].new_len)
+    );
+    assert_eq!((hunks[0].old_start, hunks[0].old_len), (3, 10));
+
+    // Lines 5 and 11: five unchanged lines are more than 2 * 2
+    let far = old
+        .replace("line5\n", "five\n")
+        .replace("line11\n", "eleven\n");
+    let hunks = diff_lines(&old, &far, 2);
+    let ranges: Vec<(usize, usize)> = hunks.iter().map(|h| (h.old_start, h.old_len)).collect();
+    assert_eq!(ranges, [(3, 5), (9, 5)]);
+    assert_eq!(
+        unified(&hunks[1..]),
+        "@@ -9,5 +9,5 @@\n line9\n line10\n-line11\n+eleven\n line12\n line13\n"
+    );
+}
+
+#[test]
+fn finds_a_shortest_edit_script() {
+    let hunks = diff_lines("a\nb\nc\na\nb\nb\na\n", "c\nb\na\nb\na\nc\n", 0);
+    let edits: Vec<LineChange> = hunks
+        .iter()
+        .flat_map(|h| h.lines.iter().map(|(change, _)| *change))
+        .collect();
+    // The classic example from Myers' paper has an edit distance of 5
+    assert_eq!(edits.len(), 5);
+    assert!(edits.iter().all(|change| *change != LineChange::Same));
+}
+
+#[test]
+fn large_differences_are_shown_as_a_full_replacement() {
+    // "shared" is common to both, but only a search past MAX_EDITS would find it
+    let old = format!(
+        "first\n{}shared\n{}last\n",
+        numbered("old", 0..1100),
+        numbered("old", 1100..2200)
+    );
+    let new = format!(
+        "first\n{}shared\n{}last\n",
+        numbered("new", 0..1100),
+        numbered("new", 1100..2200)
+    );
+    let hunks = diff_lines(&old, &new, 1);
+    let [hunk] = hunks.as_slice() else {
+        panic!("{} hunks", hunks.len())
+    };
+    assert_eq!((hunk.old_start, hunk.old_len), (1, 2203));
+    assert_eq!((hunk.new_start, hunk.new_len), (1, 2203));
+    assert_eq!(hunk.lines[1], (LineChange::Removed, "old0".to_string()));
+    assert_eq!(
+        hunk.lines[1101],
+        (LineChange::Removed, "shared".to_string())
+    );
+    assert_eq!(hunk.lines[2202], (LineChange::Added, "new0".to_string()));
+    assert_eq!(hunk.lines.last().unwrap().0, LineChange::Same);
+
+    // Below the limit the shared line is kept
+    let old = format!(
+        "first\n{}shared\n{}last\n",
+        numbered("old", 0..10),
+        numbered("old", 10..20)
+    );
+    let new = format!(
+        "first\n{}shared\n{}last\n",
+        numbered("new", 0..10),
+        numbered("new", 10..20)
+    );
+    let hunks = diff_lines(&old, &new, 0);
+    assert_eq!(hunks.len(), 2);
+    assert!(
+        hunks
+            .iter()
+            .flat_map(|h| &h.lines)
+            .all(|(_, line)| line != "shared")
+    );
+}
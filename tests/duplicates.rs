@@ -0,0 +1,156 @@
+mod common;
+
+use std::fs::{self, File};
+use std::time::{Duration, SystemTime};
+
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::duplicates::{self, DuplicateGroup};
+
+use common::{fabric_jar, host_jar, scratch, write};
+
+/// `(file name, bundled entry)` of each copy, kept one first.
+fn copies(group: &DuplicateGroup) -> Vec<(String, Option<&str>)> {
+    group
+        .jars
+        .iter()
+        .map(|jar| (jar.candidate.file_name(), jar.bundled.as_deref()))
+        .collect()
+}
+
+#[test]
+fn keeps_the_newest_jar_and_removes_the_rest() {
+    let dir = scratch("duplicates-versions");
+    write(
+        &dir.join("sodium-0.5.3.jar"),
+        &fabric_jar("sodium", "0.5.3", ""),
+    );
+    write(
+        &dir.join("sodium-0.5.8.jar"),
+        &fabric_jar("sodium", "0.5.8", ""),
+    );
+    write(
+        &dir.join("sodium-0.4.0.jar"),
+        &fabric_jar("sodium", "0.4.0", ""),
+    );
+    // Disabled jars are not loaded, so they never clash
+    write(
+        &dir.join("sodium-0.6.0.jar.disabled"),
+        &fabric_jar("sodium", "0.6.0", ""),
+    );
+    write(&dir.join("iris.jar"), &fabric_jar("iris", "1.6.0", ""));
+
+    let groups = duplicates::find(&dir).unwrap();
+    let [group] = groups.as_slice() else {
+        panic!("{groups:?}")
+    };
+    assert_eq!(group.mod_id, "sodium");
+    assert_eq!(group.kept().candidate.file_name(), "sodium-0.5.8.jar");
+    let mut removable: Vec<String> = group.removable().map(|j| j.candidate.file_name()).collect();
+    removable.sort();
+    assert_eq!(removable, ["sodium-0.4.0.jar", "sodium-0.5.3.jar"]);
+
+    let paths: Vec<_> = group
+        .removable()
+        .map(|j| j.candidate.path.clone())
+        .collect();
+    let mut backup = BackupStore::for_mods_dir(&dir).begin("duplicates").unwrap();
+    duplicates::remove(&paths, &mut backup).unwrap();
+    assert!(duplicates::find(&dir).unwrap().is_empty());
+    assert!(dir.join("sodium-0.5.8.jar").exists());
+    assert_eq!(backup.manifest.entries.len(), 2);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn keeps_the_most_recent_jar_when_versions_do_not_compare() {
+    let dir = scratch("duplicates-mtime");
+    let old = dir.join("demo-a.jar");
+    let new = dir.join("demo-b.jar");
+    write(&old, &fabric_jar("demo", "build-a", ""));
+    write(&new, &fabric_jar("demo", "build-b", ""));
+    let now = SystemTime::now();
+    File::options()
+        .write(true)
+        .open(&old)
+        .unwrap()
+        .set_modified(now - Duration::from_secs(3600))
+        .unwrap();
+    File::options()
+        .write(true)
+        .open(&new)
+        .unwrap()
+        .set_modified(now)
+        .unwrap();
+
+    let groups = duplicates::find(&dir).unwrap();
+    assert_eq!(groups[0].kept().candidate.path, new);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn a_newer_bundled_copy_never_costs_the_jar_of_its_own() {
+    let dir = scratch("duplicates-bundled-newer");
+    write(&dir.join("lib-1.0.0.jar"), &fabric_jar("lib", "1.0.0", ""));
+    let bundled = fabric_jar("lib", "2.0.0", "");
+    write(
+        &dir.join("host-1.0.0.jar"),
+        &host_jar("host", "1.0.0", &[("lib-2.0.0.jar", &bundled)]),
+    );
+
+    let groups = duplicates::find(&dir).unwrap();
+    let [group] = groups.as_slice() else {
+        panic!("{groups:?}")
+    };
+    assert_eq!(
+        copies(group),
+        [
+            ("lib-1.0.0.jar".to_string(), None),
+            (
+                "host-1.0.0.jar".to_string(),
+                Some("META-INF/jars/lib-2.0.0.jar")
+            ),
+        ]
+    );
+    assert_eq!(group.removable().count(), 0);
+    assert!(group.is_newer_than_kept(&group.others()[0]));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn bundled_copies_join_the_group_of_their_mod() {
+    let dir = scratch("duplicates-bundled");
+    let lib_old = fabric_jar("lib", "0.9.0", "");
+    let inner = host_jar("middle", "1.0.0", &[("lib.jar", &lib_old)]);
+    write(&dir.join("lib-1.0.0.jar"), &fabric_jar("lib", "1.0.0", ""));
+    write(
+        &dir.join("outer-1.0.0.jar"),
+        &host_jar("outer", "1.0.0", &[("middle.jar", &inner)]),
+    );
+    // Two mods bundling the same library, with no jar of its own
+    let shared = fabric_jar("shared", "1.0.0", "");
+    write(
+        &dir.join("a.jar"),
+        &host_jar("a", "1.0.0", &[("shared.jar", &shared)]),
+    );
+    write(
+        &dir.join("b.jar"),
+        &host_jar("b", "1.0.0", &[("shared.jar", &shared)]),
+    );
+
+    let groups = duplicates::find(&dir).unwrap();
+    let [group] = groups.as_slice() else {
+        panic!("{groups:?}")
+    };
+    assert_eq!(group.mod_id, "lib");
+    // Found two levels down
+    assert_eq!(
+        copies(group),
+        [
+            ("lib-1.0.0.jar".to_string(), None),
+            ("outer-1.0.0.jar".to_string(), Some("META-INF/jars/lib.jar")),
+        ]
+    );
+    assert!(!group.is_newer_than_kept(&group.others()[0]));
+    assert_eq!(group.removable().count(), 0);
+    fs::remove_dir_all(dir).unwrap();
+}
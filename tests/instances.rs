@@ -0,0 +1,138 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use minecraft_mod_replacer::instances::{self, Instance, Launcher, SearchRoots};
+
+use common::{scratch, write};
+
+fn roots(home: &Path) -> SearchRoots {
+    SearchRoots {
+        home: home.to_path_buf(),
+        data_home: home.join(".local/share"),
+        config_home: home.join(".config"),
+    }
+}
+
+fn mmc_pack(minecraft: &str, loader_uid: &str, loader: &str) -> String {
+    format!(
+        r#"{{"formatVersion": 1, "components": [
+            {{"uid": "org.lwjgl3", "version": "3.3.1"}},
+            {{"uid": "net.minecraft", "version": "{minecraft}"}},
+            {{"uid": "{loader_uid}", "version": "{loader}"}}
+        ]}}"#
+    )
+}
+
+fn find<'a>(found: &'a [Instance], name: &str) -> &'a Instance {
+    found
+        .iter()
+        .find(|i| i.name == name)
+        .unwrap_or_else(|| panic!("no instance {name} in {found:?}"))
+}
+
+#[test]
+fn finds_vanilla_prism_and_multimc_instances() {
+    let home = scratch("instances");
+    fs::create_dir_all(home.join(".minecraft/mods")).unwrap();
+
+    let prism = home.join(".local/share/PrismLauncher/instances/fabulous");
+    write(
+        &prism.join("instance.cfg"),
+        b"[General]\nInstanceType=OneSix\nname=Fabulous Pack\n",
+    );
+    write(
+        &prism.join("mmc-pack.json"),
+        mmc_pack("1.20.1", "net.fabricmc.fabric-loader", "0.15.11").as_bytes(),
+    );
+    fs::create_dir_all(prism.join(".minecraft/mods")).unwrap();
+
+    let flatpak =
+        home.join(".var/app/org.prismlauncher.PrismLauncher/data/PrismLauncher/instances/neo");
+    write(&flatpak.join("instance.cfg"), b"name=Neo\n");
+    write(
+        &flatpak.join("mmc-pack.json"),
+        mmc_pack("1.21", "net.neoforged", "21.0.42").as_bytes(),
+    );
+    fs::create_dir_all(flatpak.join(".minecraft/mods")).unwrap();
+
+    // MultiMC without a name, with the game in `minecraft`
+    let multimc = home.join("MultiMC/instances/old-forge");
+    write(&multimc.join("instance.cfg"), b"InstanceType=OneSix\n");
+    write(
+        &multimc.join("mmc-pack.json"),
+        mmc_pack("1.12.2", "net.minecraftforge", "14.23.5.2860").as_bytes(),
+    );
+    fs::create_dir_all(multimc.join("minecraft/mods")).unwrap();
+
+    // Neither of these has a mods folder yet, so neither is listed
+    let fresh = home.join(".local/share/PrismLauncher/instances/fresh");
+    write(&fresh.join("instance.cfg"), b"name=Fresh\n");
+    fs::create_dir_all(home.join(".local/share/PrismLauncher/instances/not-an-instance/mods"))
+        .unwrap();
+
+    let found = instances::discover_in(&roots(&home));
+    let mut names: Vec<&str> = found.iter().map(|i| i.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, ["Fabulous Pack", "Minecraft", "Neo", "old-forge"]);
+
+    let vanilla = find(&found, "Minecraft");
+    assert_eq!(vanilla.launcher, Launcher::Vanilla);
+    assert_eq!(vanilla.mods_dir, home.join(".minecraft/mods"));
+    assert_eq!(vanilla.minecraft, None);
+
+    let fabulous = find(&found, "Fabulous Pack");
+    assert_eq!(fabulous.launcher, Launcher::Prism);
+    assert_eq!(fabulous.game_dir, prism.join(".minecraft"));
+    assert_eq!(fabulous.mods_dir, prism.join(".minecraft/mods"));
+    assert_eq!(fabulous.minecraft.as_deref(), Some("1.20.1"));
+    assert_eq!(fabulous.loader.as_deref(), Some("Fabric 0.15.11"));
+
+    let neo = find(&found, "Neo");
+    assert_eq!(neo.launcher, Launcher::Prism);
+    assert_eq!(neo.loader.as_deref(), Some("NeoForge 21.0.42"));
+
+    let old = find(&found, "old-forge");
+    assert_eq!(old.launcher, Launcher::MultiMc);
+    assert_eq!(old.mods_dir, multimc.join("minecraft/mods"));
+    assert_eq!(old.minecraft.as_deref(), Some("1.12.2"));
+    assert_eq!(old.loader.as_deref(), Some("Forge 14.23.5.2860"));
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn finds_atlauncher_and_curseforge_instances() {
+    let home = scratch("instances-other");
+    let atl = home.join(".local/share/ATLauncher/instances/Pack");
+    write(
+        &atl.join("instance.json"),
+        br#"{"id": "1.20.4", "launcher": {"name": "AT Pack", "loaderVersion": {"type": "Fabric", "version": "0.15.7"}}}"#,
+    );
+    fs::create_dir_all(atl.join("mods")).unwrap();
+    let cf = home.join("curseforge/minecraft/Instances/All the Mods");
+    write(
+        &cf.join("minecraftinstance.json"),
+        br#"{"name": "ATM9", "gameVersion": "1.20.1", "baseModLoader": {"name": "forge-47.2.0"}}"#,
+    );
+    fs::create_dir_all(cf.join("mods")).unwrap();
+
+    let found = instances::discover_in(&roots(&home));
+    assert_eq!(found.len(), 2, "{found:?}");
+    let at = find(&found, "AT Pack");
+    assert_eq!(at.launcher, Launcher::AtLauncher);
+    assert_eq!(at.minecraft.as_deref(), Some("1.20.4"));
+    assert_eq!(at.loader.as_deref(), Some("Fabric 0.15.7"));
+    let atm = find(&found, "ATM9");
+    assert_eq!(atm.launcher, Launcher::CurseForge);
+    assert_eq!(atm.loader.as_deref(), Some("Forge 47.2.0"));
+    assert_eq!(atm.mods_dir, cf.join("mods"));
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn an_empty_home_has_no_instances() {
+    let home = scratch("instances-none");
+    assert!(instances::discover_in(&roots(&home)).is_empty());
+    fs::remove_dir_all(home).unwrap();
+}
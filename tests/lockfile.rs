@@ -0,0 +1,139 @@
+mod common;
+
+use std::fs;
+
+use common::{fabric_jar, scratch, write};
+use minecraft_mod_replacer::Error;
+use minecraft_mod_replacer::lockfile::{Change, Lockfile};
+
+/// Each change as `kind old-file new-file`, sorted.
+fn summary(changes: &[Change]) -> Vec<String> {
+    let mut lines: Vec<String> = changes
+        .iter()
+        .map(|change| match change {
+            Change::Added(jar) => format!("added {}", jar.file),
+            Change::Removed(jar) => format!("removed {}", jar.file),
+            Change::Modified { old, new } => format!("modified {} {}", old.file, new.file),
+            Change::Renamed { old, new } => format!("renamed {} {}", old.file, new.file),
+        })
+        .collect();
+    lines.sort();
+    lines
+}
+
+#[test]
+fn round_trips_through_the_file() {
+    let dir = scratch("lockfile-round-trip");
+    let mods = dir.join("mods");
+    write(&mods.join("sodium.jar"), &fabric_jar("sodium", "0.5.8", ""));
+    write(&mods.join("plain.jar.disabled"), b"not a zip");
+
+    let lock = Lockfile::scan(&mods).unwrap();
+    let path = dir.join("state").join("mods.lock");
+    lock.save(&path).unwrap();
+    let loaded = Lockfile::load(&path).unwrap();
+    assert_eq!(loaded.jars, lock.jars);
+    assert!(lock.changes(&loaded).is_empty());
+
+    let sodium = loaded.jars.iter().find(|j| j.file == "sodium.jar").unwrap();
+    assert_eq!(sodium.describe().as_deref(), Some("sodium 0.5.8"));
+    let plain = loaded.jars.iter().find(|j| j.file == "plain.jar.disabled");
+    assert_eq!(plain.unwrap().describe(), None);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn reports_added_removed_modified_and_renamed_jars() {
+    let dir = scratch("lockfile-changes");
+    let mods = dir.join("mods");
+    write(&mods.join("kept.jar"), &fabric_jar("kept", "1.0.0", ""));
+    write(
+        &mods.join("toggled.jar"),
+        &fabric_jar("toggled", "1.0.0", ""),
+    );
+    write(&mods.join("moved.jar"), &fabric_jar("moved", "1.0.0", ""));
+    write(
+        &mods.join("updated.jar"),
+        &fabric_jar("updated", "1.0.0", ""),
+    );
+    write(&mods.join("gone.jar"), &fabric_jar("gone", "1.0.0", ""));
+    write(&mods.join("both-1.0.jar"), &fabric_jar("both", "1.0.0", ""));
+    let before = Lockfile::scan(&mods).unwrap();
+
+    fs::rename(mods.join("toggled.jar"), mods.join("toggled.jar.disabled")).unwrap();
+    fs::rename(mods.join("moved.jar"), mods.join("moved-again.jar")).unwrap();
+    write(
+        &mods.join("updated.jar"),
+        &fabric_jar("updated", "2.0.0", ""),
+    );
+    fs::remove_file(mods.join("gone.jar")).unwrap();
+    write(&mods.join("new.jar"), &fabric_jar("new", "1.0.0", ""));
+    // Renamed and changed at once: nothing ties the two files together
+    fs::remove_file(mods.join("both-1.0.jar")).unwrap();
+    write(&mods.join("both-2.0.jar"), &fabric_jar("both", "2.0.0", ""));
+    let after = Lockfile::scan(&mods).unwrap();
+
+    let changes = before.changes(&after);
+    assert_eq!(
+        summary(&changes),
+        [
+            "added both-2.0.jar",
+            "added new.jar",
+            "modified updated.jar updated.jar",
+            "removed both-1.0.jar",
+            "removed gone.jar",
+            "renamed moved.jar moved-again.jar",
+            "renamed toggled.jar toggled.jar.disabled",
+        ]
+    );
+    let modified = changes
+        .iter()
+        .find_map(|c| match c {
+            Change::Modified { old, new } => Some((old, new)),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(modified.0.mod_version.as_deref(), Some("1.0.0"));
+    assert_eq!(modified.1.mod_version.as_deref(), Some("2.0.0"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn a_renamed_jar_is_matched_once_by_content() {
+    let dir = scratch("lockfile-copies");
+    let mods = dir.join("mods");
+    let data = fabric_jar("lib", "1.0.0", "");
+    write(&mods.join("lib.jar"), &data);
+    let before = Lockfile::scan(&mods).unwrap();
+
+    fs::remove_file(mods.join("lib.jar")).unwrap();
+    write(&mods.join("lib-a.jar"), &data);
+    write(&mods.join("lib-b.jar"), &data);
+    let after = Lockfile::scan(&mods).unwrap();
+    assert_eq!(
+        summary(&before.changes(&after)),
+        ["added lib-b.jar", "renamed lib.jar lib-a.jar"]
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn refuses_lockfiles_of_an_unknown_format_version() {
+    let dir = scratch("lockfile-version");
+    let path = dir.join("mods.lock");
+    write(&path, b"version = 2\n");
+    match Lockfile::load(&path) {
+        Err(Error::InvalidLockfile(file, reason)) => {
+            assert_eq!(file, path);
+            assert_eq!(reason, "unsupported version 2");
+        }
+        other => panic!("{other:?}"),
+    }
+
+    write(&path, b"jar = 3\n");
+    assert!(matches!(
+        Lockfile::load(&path),
+        Err(Error::InvalidLockfile(..))
+    ));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,381 @@
+mod common;
+
+use minecraft_mod_replacer::Error;
+use minecraft_mod_replacer::metadata::{self, DependencyKind, Environment, Loader, ModMetadata};
+
+use common::{fabric_jar, forge_jar, host_jar, jar};
+
+/// The dependencies of `m` as `(id, kind, versions)`.
+fn deps(m: &ModMetadata) -> Vec<(&str, DependencyKind, Option<&str>)> {
+    m.dependencies
+        .iter()
+        .map(|d| (d.id.as_str(), d.kind, d.versions.as_deref()))
+        .collect()
+}
+
+#[test]
+fn reads_fabric_mod_json() {
+    let json = br#"{
+        "schemaVersion": 1,
+        "id": "demo",
+        "name": "Demo",
+        "version": "1.2.0",
+        "environment": "client",
+        "depends": {"minecraft": "~1.20.1", "fabric-api": ["0.90.x", ">=0.91"]},
+        "recommends": {"modmenu": "*"},
+        "breaks": {"optifabric": "<1.0"},
+        "conflicts": {"sodium": "*"},
+        "provides": ["demo_api"],
+        "mixins": ["demo.mixins.json", {"config": "demo.client.mixins.json", "environment": "client"}]
+    }"#;
+    let jar = metadata::read_bytes(&jar(&[("fabric.mod.json", json)])).unwrap();
+    assert!(jar.errors.is_empty());
+    let [m] = jar.mods.as_slice() else {
+        panic!("{:?}", jar.mods)
+    };
+    assert_eq!(m.id, "demo");
+    assert_eq!(m.display_name(), "Demo");
+    assert_eq!(m.version.as_deref(), Some("1.2.0"));
+    assert_eq!(m.loader, Loader::Fabric);
+    assert_eq!(m.minecraft.as_deref(), Some("~1.20.1"));
+    assert_eq!(m.environment, Environment::Client);
+    assert_eq!(m.provides, ["demo_api"]);
+    assert_eq!(m.mixins, ["demo.mixins.json", "demo.client.mixins.json"]);
+    assert_eq!(m.source, "fabric.mod.json");
+    assert_eq!(
+        deps(m),
+        [
+            (
+                "fabric-api",
+                DependencyKind::Required,
+                Some("0.90.x || >=0.91")
+            ),
+            ("minecraft", DependencyKind::Required, Some("~1.20.1")),
+            ("modmenu", DependencyKind::Recommended, Some("*")),
+            ("optifabric", DependencyKind::Breaks, Some("<1.0")),
+            ("sodium", DependencyKind::Conflicts, Some("*")),
+        ]
+    );
+}
+
+#[test]
+fn reads_quilt_mod_json() {
+    let json = br#"{
+        "schema_version": 1,
+        "quilt_loader": {
+            "group": "org.example",
+            "id": "demo",
+            "version": "2.0.0",
+            "metadata": {"name": "Quilt Demo"},
+            "depends": [
+                "org.quiltmc:qsl",
+                {"id": "minecraft", "versions": {"any": ["1.20.1", "1.20.2"]}},
+                {"id": "modmenu", "versions": ">=7", "optional": true}
+            ],
+            "breaks": [{"id": "sodium", "versions": ["<0.5"]}],
+            "provides": ["org.example:demo_api", {"id": "demo_compat"}]
+        },
+        "minecraft": {"environment": "dedicated_server"},
+        "mixin": "demo.mixins.json"
+    }"#;
+    let jar = metadata::read_bytes(&jar(&[("quilt.mod.json", json)])).unwrap();
+    let m = jar.primary().unwrap();
+    assert_eq!(m.id, "demo");
+    assert_eq!(m.display_name(), "Quilt Demo");
+    assert_eq!(m.loader, Loader::Quilt);
+    assert_eq!(m.minecraft.as_deref(), Some("1.20.1 || 1.20.2"));
+    assert_eq!(m.environment, Environment::Server);
+    assert_eq!(m.provides, ["demo_api", "demo_compat"]);
+    assert_eq!(m.mixins, ["demo.mixins.json"]);
+    assert_eq!(
+        deps(m),
+        [
+            ("qsl", DependencyKind::Required, None),
+            (
+                "minecraft",
+                DependencyKind::Required,
+                Some("1.20.1 || 1.20.2")
+            ),
+            ("modmenu", DependencyKind::Optional, Some(">=7")),
+            ("sodium", DependencyKind::Breaks, Some("<0.5")),
+        ]
+    );
+}
+
+#[test]
+fn reads_forge_mods_toml() {
+    let toml = br#"
+modLoader = "javafml"
+loaderVersion = "[47,)"
+clientSideOnly = true
+
+[[mods]]
+modId = "demo"
+displayName = "Forge Demo"
+version = "${file.jarVersion}"
+
+[[mods]]
+modId = "demo_addon"
+version = "1.0"
+
+[[dependencies.demo]]
+modId = "minecraft"
+mandatory = true
+versionRange = "[1.20.1,1.21)"
+
+[[dependencies.demo]]
+modId = "jei"
+mandatory = false
+versionRange = "[15,)"
+
+[[dependencies.demo]]
+modId = "optifine"
+type = "INCOMPATIBLE"
+"#;
+    // A BOM and a wrapped manifest line, as some build tools write them
+    let mut text = "\u{feff}".as_bytes().to_vec();
+    text.extend_from_slice(toml);
+    let manifest = b"Manifest-Version: 1.0\r\nImplementation-Version: 3.1.4\r\nMixinConfigs: demo.mixins.json,\r\n  demo.compat.mixins.json\r\n";
+    let jar = metadata::read_bytes(&jar(&[
+        ("META-INF/mods.toml", &text),
+        ("META-INF/MANIFEST.MF", manifest),
+    ]))
+    .unwrap();
+    assert_eq!(jar.loaders(), [Loader::Forge]);
+    let [demo, addon] = jar.mods.as_slice() else {
+        panic!("{:?}", jar.mods)
+    };
+    assert_eq!(demo.id, "demo");
+    assert_eq!(demo.display_name(), "Forge Demo");
+    assert_eq!(demo.version.as_deref(), Some("3.1.4"));
+    assert_eq!(demo.minecraft.as_deref(), Some("[1.20.1,1.21)"));
+    assert_eq!(demo.environment, Environment::Client);
+    assert_eq!(demo.mixins, ["demo.mixins.json", "demo.compat.mixins.json"]);
+    assert_eq!(
+        deps(demo),
+        [
+            ("minecraft", DependencyKind::Required, Some("[1.20.1,1.21)")),
+            ("jei", DependencyKind::Optional, Some("[15,)")),
+            ("optifine", DependencyKind::Breaks, None),
+        ]
+    );
+    assert_eq!(addon.id, "demo_addon");
+    assert_eq!(addon.version.as_deref(), Some("1.0"));
+    assert!(addon.dependencies.is_empty());
+}
+
+#[test]
+fn reads_neoforge_mods_toml() {
+    let toml = br#"
+[[mods]]
+modId = "demo"
+version = "21.0.1"
+
+[[mixins]]
+config = "demo.mixins.json"
+
+[[dependencies.demo]]
+modId = "neoforge"
+type = "required"
+versionRange = "[21,)"
+
+[[dependencies.demo]]
+modId = "embeddium"
+type = "discouraged"
+"#;
+    let jar = metadata::read_bytes(&jar(&[("META-INF/neoforge.mods.toml", toml)])).unwrap();
+    let m = jar.primary().unwrap();
+    assert_eq!(m.loader, Loader::NeoForge);
+    assert_eq!(m.source, "META-INF/neoforge.mods.toml");
+    assert_eq!(m.environment, Environment::Both);
+    assert_eq!(m.mixins, ["demo.mixins.json"]);
+    assert_eq!(
+        deps(m),
+        [
+            ("neoforge", DependencyKind::Required, Some("[21,)")),
+            ("embeddium", DependencyKind::Conflicts, None),
+        ]
+    );
+}
+
+#[test]
+fn reads_legacy_mcmod_info() {
+    let list = br#"[{"modid": "old", "name": "Old Mod", "version": "1.0", "mcversion": "1.7.10"}]"#;
+    let v2 = br#"{"modListVersion": 2, "modList": [{"modid": "older"}]}"#;
+    for (data, id) in [(&list[..], "old"), (&v2[..], "older")] {
+        let jar = metadata::read_bytes(&jar(&[("mcmod.info", data)])).unwrap();
+        let m = jar.primary().unwrap();
+        assert_eq!(m.id, id);
+        assert_eq!(m.loader, Loader::Forge);
+        assert_eq!(m.source, "mcmod.info");
+    }
+    let m = metadata::read_bytes(&jar(&[("mcmod.info", list)]))
+        .unwrap()
+        .mods[0]
+        .clone();
+    assert_eq!(m.minecraft.as_deref(), Some("1.7.10"));
+}
+
+#[test]
+fn prefers_modern_descriptors_over_mcmod_info() {
+    let jar = metadata::read_bytes(&jar(&[
+        ("fabric.mod.json", br#"{"id": "modern", "version": "1"}"#),
+        ("mcmod.info", br#"[{"modid": "legacy"}]"#),
+    ]))
+    .unwrap();
+    let ids: Vec<&str> = jar.mods.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, ["modern"]);
+}
+
+#[test]
+fn multi_loader_jars_list_every_descriptor() {
+    let jar = metadata::read_bytes(&jar(&[
+        ("fabric.mod.json", br#"{"id": "demo", "version": "1"}"#),
+        (
+            "quilt.mod.json",
+            br#"{"quilt_loader": {"id": "demo", "version": "1"}}"#,
+        ),
+        (
+            "META-INF/mods.toml",
+            b"[[mods]]\nmodId = \"demo\"\nversion = \"1\"\n",
+        ),
+    ]))
+    .unwrap();
+    assert_eq!(
+        jar.loaders(),
+        [Loader::Quilt, Loader::Fabric, Loader::Forge]
+    );
+}
+
+#[test]
+fn a_broken_descriptor_keeps_the_others() {
+    let jar = metadata::read_bytes(&jar(&[
+        ("quilt.mod.json", b"{\"quilt_loader\": "),
+        ("fabric.mod.json", br#"{"id": "demo", "version": "1"}"#),
+    ]))
+    .unwrap();
+    assert_eq!(jar.primary().unwrap().id, "demo");
+    assert_eq!(jar.errors.len(), 1);
+    assert!(jar.errors[0].contains("quilt.mod.json"), "{:?}", jar.errors);
+}
+
+#[test]
+fn only_broken_descriptors_are_an_error() {
+    let err = metadata::read_bytes(&jar(&[
+        ("fabric.mod.json", b"{\"id\": 5}"),
+        ("META-INF/mods.toml", b"[[mods]\n"),
+    ]))
+    .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::InvalidMetadata {
+                file: "fabric.mod.json",
+                ..
+            }
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn jars_without_descriptors_are_empty() {
+    let jar = metadata::read_bytes(&jar(&[("a.txt", b"a")])).unwrap();
+    assert!(jar.is_empty());
+    assert!(jar.errors.is_empty());
+}
+
+#[test]
+fn reads_jars_bundled_by_fabric_quilt_and_jarjar() {
+    let lib = fabric_jar("lib", "1.0.0", "");
+    let qlib = fabric_jar("qlib", "2.0.0", "");
+    let forge_lib = forge_jar("flib", "3.0", "");
+    let quilt = br#"{"quilt_loader": {"id": "host", "version": "1", "jars": ["META-INF/jars/qlib.jar", "META-INF/jars/lib.jar"]}}"#;
+    let jarjar = br#"{"jars": [
+        {"identifier": {"group": "org.example", "artifact": "flib"},
+         "version": {"range": "[3,)", "artifactVersion": "3.0"},
+         "path": "META-INF/jarjar/flib.jar"},
+        {"identifier": {"group": "org.example", "artifact": "plain"},
+         "path": "META-INF/jarjar/plain.jar"}
+    ]}"#;
+    let data = jar(&[
+        (
+            "fabric.mod.json",
+            br#"{"id": "host", "version": "1", "jars": [{"file": "META-INF/jars/lib.jar"}, {"file": "META-INF/jars/gone.jar"}]}"#,
+        ),
+        ("quilt.mod.json", quilt),
+        ("META-INF/jarjar/metadata.json", jarjar),
+        ("META-INF/jars/lib.jar", &lib),
+        ("META-INF/jars/qlib.jar", &qlib),
+        ("META-INF/jarjar/flib.jar", &forge_lib),
+        ("META-INF/jarjar/plain.jar", b"not a zip"),
+    ]);
+    let host = metadata::read_bytes(&data).unwrap();
+    type Seen<'a> = (
+        &'a str,
+        Option<&'a str>,
+        Option<&'a str>,
+        Option<&'a str>,
+        bool,
+    );
+    let nested: Vec<Seen> = host
+        .nested
+        .iter()
+        .map(|n| {
+            (
+                n.path.as_str(),
+                n.metadata.primary().map(|m| m.id.as_str()),
+                n.artifact.as_deref(),
+                n.artifact_version.as_deref(),
+                n.error.is_some(),
+            )
+        })
+        .collect();
+    // Listed twice, read once; listed but absent or unreadable, kept with an error
+    assert_eq!(
+        nested,
+        [
+            ("META-INF/jars/lib.jar", Some("lib"), None, None, false),
+            ("META-INF/jars/gone.jar", None, None, None, true),
+            ("META-INF/jars/qlib.jar", Some("qlib"), None, None, false),
+            (
+                "META-INF/jarjar/flib.jar",
+                Some("flib"),
+                Some("org.example:flib"),
+                Some("3.0"),
+                false
+            ),
+            (
+                "META-INF/jarjar/plain.jar",
+                None,
+                Some("org.example:plain"),
+                None,
+                true
+            ),
+        ]
+    );
+    assert_eq!(host.nested[0].file_name(), "lib.jar");
+}
+
+#[test]
+fn bundled_jars_are_followed_eight_levels_deep() {
+    let mut data = fabric_jar("level10", "1.0.0", "");
+    for level in (0..10).rev() {
+        data = host_jar(&format!("level{level}"), "1.0.0", &[("inner.jar", &data)]);
+    }
+    let root = metadata::read_bytes(&data).unwrap();
+    assert_eq!(root.primary().unwrap().id, "level0");
+    let ids: Vec<&str> = root
+        .bundled()
+        .iter()
+        .map(|n| n.metadata.primary().unwrap().id.as_str())
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            "level1", "level2", "level3", "level4", "level5", "level6", "level7", "level8"
+        ]
+    );
+    // The deepest jar read does not list what it bundles
+    assert!(root.bundled()[7].metadata.nested.is_empty());
+}
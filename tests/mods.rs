@@ -0,0 +1,126 @@
+mod common;
+
+use std::fs;
+
+use minecraft_mod_replacer::Error;
+use minecraft_mod_replacer::mods::{self, enabled_name, is_disabled};
+
+use common::{fabric_jar, scratch, write};
+
+#[test]
+fn set_enabled_renames_both_ways() {
+    let dir = scratch("mods-toggle");
+    let jar = dir.join("sodium.jar");
+    write(&jar, b"jar");
+
+    let disabled = mods::set_enabled(&jar, false).unwrap();
+    assert_eq!(disabled, dir.join("sodium.jar.disabled"));
+    assert!(!jar.exists() && disabled.is_file());
+    assert!(is_disabled(&disabled));
+    assert_eq!(enabled_name(&disabled), "sodium.jar");
+    // Already disabled: nothing happens
+    assert_eq!(mods::set_enabled(&disabled, false).unwrap(), disabled);
+
+    assert_eq!(mods::set_enabled(&disabled, true).unwrap(), jar);
+    assert_eq!(fs::read(&jar).unwrap(), b"jar");
+    assert!(!disabled.exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn set_enabled_refuses_to_overwrite() {
+    let dir = scratch("mods-toggle-taken");
+    let jar = dir.join("sodium.jar");
+    let disabled = dir.join("sodium.jar.disabled");
+    write(&jar, b"enabled");
+    write(&disabled, b"disabled");
+
+    assert!(matches!(
+        mods::set_enabled(&disabled, true),
+        Err(Error::AlreadyExists(path)) if path == jar
+    ));
+    assert!(matches!(
+        mods::set_enabled(&jar, false),
+        Err(Error::AlreadyExists(path)) if path == disabled
+    ));
+    assert_eq!(fs::read(&jar).unwrap(), b"enabled");
+    assert_eq!(fs::read(&disabled).unwrap(), b"disabled");
+
+    let text = dir.join("notes.txt");
+    write(&text, b"");
+    assert!(matches!(
+        mods::set_enabled(&text, false),
+        Err(Error::NotAJar(_))
+    ));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn resolve_target_accepts_short_and_disabled_names() {
+    let dir = scratch("mods-resolve");
+    write(&dir.join("sodium-0.5.8.jar"), b"");
+    write(&dir.join("lithium-0.11.2.jar.disabled"), b"");
+    write(&dir.join("readme.txt"), b"");
+
+    for (name, file) in [
+        ("sodium-0.5.8.jar", "sodium-0.5.8.jar"),
+        ("sodium-0.5.8", "sodium-0.5.8.jar"),
+        ("lithium-0.11.2.jar.disabled", "lithium-0.11.2.jar.disabled"),
+        ("lithium-0.11.2.jar", "lithium-0.11.2.jar.disabled"),
+        ("lithium-0.11.2", "lithium-0.11.2.jar.disabled"),
+    ] {
+        assert_eq!(
+            mods::resolve_target(&dir, name).unwrap(),
+            dir.join(file),
+            "{name}"
+        );
+    }
+    for name in ["readme.txt", "readme", "iris"] {
+        assert!(
+            matches!(
+                mods::resolve_target(&dir, name),
+                Err(Error::TargetNotFound { .. })
+            ),
+            "{name}"
+        );
+    }
+    assert!(matches!(
+        mods::resolve_target(&dir.join("missing"), "sodium"),
+        Err(Error::NotADirectory(_))
+    ));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn resolve_target_or_id_falls_back_to_mod_ids() {
+    let dir = scratch("mods-resolve-id");
+    write(&dir.join("a.jar"), &fabric_jar("sodium", "0.5.8", ""));
+    write(
+        &dir.join("b.jar.disabled"),
+        &fabric_jar("iris", "1.6.0", ""),
+    );
+    write(&dir.join("c.jar"), &fabric_jar("lithium", "0.11.2", ""));
+    write(&dir.join("d.jar"), &fabric_jar("lithium", "0.11.3", ""));
+
+    assert_eq!(
+        mods::resolve_target_or_id(&dir, "sodium").unwrap(),
+        dir.join("a.jar")
+    );
+    assert_eq!(
+        mods::resolve_target_or_id(&dir, "iris").unwrap(),
+        dir.join("b.jar.disabled")
+    );
+    assert_eq!(
+        mods::resolve_target_or_id(&dir, "c").unwrap(),
+        dir.join("c.jar")
+    );
+    assert!(matches!(
+        mods::resolve_target_or_id(&dir, "lithium"),
+        Err(Error::AmbiguousTarget { matches, .. }) if matches.len() == 2
+    ));
+    assert!(matches!(
+        mods::resolve_target_or_id(&dir, "phosphor"),
+        Err(Error::TargetNotFound { .. })
+    ));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use minecraft_mod_replacer::Error;
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::cache::FileCache;
+use minecraft_mod_replacer::hashes::{HashAlgo, sha1_hex, sha512_hex};
+use minecraft_mod_replacer::mrpack::{
+    self, ExportOptions, FileChange, Hashes, INDEX_FILE, Index, IndexFile, Pack, Side,
+};
+use minecraft_mod_replacer::zip::{self, Archive, CompressionMethod, ZipWriter};
+
+use common::{fabric_jar, jar, scratch, write};
+
+#[test]
+fn written_archives_parse_back() {
+    let big = vec![b'x'; 10_000];
+    let files: &[(&str, &[u8])] = &[
+        ("empty.txt", b""),
+        ("tiny.bin", &[1, 2, 3]),
+        ("big.txt", &big),
+        ("assets/ünïcode/ファイル.json", b"{}"),
+    ];
+    let data = jar(files);
+    assert!(zip::verify(&data).unwrap().is_ok());
+
+    let archive = Archive::parse(&data).unwrap();
+    assert_eq!(archive.entries.len(), files.len());
+    assert_eq!(archive.trailing_len(), 0);
+    for (entry, (name, content)) in archive.entries.iter().zip(files) {
+        assert_eq!(entry.name, *name);
+        assert_eq!(archive.read(entry).unwrap(), *content, "{name}");
+    }
+    assert_eq!(
+        archive.find("big.txt").unwrap().method,
+        CompressionMethod::Deflated
+    );
+    assert_eq!(
+        archive.find("tiny.bin").unwrap().method,
+        CompressionMethod::Stored
+    );
+    // The same input always gives the same bytes
+    assert_eq!(jar(files), data);
+    assert!(
+        Archive::parse(&ZipWriter::new().finish().unwrap())
+            .unwrap()
+            .entries
+            .is_empty()
+    );
+}
+
+fn export_options() -> ExportOptions {
+    ExportOptions {
+        name: "Demo Pack".to_string(),
+        version_id: "1.0.0".to_string(),
+        summary: None,
+        dependencies: BTreeMap::from([
+            ("minecraft".to_string(), "1.20.1".to_string()),
+            ("fabric-loader".to_string(), "0.15.11".to_string()),
+        ]),
+    }
+}
+
+#[test]
+fn exported_packs_install_the_same_jars() {
+    let dir = scratch("mrpack-roundtrip");
+    let mods = dir.join("instance/mods");
+    let linked = fabric_jar("linked", "1.0.0", r#""environment": "client""#);
+    let local = fabric_jar("local", "2.0.0", "");
+    write(&mods.join("linked-1.0.0.jar"), &linked);
+    write(&mods.join("local-2.0.0.jar"), &local);
+    write(
+        &mods.join("off-1.0.0.jar.disabled"),
+        &fabric_jar("off", "1.0.0", ""),
+    );
+
+    let known = Index {
+        format_version: 1,
+        game: "minecraft".to_string(),
+        version_id: "0.9".to_string(),
+        name: "Old".to_string(),
+        summary: None,
+        files: vec![IndexFile {
+            path: "mods/linked.jar".to_string(),
+            hashes: Hashes {
+                sha1: sha1_hex(&linked),
+                sha512: sha512_hex(&linked),
+            },
+            env: None,
+            downloads: vec!["https://cdn.example/linked-1.0.0.jar".to_string()],
+            file_size: linked.len() as u64,
+        }],
+        dependencies: BTreeMap::new(),
+    };
+    let (data, summary) = mrpack::export(&mods, &export_options(), Some(&known)).unwrap();
+    assert_eq!(summary.indexed, ["linked-1.0.0.jar"]);
+    assert_eq!(summary.embedded, ["local-2.0.0.jar"]);
+
+    let pack_path = dir.join("demo.mrpack");
+    write(&pack_path, &data);
+    let pack = Pack::read(&pack_path).unwrap();
+    assert_eq!(pack.index.name, "Demo Pack");
+    assert_eq!(pack.index.dependencies, export_options().dependencies);
+    let [file] = pack.index.files.as_slice() else {
+        panic!("{:?}", pack.index.files)
+    };
+    assert_eq!(file.path, "mods/linked-1.0.0.jar");
+    assert_eq!(file.downloads, known.files[0].downloads);
+    // The environment comes from the jar's own metadata
+    assert!(file.wanted_on(Side::Client) && !file.wanted_on(Side::Server));
+    let [embedded] = pack.overrides.as_slice() else {
+        panic!("{:?}", pack.overrides)
+    };
+    assert_eq!(embedded.path, "mods/local-2.0.0.jar");
+    assert_eq!(embedded.data, local);
+
+    // Install into a fresh instance, taking the linked jar from a cache
+    write(&dir.join("cache/anything.jar"), &linked);
+    let cache = FileCache::open(&dir.join("cache"), &[HashAlgo::Sha512, HashAlgo::Sha1]).unwrap();
+    let game = dir.join("fresh");
+    let plan = pack.plan_install(&game, &cache, Side::Client).unwrap();
+    assert!(plan.missing.is_empty());
+    assert!(plan.files.iter().all(|f| f.change == FileChange::New));
+    let mut backup = BackupStore::for_mods_dir(&plan.mods_dir())
+        .begin("mrpack import")
+        .unwrap();
+    plan.apply(&mut backup).unwrap();
+    assert!(backup.is_empty());
+    assert_eq!(
+        fs::read(game.join("mods/linked-1.0.0.jar")).unwrap(),
+        linked
+    );
+    assert_eq!(fs::read(game.join("mods/local-2.0.0.jar")).unwrap(), local);
+    let saved = mrpack::saved_index(&plan.mods_dir()).unwrap();
+    assert_eq!(saved.files.len(), 1);
+
+    // A server install leaves the client-only jar out
+    let server = pack
+        .plan_install(&dir.join("server"), &cache, Side::Server)
+        .unwrap();
+    let paths: Vec<_> = server.files.iter().map(|f| f.path.clone()).collect();
+    assert_eq!(paths, [dir.join("server/mods/local-2.0.0.jar")]);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn exports_need_a_minecraft_version() {
+    let dir = scratch("mrpack-no-minecraft");
+    let options = ExportOptions {
+        dependencies: BTreeMap::new(),
+        ..export_options()
+    };
+    assert!(matches!(
+        mrpack::export(&dir, &options, None),
+        Err(Error::InvalidPack(_))
+    ));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn failed_installs_remove_new_files_and_restore_the_rest() {
+    let dir = scratch("mrpack-rollback");
+    let game = dir.join("game");
+    write(&game.join("options.txt"), b"old options");
+    let index = br#"{"formatVersion": 1, "game": "minecraft", "versionId": "1", "name": "Broken", "files": []}"#;
+    // `config` is written as a file, so nothing can be written inside it
+    let pack_data = jar(&[
+        (INDEX_FILE, index),
+        ("overrides/options.txt", b"new options"),
+        ("overrides/mods/new.jar", b"new jar"),
+        ("overrides/config", b"a file"),
+        ("overrides/config/demo.toml", b"a = 1"),
+    ]);
+    let pack_path = dir.join("broken.mrpack");
+    write(&pack_path, &pack_data);
+    let pack = Pack::read(&pack_path).unwrap();
+
+    fs::create_dir_all(dir.join("cache")).unwrap();
+    let cache = FileCache::open(&dir.join("cache"), &[HashAlgo::Sha512]).unwrap();
+    let plan = pack.plan_install(&game, &cache, Side::Client).unwrap();
+    let mut backup = BackupStore::for_mods_dir(&plan.mods_dir())
+        .begin("mrpack import")
+        .unwrap();
+    let err = plan.apply(&mut backup).unwrap_err();
+    assert!(
+        matches!(&err, Error::RolledBack { target, .. } if target == "demo.toml"),
+        "{err}"
+    );
+
+    assert_eq!(fs::read(game.join("options.txt")).unwrap(), b"old options");
+    assert!(!game.join("mods/new.jar").exists());
+    assert!(!game.join("config").exists());
+    assert!(mrpack::saved_index(&plan.mods_dir()).is_none());
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,247 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use minecraft_mod_replacer::Error;
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::cache::FileCache;
+use minecraft_mod_replacer::hashes::{sha1_hex, sha256_hex};
+use minecraft_mod_replacer::mrpack::Side;
+use minecraft_mod_replacer::packwiz::{FileStatus, Pack, status};
+
+use common::{fabric_jar, scratch, write};
+
+struct Fixture {
+    dir: PathBuf,
+    pack_toml: PathBuf,
+    game: PathBuf,
+    sodium: Vec<u8>,
+    iris: Vec<u8>,
+}
+
+fn metafile(name: &str, filename: &str, side: &str, format: &str, hash: &str) -> String {
+    format!(
+        "name = \"{name}\"\nfilename = \"{filename}\"\nside = \"{side}\"\n\n[download]\nurl = \"https://cdn.example/{filename}\"\nhash-format = \"{format}\"\nhash = \"{hash}\"\n"
+    )
+}
+
+/// A pack with a plain config file, a preserved one, a jar for both sides,
+/// a client-only jar and a jar hashed with md5, and a game folder that
+/// partly matches it.
+fn fixture(name: &str) -> Fixture {
+    let dir = scratch(name);
+    let pack = dir.join("pack");
+    let sodium = fabric_jar("sodium", "0.5.8", "");
+    let iris = fabric_jar("iris", "1.6.0", "");
+
+    let mut files: Vec<(String, Vec<u8>, bool)> = vec![
+        ("config/demo.toml".into(), b"a = 1\n".to_vec(), false),
+        ("config/keep.txt".into(), b"defaults".to_vec(), false),
+        (
+            "mods/sodium.pw.toml".into(),
+            metafile("Sodium", "sodium.jar", "both", "sha1", &sha1_hex(&sodium)).into_bytes(),
+            true,
+        ),
+        (
+            "mods/iris.pw.toml".into(),
+            metafile("Iris", "iris.jar", "client", "sha1", &sha1_hex(&iris)).into_bytes(),
+            true,
+        ),
+        (
+            "mods/old.pw.toml".into(),
+            metafile(
+                "Old",
+                "old.jar",
+                "both",
+                "md5",
+                "0123456789abcdef0123456789abcdef",
+            )
+            .into_bytes(),
+            true,
+        ),
+    ];
+    let mut index = String::from("hash-format = \"sha256\"\n");
+    for (path, data, meta) in &mut files {
+        write(&pack.join(&*path), data);
+        index.push_str(&format!(
+            "\n[[files]]\nfile = \"{path}\"\nhash = \"{}\"\n",
+            sha256_hex(data)
+        ));
+        if *meta {
+            index.push_str("metafile = true\n");
+        }
+        if path.ends_with("keep.txt") {
+            index.push_str("preserve = true\n");
+        }
+    }
+    write(&pack.join("index.toml"), index.as_bytes());
+    let pack_toml = pack.join("pack.toml");
+    write(
+        &pack_toml,
+        format!(
+            "name = \"Demo\"\npack-format = \"packwiz:1.1.0\"\n\n[index]\nfile = \"index.toml\"\nhash-format = \"sha256\"\nhash = \"{}\"\n\n[versions]\nminecraft = \"1.20.1\"\nfabric = \"0.15.11\"\n",
+            sha256_hex(index.as_bytes())
+        )
+        .as_bytes(),
+    );
+
+    let game = dir.join("game");
+    write(&game.join("config/demo.toml"), b"a = 2\n");
+    write(&game.join("config/keep.txt"), b"my settings");
+    write(&game.join("mods/sodium.jar"), &sodium);
+    write(&game.join("mods/old.jar"), b"whatever");
+    write(&game.join("mods/extra.jar"), b"extra");
+    write(&game.join("mods/off.jar.disabled"), b"off");
+    Fixture {
+        dir,
+        pack_toml,
+        game,
+        sodium,
+        iris,
+    }
+}
+
+fn statuses(pack: &Pack, game: &Path, side: Side) -> Vec<(String, FileStatus)> {
+    status(pack, game, side)
+        .unwrap()
+        .files
+        .into_iter()
+        .map(|(file, status)| (file.path, status))
+        .collect()
+}
+
+#[test]
+fn status_checks_hashes_sides_and_extras() {
+    let f = fixture("packwiz-status");
+    let pack = Pack::load(&f.pack_toml).unwrap();
+    assert_eq!(pack.pack.name, "Demo");
+    assert_eq!(pack.files.len(), 5);
+
+    let client = statuses(&pack, &f.game, Side::Client);
+    assert_eq!(
+        client,
+        [
+            ("config/demo.toml".to_string(), FileStatus::Modified),
+            ("config/keep.txt".to_string(), FileStatus::Preserved),
+            ("mods/sodium.jar".to_string(), FileStatus::Ok),
+            ("mods/iris.jar".to_string(), FileStatus::Missing),
+            ("mods/old.jar".to_string(), FileStatus::Unverifiable),
+        ]
+    );
+    let server = statuses(&pack, &f.game, Side::Server);
+    assert!(server.iter().all(|(path, _)| path != "mods/iris.jar"));
+
+    let report = status(&pack, &f.game, Side::Client).unwrap();
+    assert_eq!(report.extra, [f.game.join("mods/extra.jar")]);
+    assert!(!report.is_clean());
+    fs::remove_dir_all(f.dir).unwrap();
+}
+
+#[test]
+fn tampered_metafiles_are_refused() {
+    let f = fixture("packwiz-tampered");
+    let meta = f.pack_toml.with_file_name("mods/sodium.pw.toml");
+    let text = fs::read_to_string(&meta).unwrap();
+    fs::write(&meta, text.replace("Sodium", "Sodium!")).unwrap();
+    let err = Pack::load(&f.pack_toml).unwrap_err();
+    assert!(
+        matches!(&err, Error::InvalidPack(reason) if reason.contains("sodium.pw.toml")),
+        "{err}"
+    );
+    fs::remove_dir_all(f.dir).unwrap();
+}
+
+#[test]
+fn sync_writes_missing_files_and_moves_extras_out() {
+    let f = fixture("packwiz-sync");
+    let pack = Pack::load(&f.pack_toml).unwrap();
+    write(&f.dir.join("cache/iris-download.jar"), &f.iris);
+    let cache = FileCache::open(&f.dir.join("cache"), &pack.hash_formats()).unwrap();
+
+    let plan = status(&pack, &f.game, Side::Client)
+        .unwrap()
+        .plan_sync(&cache)
+        .unwrap();
+    let writes: Vec<(PathBuf, bool)> = plan
+        .writes
+        .iter()
+        .map(|w| (w.path.clone(), w.replaces))
+        .collect();
+    assert_eq!(
+        writes,
+        [
+            (f.game.join("config/demo.toml"), true),
+            (f.game.join("mods/iris.jar"), false),
+        ]
+    );
+    assert_eq!(plan.removals, [f.game.join("mods/extra.jar")]);
+    assert!(plan.unavailable.is_empty());
+
+    let store = BackupStore::for_mods_dir(&f.game.join("mods"));
+    let mut backup = store.begin("packwiz sync").unwrap();
+    plan.apply(&mut backup).unwrap();
+    assert_eq!(
+        fs::read(f.game.join("config/demo.toml")).unwrap(),
+        b"a = 1\n"
+    );
+    assert_eq!(
+        fs::read(f.game.join("config/keep.txt")).unwrap(),
+        b"my settings"
+    );
+    assert_eq!(fs::read(f.game.join("mods/iris.jar")).unwrap(), f.iris);
+    assert_eq!(fs::read(f.game.join("mods/sodium.jar")).unwrap(), f.sodium);
+    assert!(!f.game.join("mods/extra.jar").exists());
+    assert!(f.game.join("mods/off.jar.disabled").exists());
+    assert_eq!(backup.manifest.entries.len(), 2);
+    assert!(status(&pack, &f.game, Side::Client).unwrap().is_clean());
+    fs::remove_dir_all(f.dir).unwrap();
+}
+
+#[test]
+fn failed_syncs_remove_new_files_and_restore_the_rest() {
+    let f = fixture("packwiz-rollback");
+    let pack = Pack::load(&f.pack_toml).unwrap();
+    write(&f.dir.join("cache/iris.jar"), &f.iris);
+    let cache = FileCache::open(&f.dir.join("cache"), &pack.hash_formats()).unwrap();
+    let plan = status(&pack, &f.game, Side::Client)
+        .unwrap()
+        .plan_sync(&cache)
+        .unwrap();
+    // The extra jar goes away before it can be moved out
+    fs::remove_file(f.game.join("mods/extra.jar")).unwrap();
+
+    let store = BackupStore::for_mods_dir(&f.game.join("mods"));
+    let mut backup = store.begin("packwiz sync").unwrap();
+    let err = plan.apply(&mut backup).unwrap_err();
+    assert!(
+        matches!(&err, Error::RolledBack { target, .. } if target == "extra.jar"),
+        "{err}"
+    );
+    assert_eq!(
+        fs::read(f.game.join("config/demo.toml")).unwrap(),
+        b"a = 2\n"
+    );
+    assert!(!f.game.join("mods/iris.jar").exists());
+    fs::remove_dir_all(f.dir).unwrap();
+}
+
+#[test]
+fn md5_files_are_never_written() {
+    let f = fixture("packwiz-md5");
+    fs::remove_file(f.game.join("mods/old.jar")).unwrap();
+    let pack = Pack::load(&f.pack_toml).unwrap();
+    assert!(pack.files.iter().any(|file| file.hash_format.is_none()));
+    write(&f.dir.join("cache/old.jar"), b"whatever");
+    let cache = FileCache::open(&f.dir.join("cache"), &pack.hash_formats()).unwrap();
+    let report = status(&pack, &f.game, Side::Server).unwrap();
+    assert!(
+        report
+            .files
+            .iter()
+            .any(|(file, s)| file.path == "mods/old.jar" && *s == FileStatus::Missing)
+    );
+    let plan = report.plan_sync(&cache).unwrap();
+    assert_eq!(plan.unavailable, ["mods/old.jar"]);
+    fs::remove_dir_all(f.dir).unwrap();
+}
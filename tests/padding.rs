@@ -0,0 +1,195 @@
+use std::fs;
+use std::path::PathBuf;
+
+use minecraft_mod_replacer::zip::{self, Archive, MAX_COMMENT_LEN, ZipError, ZipWriter};
+use minecraft_mod_replacer::{PaddingStrategy, ReplaceOptions, replace_mod};
+
+const FILES: &[(&str, &[u8])] = &[
+    (
+        "fabric.mod.json",
+        b"{\"schemaVersion\": 1, \"id\": \"demo\", \"version\": \"1.0.0\", \
+          \"description\": \"A demo mod, a demo mod, a demo mod, a demo mod.\"}",
+    ),
+    (
+        "assets/demo/lang/en_us.json",
+        b"{\"item.demo.thing\": \"Thing\"}",
+    ),
+    ("demo/Main.class", &[0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 61]),
+];
+
+fn jar(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = ZipWriter::new();
+    for (name, data) in files {
+        writer.add_file(name, data).unwrap();
+    }
+    writer.finish().unwrap()
+}
+
+/// Asserts that `data` passes a full verification and that every entry of
+/// `original` is still there with the same bytes.
+fn assert_same_entries(original: &[u8], data: &[u8], label: &str) {
+    let report = zip::verify(data).unwrap();
+    assert!(report.is_ok(), "{label}: {:?}", report.problems);
+    let before = Archive::parse(original).unwrap();
+    let after = Archive::parse(data).unwrap();
+    assert_eq!(before.entries.len(), after.entries.len(), "{label}");
+    for entry in &before.entries {
+        assert_eq!(
+            after.read_file(&entry.name).unwrap().unwrap(),
+            before.read(entry).unwrap(),
+            "{label}: {}",
+            entry.name
+        );
+        assert_eq!(
+            after.raw_data(after.find(&entry.name).unwrap()).unwrap(),
+            before.raw_data(entry).unwrap(),
+            "{label}: {}",
+            entry.name
+        );
+    }
+}
+
+#[test]
+fn comment_padding_keeps_every_entry() {
+    let original = jar(FILES);
+    for padding in [1, 2, 100, 4096, MAX_COMMENT_LEN] {
+        let padded = zip::pad_zip_file(original.clone(), padding).unwrap();
+        assert_eq!(padded.len(), original.len() + padding, "{padding}");
+        assert_eq!(Archive::parse(&padded).unwrap().trailing_len(), padding);
+        assert_same_entries(&original, &padded, &format!("comment {padding}"));
+    }
+}
+
+#[test]
+fn comment_padding_folds_in_trailing_bytes() {
+    let mut original = jar(FILES);
+    original.extend([0u8; 10]);
+    let padded = zip::pad_zip_file(original.clone(), 50).unwrap();
+    assert_eq!(padded.len(), original.len() + 50);
+    let archive = Archive::parse(&padded).unwrap();
+    assert_eq!(usize::from(archive.eocd.comment_len), 60);
+    assert_eq!(archive.trailing_len(), 60);
+    assert_same_entries(&original, &padded, "trailing bytes");
+}
+
+#[test]
+fn comment_padding_respects_the_comment_limit() {
+    let original = jar(FILES);
+    assert!(matches!(
+        zip::pad_zip_file(original.clone(), MAX_COMMENT_LEN + 1),
+        Err(ZipError::PaddingTooLarge(_))
+    ));
+    let half = zip::pad_zip_file(original, 40_000).unwrap();
+    assert!(matches!(
+        zip::pad_zip_file(half, 40_000),
+        Err(ZipError::CommentOverflow {
+            current: 40_000,
+            padding: 40_000
+        })
+    ));
+}
+
+#[test]
+fn appended_padding_keeps_every_entry() {
+    let original = jar(FILES);
+    // Readers only look for the EOCD record within a comment's reach of the end
+    for padding in [1, 100, 60_000] {
+        let padded = zip::append_padding(original.clone(), padding).unwrap();
+        assert_eq!(padded.len(), original.len() + padding, "{padding}");
+        assert!(padded[original.len()..].iter().all(|&b| b == 0));
+        assert_same_entries(&original, &padded, &format!("append {padding}"));
+    }
+}
+
+#[test]
+fn verify_catches_damaged_entries() {
+    let original = jar(FILES);
+    let archive = Archive::parse(&original).unwrap();
+    let deflated = archive.find("fabric.mod.json").unwrap();
+    let stored = archive.find("demo/Main.class").unwrap();
+    assert_eq!(deflated.method, zip::CompressionMethod::Deflated);
+    assert_eq!(stored.method, zip::CompressionMethod::Stored);
+
+    type Expect = fn(&ZipError) -> bool;
+    let cases: &[(&str, usize, Expect)] = &[
+        ("demo/Main.class", stored.data_offset as usize + 2, |err| {
+            matches!(err, ZipError::CrcMismatch { .. })
+        }),
+        (
+            "fabric.mod.json",
+            deflated.data_offset as usize + 5,
+            |err| {
+                matches!(
+                    err,
+                    ZipError::CrcMismatch { .. }
+                        | ZipError::Inflate { .. }
+                        | ZipError::SizeMismatch { .. }
+                )
+            },
+        ),
+        (
+            "demo/Main.class",
+            stored.local_header_offset as usize + 14,
+            |err| matches!(err, ZipError::LocalHeaderMismatch { field: "crc32", .. }),
+        ),
+        (
+            "fabric.mod.json",
+            deflated.local_header_offset as usize + 8,
+            |err| {
+                matches!(
+                    err,
+                    ZipError::LocalHeaderMismatch {
+                        field: "compression method",
+                        ..
+                    }
+                )
+            },
+        ),
+    ];
+    for (name, offset, expect) in cases {
+        let mut data = original.clone();
+        data[*offset] ^= 0x55;
+        let report = zip::verify(&data).unwrap();
+        assert_eq!(report.entries, FILES.len());
+        assert_eq!(report.problems.len(), 1, "{name} at {offset}");
+        assert_eq!(report.problems[0].entry, *name);
+        assert!(
+            expect(&report.problems[0].error),
+            "{name} at {offset}: {:?}",
+            report.problems[0].error
+        );
+    }
+}
+
+/// A fresh scratch folder for one test.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mod-replacer-test-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn replacing_keeps_the_original_size() {
+    let dir = scratch("replace-size");
+    let mods = dir.join("mods");
+    fs::create_dir_all(&mods).unwrap();
+    let target = mods.join("demo-1.0.0.jar");
+    let replacement = dir.join("demo-1.0.1.jar");
+
+    let big_file = vec![7u8; 3000];
+    let mut original_files = FILES.to_vec();
+    original_files.push(("demo/big.bin", &big_file));
+    fs::write(&target, jar(&original_files)).unwrap();
+    let new = jar(FILES);
+    fs::write(&replacement, &new).unwrap();
+    let original_size = fs::metadata(&target).unwrap().len();
+
+    let outcome = replace_mod(&target, &replacement, &ReplaceOptions::default()).unwrap();
+    assert_eq!(outcome.plan.strategy, PaddingStrategy::ZipComment);
+    let written = fs::read(&target).unwrap();
+    assert_eq!(written.len() as u64, original_size);
+    assert_same_entries(&new, &written, "replace");
+    assert!(outcome.backup.is_some());
+    fs::remove_dir_all(dir).unwrap();
+}
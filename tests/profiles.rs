@@ -0,0 +1,179 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{fabric_jar, scratch, write};
+use minecraft_mod_replacer::Error;
+use minecraft_mod_replacer::profiles::{Conflict, ProfilesFile, SwitchAction, SwitchPlan};
+
+fn load(dir: &Path, mods: &str) -> ProfilesFile {
+    let path = dir.join("profiles.toml");
+    write(
+        &path,
+        format!("store = \"store\"\n\n[profiles.test]\nmods = {mods}\n").as_bytes(),
+    );
+    ProfilesFile::load(&path).unwrap()
+}
+
+#[test]
+fn plans_a_switch_by_mod_id_and_file_name() {
+    let dir = scratch("profiles-plan");
+    let (mods, store) = (dir.join("mods"), dir.join("store"));
+    write(
+        &mods.join("sodium-0.5.jar"),
+        &fabric_jar("sodium", "0.5.0", ""),
+    );
+    write(
+        &mods.join("lithium.jar.disabled"),
+        &fabric_jar("lithium", "0.11.0", ""),
+    );
+    write(
+        &mods.join("iris-old.jar.disabled"),
+        &fabric_jar("iris", "1.0.0", ""),
+    );
+    write(
+        &mods.join("iris-new.jar.disabled"),
+        &fabric_jar("iris", "1.2.0", ""),
+    );
+    write(&mods.join("extra.jar"), &fabric_jar("extra", "1.0.0", ""));
+    write(&mods.join("taken.jar"), &fabric_jar("other", "1.0.0", ""));
+    write(
+        &store.join("ferritecore-1.0.jar"),
+        &fabric_jar("ferritecore", "1.0.0", ""),
+    );
+    write(
+        &store.join("ferritecore-2.0.jar"),
+        &fabric_jar("ferritecore", "2.0.0", ""),
+    );
+    write(
+        &store.join("taken.jar"),
+        &fabric_jar("storemod", "1.0.0", ""),
+    );
+    write(
+        &store.join("fapi.jar"),
+        &fabric_jar("fabric-api", "0.90.0", ""),
+    );
+
+    let file = load(
+        &dir,
+        r#"["sodium", "lithium.jar", "iris", "ferritecore", "storemod", "fapi", "fapi.jar", "missing"]"#,
+    );
+    let plan = file.plan_switch("test", &mods, None).unwrap();
+
+    let mut expected = vec![
+        SwitchAction::Enable(mods.join("lithium.jar.disabled")),
+        // The newest of the disabled copies
+        SwitchAction::Enable(mods.join("iris-new.jar.disabled")),
+        SwitchAction::Install {
+            from: store.join("ferritecore-2.0.jar"),
+            to: mods.join("ferritecore-2.0.jar"),
+        },
+        // Named twice, installed once
+        SwitchAction::Install {
+            from: store.join("fapi.jar"),
+            to: mods.join("fapi.jar"),
+        },
+        SwitchAction::Disable(mods.join("extra.jar")),
+        SwitchAction::Disable(mods.join("taken.jar")),
+    ];
+    let mut actions = plan.actions.clone();
+    let key = |a: &SwitchAction| format!("{a:?}");
+    actions.sort_by_key(key);
+    expected.sort_by_key(key);
+    assert_eq!(actions, expected);
+    assert_eq!(plan.unresolved, ["missing"]);
+    assert_eq!(
+        plan.conflicts,
+        [Conflict {
+            entry: "storemod".to_string(),
+            from: store.join("taken.jar"),
+            to: mods.join("taken.jar"),
+        }]
+    );
+    assert!(!plan.is_current());
+
+    // A plan with conflicts changes nothing
+    assert!(
+        matches!(plan.apply(), Err(Error::AlreadyExists(path)) if path == mods.join("taken.jar"))
+    );
+    assert!(mods.join("extra.jar").exists());
+    assert!(!mods.join("fapi.jar").exists());
+
+    let plan = SwitchPlan {
+        conflicts: Vec::new(),
+        ..plan
+    };
+    plan.apply().unwrap();
+    for name in [
+        "sodium-0.5.jar",
+        "lithium.jar",
+        "iris-new.jar",
+        "iris-old.jar.disabled",
+        "ferritecore-2.0.jar",
+        "fapi.jar",
+        "extra.jar.disabled",
+        "taken.jar.disabled",
+    ] {
+        assert!(mods.join(name).exists(), "{name}");
+    }
+    assert_eq!(
+        fs::read(mods.join("fapi.jar")).unwrap(),
+        fs::read(store.join("fapi.jar")).unwrap()
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn a_folder_matching_the_profile_is_current() {
+    let dir = scratch("profiles-current");
+    let mods = dir.join("mods");
+    write(&mods.join("sodium.jar"), &fabric_jar("sodium", "0.5.0", ""));
+    write(
+        &mods.join("extra.jar.disabled"),
+        &fabric_jar("extra", "1.0.0", ""),
+    );
+    fs::create_dir(dir.join("store")).unwrap();
+    let file = load(&dir, r#"["sodium"]"#);
+    assert!(file.plan_switch("test", &mods, None).unwrap().is_current());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn failed_switch_reverses_renames_and_installs() {
+    let dir = scratch("profiles-rollback");
+    let (mods, store) = (dir.join("mods"), dir.join("store"));
+    write(&mods.join("a.jar"), &fabric_jar("a", "1.0.0", ""));
+    write(&mods.join("b.jar.disabled"), &fabric_jar("b", "1.0.0", ""));
+    write(&store.join("c.jar"), &fabric_jar("c", "1.0.0", ""));
+
+    let plan = SwitchPlan {
+        profile: "test".to_string(),
+        actions: vec![
+            SwitchAction::Disable(mods.join("a.jar")),
+            SwitchAction::Enable(mods.join("b.jar.disabled")),
+            SwitchAction::Install {
+                from: store.join("c.jar"),
+                to: mods.join("c.jar"),
+            },
+            // Gone from the store since the plan was made
+            SwitchAction::Install {
+                from: store.join("gone.jar"),
+                to: mods.join("gone.jar"),
+            },
+        ],
+        unresolved: Vec::new(),
+        conflicts: Vec::new(),
+    };
+    match plan.apply() {
+        Err(Error::RolledBack { target, .. }) => assert_eq!(target, "gone.jar"),
+        other => panic!("{other:?}"),
+    }
+    let mut names: Vec<String> = fs::read_dir(&mods)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["a.jar", "b.jar.disabled"]);
+    fs::remove_dir_all(dir).unwrap();
+}
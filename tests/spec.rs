@@ -0,0 +1,184 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{fabric_jar, scratch, write};
+use minecraft_mod_replacer::Error;
+use minecraft_mod_replacer::backup::BackupStore;
+use minecraft_mod_replacer::cache::FileCache;
+use minecraft_mod_replacer::spec::{PackSpec, Reconcile};
+
+const SPEC: &str = r#"
+[[mod]]
+id = "a"
+
+[[mod]]
+id = "b"
+
+[[mod]]
+id = "f"
+file = "extra.jar"
+
+[[mod]]
+id = "c"
+
+[[mod]]
+id = "d"
+file = "shared.jar"
+
+[[mod]]
+id = "e"
+file = "shared.jar"
+
+[[mod]]
+id = "b2"
+
+[[mod]]
+id = "missing"
+"#;
+
+/// A folder with `a` satisfied, `b` disabled and two extras, and a cache
+/// holding `c`, `d`, `e`, `f` and a `b2` whose file is named `b.jar`.
+fn setup(dir: &Path) -> (Reconcile, FileCache) {
+    let (mods, cache) = (dir.join("mods"), dir.join("cache"));
+    write(&mods.join("a.jar"), &fabric_jar("a", "1.0.0", ""));
+    write(&mods.join("b.jar.disabled"), &fabric_jar("b", "1.0.0", ""));
+    write(&mods.join("extra.jar"), &fabric_jar("extra", "1.0.0", ""));
+    write(&mods.join("extra2.jar"), &fabric_jar("extra2", "1.0.0", ""));
+    for id in ["c", "d", "e", "f"] {
+        write(
+            &cache.join(format!("{id}-1.0.jar")),
+            &fabric_jar(id, "1.0.0", ""),
+        );
+    }
+    write(&cache.join("b.jar"), &fabric_jar("b2", "1.0.0", ""));
+
+    let path = dir.join("spec.toml");
+    write(&path, SPEC.as_bytes());
+    let spec = PackSpec::load(&path).unwrap();
+    let cache = FileCache::open(&cache, &spec.hash_formats()).unwrap();
+    (spec.reconcile(&mods, Some(&cache)).unwrap(), cache)
+}
+
+fn names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn reconcile_never_plans_two_jars_under_one_name() {
+    let dir = scratch("spec-reconcile");
+    let mods = dir.join("mods");
+    let (plan, _cache) = setup(&dir);
+
+    assert_eq!(plan.satisfied, 1);
+    assert_eq!(plan.enables, [mods.join("b.jar.disabled")]);
+    assert_eq!(plan.removals, [mods.join("extra2.jar")]);
+    let installs: Vec<(&str, &Path, bool)> = plan
+        .installs
+        .iter()
+        .map(|i| (i.id.as_str(), i.path.as_path(), i.replaces))
+        .collect();
+    assert_eq!(
+        installs,
+        [
+            // Takes the place of an extra that was to be removed
+            ("f", mods.join("extra.jar").as_path(), true),
+            ("c", mods.join("c-1.0.jar").as_path(), false),
+            ("d", mods.join("shared.jar").as_path(), false),
+        ]
+    );
+    assert_eq!(
+        plan.unresolved,
+        [
+            (
+                "e".to_string(),
+                "shared.jar is planned for another entry".to_string()
+            ),
+            (
+                "b2".to_string(),
+                "b.jar is planned for another entry".to_string()
+            ),
+            ("missing".to_string(), "not in the cache".to_string()),
+        ]
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn reconcile_without_a_cache_leaves_missing_jars_unresolved() {
+    let dir = scratch("spec-no-cache");
+    let mods = dir.join("mods");
+    write(&mods.join("a.jar"), &fabric_jar("a", "1.0.0", ""));
+    let path = dir.join("spec.toml");
+    write(
+        &path,
+        b"[[mod]]\nid = \"a\"\nversion = \"1.0\"\n\n[[mod]]\nid = \"c\"\n",
+    );
+    let plan = PackSpec::load(&path)
+        .unwrap()
+        .reconcile(&mods, None)
+        .unwrap();
+    assert_eq!(plan.satisfied, 1);
+    assert!(plan.is_empty());
+    assert_eq!(
+        plan.unresolved,
+        [("c".to_string(), "no cache to install from".to_string())]
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn applies_a_reconcile_plan() {
+    let dir = scratch("spec-apply");
+    let mods = dir.join("mods");
+    let (plan, _cache) = setup(&dir);
+    let extra = fs::read(mods.join("extra.jar")).unwrap();
+
+    let mut backup = BackupStore::new(dir.join("backups")).begin("sync").unwrap();
+    plan.apply(&mut backup).unwrap();
+    assert_eq!(
+        names(&mods),
+        ["a.jar", "b.jar", "c-1.0.jar", "extra.jar", "shared.jar"]
+    );
+    assert_eq!(
+        fs::read(mods.join("extra.jar")).unwrap(),
+        fs::read(dir.join("cache").join("f-1.0.jar")).unwrap()
+    );
+    // The overwritten and the removed extra are both kept
+    let entries = &backup.manifest.entries;
+    assert_eq!(entries.len(), 2);
+    assert_eq!(backup.read(&entries[1]).unwrap(), extra);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn failed_reconcile_restores_the_folder() {
+    let dir = scratch("spec-rollback");
+    let mods = dir.join("mods");
+    let (plan, _cache) = setup(&dir);
+    let before: Vec<(String, Vec<u8>)> = names(&mods)
+        .into_iter()
+        .map(|name| (name.clone(), fs::read(mods.join(&name)).unwrap()))
+        .collect();
+    // Something takes the last install's name after the plan was made
+    write(&mods.join("shared.jar").join("blocker"), b"");
+
+    let mut backup = BackupStore::new(dir.join("backups")).begin("sync").unwrap();
+    match plan.apply(&mut backup) {
+        Err(Error::RolledBack { target, .. }) => assert_eq!(target, "shared.jar"),
+        other => panic!("{other:?}"),
+    }
+    fs::remove_dir_all(mods.join("shared.jar")).unwrap();
+    let after: Vec<(String, Vec<u8>)> = names(&mods)
+        .into_iter()
+        .map(|name| (name.clone(), fs::read(mods.join(&name)).unwrap()))
+        .collect();
+    assert_eq!(after, before);
+    fs::remove_dir_all(dir).unwrap();
+}
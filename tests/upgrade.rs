@@ -0,0 +1,135 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use common::{fabric_jar, fabric_json, heavy, scratch, write};
+use minecraft_mod_replacer::compat::VersionChange;
+use minecraft_mod_replacer::upgrade::{self, UpgradePair};
+
+/// An installed jar with room to spare for any of the small test jars.
+fn installed(path: &Path, id: &str, version: &str) {
+    let json = fabric_json(id, version, "");
+    write(path, &heavy(&[("fabric.mod.json", json.as_bytes())]));
+}
+
+fn names(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+        .collect()
+}
+
+#[test]
+fn pairs_new_jars_with_the_installed_jars_of_their_mod() {
+    let dir = scratch("upgrade-suggest");
+    let (mods, from) = (dir.join("mods"), dir.join("downloads"));
+    installed(&mods.join("sodium-0.5.jar"), "sodium", "0.5.0");
+    installed(&mods.join("lithium-0.12.jar"), "lithium", "0.12.0");
+    installed(&mods.join("fapi.jar"), "fabric-api", "0.90.0");
+    installed(&mods.join("old.jar.disabled"), "old", "1.0.0");
+    installed(&mods.join("iris-a.jar"), "iris", "1.0.0");
+    installed(&mods.join("iris-b.jar"), "iris", "1.1.0");
+    write(&mods.join("tiny.jar"), &fabric_jar("tiny", "1.0.0", ""));
+
+    write(
+        &from.join("sodium-0.4.jar"),
+        &fabric_jar("sodium", "0.4.0", ""),
+    );
+    write(
+        &from.join("sodium-0.6.jar"),
+        &fabric_jar("sodium", "0.6.0", ""),
+    );
+    write(
+        &from.join("lithium-0.11.jar"),
+        &fabric_jar("lithium", "0.11.0", ""),
+    );
+    write(
+        &from.join("fapi-again.jar"),
+        &fabric_jar("fabric-api", "0.90.0", ""),
+    );
+    write(&from.join("old-2.0.jar"), &fabric_jar("old", "2.0.0", ""));
+    write(&from.join("iris-1.2.jar"), &fabric_jar("iris", "1.2.0", ""));
+    write(&from.join("newmod.jar"), &fabric_jar("newmod", "1.0.0", ""));
+    write(&from.join("notes.jar"), b"not a zip");
+    let big = fabric_json("tiny", "2.0.0", "");
+    write(
+        &from.join("tiny-2.0.jar"),
+        &heavy(&[("fabric.mod.json", big.as_bytes())]),
+    );
+
+    let suggestions = upgrade::suggest(&mods, &from).unwrap();
+    let pairs: Vec<(&str, String, String, VersionChange, bool)> = suggestions
+        .pairs
+        .iter()
+        .map(|p: &UpgradePair| {
+            (
+                p.mod_id.as_str(),
+                p.installed.file_name(),
+                p.update.file_name(),
+                p.change,
+                p.fits(),
+            )
+        })
+        .collect();
+    let pair = |id, installed: &str, update: &str, change| {
+        (id, installed.to_string(), update.to_string(), change, true)
+    };
+    assert_eq!(
+        pairs,
+        [
+            // A disabled jar is upgraded when there is no enabled copy
+            pair(
+                "old",
+                "old.jar.disabled",
+                "old-2.0.jar",
+                VersionChange::Upgrade
+            ),
+            pair(
+                "sodium",
+                "sodium-0.5.jar",
+                "sodium-0.6.jar",
+                VersionChange::Upgrade
+            ),
+            (
+                "tiny",
+                "tiny.jar".to_string(),
+                "tiny-2.0.jar".to_string(),
+                VersionChange::Upgrade,
+                false
+            ),
+            pair(
+                "fabric-api",
+                "fapi.jar",
+                "fapi-again.jar",
+                VersionChange::Same
+            ),
+            pair(
+                "lithium",
+                "lithium-0.12.jar",
+                "lithium-0.11.jar",
+                VersionChange::Downgrade
+            ),
+        ]
+    );
+    assert_eq!(suggestions.pairs[1].from.as_deref(), Some("0.5.0"));
+    assert_eq!(suggestions.pairs[1].to.as_deref(), Some("0.6.0"));
+    assert_eq!(names(&suggestions.unmatched), ["newmod.jar", "notes.jar"]);
+    assert_eq!(names(&suggestions.superseded), ["sodium-0.4.jar"]);
+    let [(id, paths)] = suggestions.ambiguous.as_slice() else {
+        panic!("{:?}", suggestions.ambiguous)
+    };
+    assert_eq!(id, "iris");
+    let mut paths = names(paths);
+    paths.sort();
+    assert_eq!(paths, ["iris-a.jar", "iris-b.jar"]);
+
+    let plan = upgrade::plan(&[&suggestions.pairs[1]]);
+    assert_eq!(plan.replacements.len(), 1);
+    assert_eq!(plan.replacements[0].target, "sodium-0.5.jar");
+    assert_eq!(
+        plan.replacements[0].replacement,
+        from.join("sodium-0.6.jar")
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,395 @@
+use std::cmp::Ordering;
+
+use minecraft_mod_replacer::version::{RangeSyntax, Version, VersionRange};
+
+fn fabric(s: &str) -> Version {
+    Version::parse(s, RangeSyntax::Fabric).unwrap()
+}
+
+fn maven(s: &str) -> Version {
+    Version::parse(s, RangeSyntax::Maven).unwrap()
+}
+
+/// Asserts that every version in `ordered` sorts strictly before every
+/// later one.
+fn assert_ascending(parse: fn(&str) -> Version, ordered: &[&str]) {
+    for (i, a) in ordered.iter().enumerate() {
+        for b in &ordered[i + 1..] {
+            assert_eq!(
+                parse(a).compare(&parse(b)),
+                Some(Ordering::Less),
+                "{a} < {b}"
+            );
+            assert_eq!(
+                parse(b).compare(&parse(a)),
+                Some(Ordering::Greater),
+                "{b} > {a}"
+            );
+        }
+    }
+}
+
+fn assert_equal(parse: fn(&str) -> Version, equal: &[&str]) {
+    for a in equal {
+        for b in equal {
+            assert_eq!(
+                parse(a).compare(&parse(b)),
+                Some(Ordering::Equal),
+                "{a} == {b}"
+            );
+            // `==` and the ordering operators must agree with `compare`
+            assert_eq!(parse(a), parse(b), "{a} == {b}");
+            assert_eq!(
+                parse(a).partial_cmp(&parse(b)),
+                Some(Ordering::Equal),
+                "{a} == {b}"
+            );
+        }
+    }
+}
+
+#[test]
+fn fabric_ordering() {
+    assert_ascending(
+        fabric,
+        &[
+            "0.9",
+            "0.15-",
+            "0.15.0-alpha",
+            "0.15.0-alpha.1",
+            "0.15.0-alpha.beta",
+            "0.15.0-beta",
+            "0.15.0-beta.2",
+            "0.15.0-beta.11",
+            "0.15.0-rc.1",
+            "0.15.0",
+            "0.15.1",
+            "0.15.11",
+            "1.0.0-0.3.7",
+            "1.0.0-x.7.z.92",
+            "1.0.0",
+            "1.19.4",
+            "1.20-pre1",
+            "1.20",
+            "1.20.1-2.3.4+build.7",
+            "1.20.1",
+            "1.20.10",
+            "2",
+        ],
+    );
+}
+
+#[test]
+fn fabric_equality() {
+    let groups: &[&[&str]] = &[
+        &["1", "1.0", "1.0.0"],
+        &["1.20.1", "1.20.1+build.7", "1.20.1+fabric"],
+        &["1.0.0-beta.2", "1.0.0-beta.2+build.5"],
+        &["1.0-", "1.0.0-"],
+    ];
+    for group in groups {
+        assert_equal(fabric, group);
+    }
+}
+
+#[test]
+fn trailing_zeros_are_equal_everywhere() {
+    for syntax in [RangeSyntax::Fabric, RangeSyntax::Maven] {
+        let one = Version::parse("1", syntax).unwrap();
+        let one_zero = Version::parse("1.0", syntax).unwrap();
+        assert_eq!(one, one_zero);
+        assert!(one <= one_zero);
+        assert!(one >= one_zero);
+        assert_eq!(one.compare(&one_zero), Some(Ordering::Equal));
+    }
+    assert_eq!(
+        VersionRange::parse("1.0", RangeSyntax::Fabric),
+        VersionRange::parse("1", RangeSyntax::Fabric)
+    );
+    assert!(
+        VersionRange::parse("=1", RangeSyntax::Fabric)
+            .unwrap()
+            .contains(&fabric("1.0"))
+    );
+}
+
+#[test]
+fn fabric_non_semantic_versions_only_compare_for_equality() {
+    let cases = [
+        ("v1.0", "v1.0", Some(Ordering::Equal)),
+        ("v1.0", "v1.1", None),
+        ("v1.0", "1.0", None),
+        ("1.0_beta", "1.0", None),
+        ("1.0.", "1.0", None),
+    ];
+    for (a, b, expected) in cases {
+        assert_eq!(fabric(a).compare(&fabric(b)), expected, "{a} vs {b}");
+    }
+}
+
+#[test]
+fn fabric_predicates() {
+    let cases: &[(&str, &[&str], &[&str])] = &[
+        ("*", &["0.1", "1.20.1", "v-odd", "2.0.0-beta"], &[]),
+        (
+            "1.20.1",
+            &["1.20.1", "1.20.1+build.3", "1.20.1.0"],
+            &["1.20", "1.20.2", "1.20.1-rc1"],
+        ),
+        ("=1.20.1", &["1.20.1"], &["1.20.2"]),
+        (
+            ">=1.20",
+            &["1.20", "1.20.1", "2.0"],
+            &["1.19.4", "1.20-pre1"],
+        ),
+        (">1.20", &["1.20.1", "1.21"], &["1.20", "1.20.0"]),
+        ("<=1.20", &["1.20", "1.19", "1.20-rc1"], &["1.20.1"]),
+        ("<1.20", &["1.19.4", "1.20-rc1"], &["1.20", "1.20.1"]),
+        (
+            ">=0.15- <0.16",
+            &["0.15-alpha", "0.15.0", "0.15.11", "0.16-beta"],
+            &["0.14.25", "0.16", "0.16.0"],
+        ),
+        (
+            ">=0.15- <0.16-",
+            &["0.15.0-alpha", "0.15.11"],
+            &["0.16-beta", "0.16"],
+        ),
+        (
+            "~1.20.1",
+            &["1.20.1", "1.20.6"],
+            &["1.20", "1.21", "1.21-pre1"],
+        ),
+        (
+            "~1.20",
+            &["1.20", "1.20.6"],
+            &["1.19.4", "1.21", "1.21-rc1"],
+        ),
+        (
+            "^1.2.3",
+            &["1.2.3", "1.9.0"],
+            &["1.2.2", "2.0.0", "2.0.0-alpha"],
+        ),
+        ("^0.5", &["0.5", "0.9.1"], &["0.4", "1.0"]),
+        (
+            "1.20.x",
+            &["1.20", "1.20.4", "1.20.4-rc1", "1.20-pre2"],
+            &["1.19.4", "1.21", "1.21-pre1"],
+        ),
+        ("1.X", &["1.0", "1.20.1"], &["0.9", "2.0"]),
+        ("1.x.x", &["1.2.3"], &["2.0"]),
+        ("x", &["5.0"], &[]),
+        (
+            "1.19.x || 1.20.x",
+            &["1.19.2", "1.20.1"],
+            &["1.18.2", "1.21"],
+        ),
+        (
+            ">=1.19 <1.20 || >=1.20.2",
+            &["1.19.3", "1.20.4"],
+            &["1.20", "1.20.1"],
+        ),
+        ("=v1.0", &["v1.0"], &["v1.1", "1.0"]),
+        (">=1.0", &[], &["v1.0", "1.0_beta"]),
+    ];
+    for (predicate, inside, outside) in cases {
+        let range = VersionRange::parse(predicate, RangeSyntax::Fabric)
+            .unwrap_or_else(|| panic!("{predicate} parses"));
+        for v in *inside {
+            assert!(range.contains(&fabric(v)), "{v} satisfies {predicate}");
+        }
+        for v in *outside {
+            assert!(
+                !range.contains(&fabric(v)),
+                "{v} does not satisfy {predicate}"
+            );
+        }
+    }
+}
+
+#[test]
+fn fabric_invalid_predicates() {
+    for predicate in [">=", "~v1", "^", "1.20-rc.x", "~1.x"] {
+        assert_eq!(
+            VersionRange::parse(predicate, RangeSyntax::Fabric),
+            None,
+            "{predicate} is rejected"
+        );
+    }
+}
+
+/// The orderings from Maven's own `ComparableVersionTest`.
+#[test]
+fn maven_qualifier_ordering() {
+    assert_ascending(
+        maven,
+        &[
+            "1-alpha2snapshot",
+            "1-alpha2",
+            "1-alpha-123",
+            "1-beta-2",
+            "1-beta123",
+            "1-m2",
+            "1-m11",
+            "1-rc",
+            "1-cr2",
+            "1-rc123",
+            "1-SNAPSHOT",
+            "1",
+            "1-sp",
+            "1-sp2",
+            "1-sp123",
+            "1-abc",
+            "1-def",
+            "1-pom-1",
+            "1-1-snapshot",
+            "1-1",
+            "1-2",
+            "1-123",
+        ],
+    );
+}
+
+#[test]
+fn maven_number_ordering() {
+    assert_ascending(
+        maven,
+        &[
+            "2.0", "2-1", "2.0.a", "2.0.0.a", "2.0.2", "2.0.123", "2.1.0", "2.1-a", "2.1b",
+            "2.1-c", "2.1-1", "2.1.0.1", "2.2", "2.123", "11.a2", "11.a11", "11.b2", "11.b11",
+            "11.m2", "11.m11", "11", "11.a", "11b", "11c", "11m",
+        ],
+    );
+}
+
+#[test]
+fn maven_equality() {
+    let groups: &[&[&str]] = &[
+        &["1", "1.0", "1.0.0", "1-0", "1.0-0"],
+        &["1a", "1-a", "1.0-a", "1.0.0-a", "1.0.0.0.0.0-a", "1.0a"],
+        &["1a1", "1-a1", "1alpha1", "1-alpha-1", "1ALPHA1"],
+        &["1b2", "1-b2", "1-beta-2", "1beta2"],
+        &["1m3", "1-m3", "1-milestone-3"],
+        &["1rc", "1cr", "1-rc", "1-cr"],
+        &["1-ga", "1", "1-final", "1-release", "1.0.0-GA"],
+        &["1-SNAPSHOT", "1-snapshot"],
+        &["1x", "1.0.0-x", "1-x"],
+        &["2.0", "2.0.0", "2"],
+    ];
+    for group in groups {
+        assert_equal(maven, group);
+    }
+}
+
+#[test]
+fn maven_large_and_padded_numbers() {
+    assert_equal(maven, &["1.007", "1.7"]);
+    assert_ascending(
+        maven,
+        &[
+            "1.9",
+            "1.10",
+            "1.99999999999999999999",
+            "1.100000000000000000000",
+        ],
+    );
+}
+
+#[test]
+fn forge_style_versions() {
+    // Anything after the Minecraft version makes it a later version, and
+    // a number there beats a word
+    assert_ascending(
+        maven,
+        &[
+            "1.19.2-1.0.0",
+            "1.20.1",
+            "1.20.1-forge-2.3.4",
+            "1.20.1-2.3.4",
+            "1.20.1-2.3.4.5",
+            "1.20.1-2.3.10",
+            "47.1",
+            "47.1.3",
+            "47.2.0",
+        ],
+    );
+}
+
+#[test]
+fn maven_ranges() {
+    let cases: &[(&str, &[&str], &[&str])] = &[
+        (
+            "[47.1,)",
+            &["47.1", "47.1.0", "47.2.0", "48"],
+            &["47.0.35", "47.1-beta"],
+        ),
+        ("(47.1,)", &["47.1.1", "48"], &["47.1", "47.1.0"]),
+        (
+            "[1.20.1,1.21)",
+            &["1.20.1", "1.20.6"],
+            &["1.20", "1.21", "1.21.0"],
+        ),
+        ("[1.20.1,1.21]", &["1.21", "1.21.0"], &["1.21.1"]),
+        ("(,1.20.1]", &["1.19", "1.20.1"], &["1.20.2"]),
+        ("(,1.20.1)", &["1.20"], &["1.20.1"]),
+        ("[1.20.1]", &["1.20.1", "1.20.1.0"], &["1.20.2", "1.20"]),
+        (
+            "[1.18,1.19),[1.20,)",
+            &["1.18.2", "1.20.4"],
+            &["1.19", "1.19.4"],
+        ),
+        ("[1.18,1.19), [1.20,)", &["1.18.2"], &["1.19.2"]),
+        ("1.20.1", &["1.0", "1.20.1", "2.0"], &[]),
+        ("*", &["0.1"], &[]),
+        ("", &["0.1"], &[]),
+    ];
+    for (spec, inside, outside) in cases {
+        let range = VersionRange::parse(spec, RangeSyntax::Maven)
+            .unwrap_or_else(|| panic!("{spec} parses"));
+        for v in *inside {
+            assert!(range.contains(&maven(v)), "{v} is in {spec}");
+        }
+        for v in *outside {
+            assert!(!range.contains(&maven(v)), "{v} is not in {spec}");
+        }
+    }
+}
+
+#[test]
+fn maven_invalid_ranges() {
+    for spec in ["[1.0", "(1.0)", "[1.0,2.0)x", "[1.0,2.0),junk"] {
+        assert_eq!(
+            VersionRange::parse(spec, RangeSyntax::Maven),
+            None,
+            "{spec} is rejected"
+        );
+    }
+}
+
+#[test]
+fn range_overlap() {
+    let cases = [
+        (RangeSyntax::Fabric, "1.20.x", ">=1.20.4", true),
+        (RangeSyntax::Fabric, "1.20.x", "1.21.x", false),
+        (RangeSyntax::Fabric, "<1.20", ">=1.20", false),
+        (RangeSyntax::Fabric, "<=1.20", ">=1.20", true),
+        (RangeSyntax::Fabric, "~1.20.1", "1.20.6", true),
+        (RangeSyntax::Fabric, "1.19.x || 1.21.x", "1.20.x", false),
+        (RangeSyntax::Maven, "[1.20,1.21)", "[1.20.4,)", true),
+        (RangeSyntax::Maven, "[1.20,1.21)", "[1.21,)", false),
+        (RangeSyntax::Maven, "[1.20,1.21]", "[1.21,)", true),
+        (RangeSyntax::Maven, "[1.20.1]", "[1.20.1.0]", true),
+    ];
+    for (syntax, a, b, expected) in cases {
+        let a_range = VersionRange::parse(a, syntax).unwrap();
+        let b_range = VersionRange::parse(b, syntax).unwrap();
+        assert_eq!(a_range.overlaps(&b_range), expected, "{a} overlaps {b}");
+        assert_eq!(b_range.overlaps(&a_range), expected, "{b} overlaps {a}");
+    }
+}
+
+#[test]
+fn schemes_do_not_mix() {
+    assert_eq!(fabric("1.0").compare(&maven("1.0")), None);
+    assert_eq!(maven("1.0").compare(&fabric("1.0")), None);
+}
@@ -0,0 +1,523 @@
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+use minecraft_mod_replacer::zip::{self, Archive, ZipError, ZipWriter};
+
+const CENTRAL_LEN: usize = 46;
+const EOCD_LEN: usize = 22;
+
+/// A small archive with a deflated and a stored entry.
+fn sample() -> Vec<u8> {
+    let mut writer = ZipWriter::new();
+    writer
+        .add_file(
+            "fabric.mod.json",
+            "{\"id\": \"demo\"}\n".repeat(20).as_bytes(),
+        )
+        .unwrap();
+    writer.add_file("a.bin", &[1, 2, 3]).unwrap();
+    writer.finish().unwrap()
+}
+
+fn put_u16(data: &mut [u8], pos: usize, value: u16) {
+    data[pos..pos + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(data: &mut [u8], pos: usize, value: u32) {
+    data[pos..pos + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Offsets of the records a test corrupts.
+#[derive(Clone, Copy)]
+struct Layout {
+    eocd: usize,
+    central: usize,
+    /// The second central directory entry.
+    second_central: usize,
+}
+
+fn layout(data: &[u8]) -> Layout {
+    let archive = Archive::parse(data).unwrap();
+    let first = &archive.entries[0];
+    Layout {
+        eocd: archive.eocd.offset,
+        central: archive.eocd.cd_offset as usize,
+        second_central: archive.eocd.cd_offset as usize + CENTRAL_LEN + first.raw_name.len(),
+    }
+}
+
+/// Appends `comment` after the EOCD record and declares it.
+fn with_comment(mut data: Vec<u8>, comment: &[u8]) -> Vec<u8> {
+    let eocd = data.len() - EOCD_LEN;
+    put_u16(&mut data, eocd + 20, comment.len() as u16);
+    data.extend_from_slice(comment);
+    data
+}
+
+#[test]
+fn reads_entries_through_the_central_directory() {
+    let data = sample();
+    let archive = Archive::parse(&data).unwrap();
+    let names: Vec<&str> = archive.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["fabric.mod.json", "a.bin"]);
+    assert_eq!(
+        archive.read_file("fabric.mod.json").unwrap().unwrap(),
+        "{\"id\": \"demo\"}\n".repeat(20).as_bytes()
+    );
+    assert_eq!(archive.read_file("a.bin").unwrap().unwrap(), [1, 2, 3]);
+    assert_eq!(archive.read_file("missing").unwrap(), None);
+    assert!(!archive.is_zip64());
+}
+
+#[test]
+fn finds_the_eocd_before_a_trailing_comment() {
+    let fake_eocd = [0x50, 0x4b, 0x05, 0x06, 0, 0, 0, 0, 9, 9, 9, 9];
+    let cases: &[(&str, Vec<u8>)] = &[
+        ("empty comment", Vec::new()),
+        ("text comment", b"built by hand".to_vec()),
+        ("comment holding an EOCD signature", fake_eocd.to_vec()),
+        (
+            "EOCD signature at the very end",
+            [&[0u8; 30][..], &fake_eocd[..4]].concat(),
+        ),
+        ("maximum comment", vec![b'#'; 65535]),
+    ];
+    for (label, comment) in cases {
+        let plain = sample();
+        let data = with_comment(plain.clone(), comment);
+        let archive = Archive::parse(&data).unwrap_or_else(|err| panic!("{label}: {err}"));
+        assert_eq!(archive.eocd.offset, plain.len() - EOCD_LEN, "{label}");
+        assert_eq!(
+            usize::from(archive.eocd.comment_len),
+            comment.len(),
+            "{label}"
+        );
+        assert_eq!(archive.trailing_len(), comment.len(), "{label}");
+        assert_eq!(archive.entries.len(), 2, "{label}");
+    }
+}
+
+#[test]
+fn counts_bytes_appended_after_the_comment() {
+    let mut data = with_comment(sample(), b"note");
+    data.extend([0u8; 100]);
+    let archive = Archive::parse(&data).unwrap();
+    assert_eq!(archive.eocd.comment_len, 4);
+    assert_eq!(archive.trailing_len(), 104);
+}
+
+#[test]
+fn rejects_damaged_archives() {
+    type Corrupt = fn(&mut Vec<u8>, Layout);
+    type Expect = fn(&ZipError) -> bool;
+    let cases: &[(&str, Corrupt, Expect)] = &[
+        (
+            "no EOCD record",
+            |data, l| data.truncate(l.eocd),
+            |err| matches!(err, ZipError::EocdNotFound(_)),
+        ),
+        (
+            "too short for any record",
+            |data, _| data.truncate(10),
+            |err| matches!(err, ZipError::EocdNotFound(10)),
+        ),
+        (
+            "comment longer than the file",
+            |data, l| put_u16(data, l.eocd + 20, 50),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "central directory cut short",
+            |data, l| {
+                let eocd = data.split_off(l.eocd);
+                data.truncate(l.second_central + 10);
+                let cd_size = (data.len() - l.central) as u32;
+                data.extend(eocd);
+                let eocd = data.len() - EOCD_LEN;
+                put_u32(data, eocd + 12, cd_size);
+            },
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "central directory size past the EOCD",
+            |data, l| put_u32(data, l.eocd + 12, 10_000),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "central directory offset past the end",
+            |data, l| put_u32(data, l.eocd + 16, u32::MAX - 8),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "central directory offset inside the data",
+            |data, l| put_u32(data, l.eocd + 16, 4),
+            |err| matches!(err, ZipError::BadSignature { .. }),
+        ),
+        (
+            "fewer entries than declared",
+            |data, l| {
+                put_u16(data, l.eocd + 8, 3);
+                put_u16(data, l.eocd + 10, 3);
+            },
+            |err| {
+                matches!(
+                    err,
+                    ZipError::EntryCountMismatch {
+                        declared: 3,
+                        found: 2
+                    }
+                )
+            },
+        ),
+        (
+            "more entries than declared",
+            |data, l| {
+                put_u16(data, l.eocd + 8, 1);
+                put_u16(data, l.eocd + 10, 1);
+            },
+            |err| matches!(err, ZipError::EntryCountMismatch { declared: 1, .. }),
+        ),
+        (
+            "central name runs past the directory",
+            |data, l| put_u16(data, l.second_central + 28, 60_000),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "central extra field runs past the directory",
+            |data, l| put_u16(data, l.second_central + 30, 60_000),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "central comment runs past the directory",
+            |data, l| put_u16(data, l.second_central + 32, 60_000),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "local header offset past the end",
+            |data, l| put_u32(data, l.central + 42, 0x00ff_0000),
+            |err| matches!(err, ZipError::BadSignature { .. }),
+        ),
+        (
+            "local header offset into the middle of an entry",
+            |data, l| put_u32(data, l.central + 42, 7),
+            |err| matches!(err, ZipError::BadSignature { .. }),
+        ),
+        (
+            "local name differs",
+            |data, _| data[30] = b'F',
+            |err| matches!(err, ZipError::LocalHeaderMismatch { field: "name", .. }),
+        ),
+        (
+            "local name runs past the end",
+            |data, _| put_u16(data, 26, 60_000),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "local extra field runs past the end",
+            |data, _| put_u16(data, 28, 60_000),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "compressed size past the end",
+            |data, l| put_u32(data, l.central + 20, 1_000_000),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+    ];
+
+    for (label, corrupt, expect) in cases {
+        let mut data = sample();
+        let l = layout(&data);
+        corrupt(&mut data, l);
+        match Archive::parse(&data) {
+            Ok(_) => panic!("{label}: parsed"),
+            Err(err) => assert!(expect(&err), "{label}: {err:?}"),
+        }
+    }
+}
+
+/// Offsets inside an archive built by [`zip64_sample`].
+struct Zip64Layout {
+    central: usize,
+    record: usize,
+    locator: usize,
+}
+
+/// A ZIP64 archive of stored entries, as large-jar tools write them. With
+/// `saturate_sizes` the sizes move into the ZIP64 extra fields too,
+/// otherwise only the local header offset does.
+fn zip64_sample(entries: &[(&str, &[u8])], saturate_sizes: bool) -> (Vec<u8>, Zip64Layout) {
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, body) in entries {
+        let offset = out.len() as u64;
+        let size = body.len() as u64;
+        let crc = crc32fast::hash(body);
+        let header_size = if saturate_sizes {
+            u32::MAX
+        } else {
+            size as u32
+        };
+
+        out.extend(0x0403_4b50u32.to_le_bytes());
+        out.extend([45, 0, 0, 0, 0, 0, 0, 0, 0x21, 0]);
+        out.extend(crc.to_le_bytes());
+        out.extend(header_size.to_le_bytes());
+        out.extend(header_size.to_le_bytes());
+        out.extend((name.len() as u16).to_le_bytes());
+        let local_extra: Vec<u64> = if saturate_sizes {
+            vec![size, size]
+        } else {
+            Vec::new()
+        };
+        out.extend(extra_len(&local_extra).to_le_bytes());
+        out.extend(name.as_bytes());
+        out.extend(zip64_extra(&local_extra));
+        out.extend(*body);
+
+        let mut fields = Vec::new();
+        if saturate_sizes {
+            fields.extend([size, size]);
+        }
+        fields.push(offset);
+        central.extend(0x0201_4b50u32.to_le_bytes());
+        central.extend([45, 0, 45, 0, 0, 0, 0, 0, 0, 0, 0x21, 0]);
+        central.extend(crc.to_le_bytes());
+        central.extend(header_size.to_le_bytes());
+        central.extend(header_size.to_le_bytes());
+        central.extend((name.len() as u16).to_le_bytes());
+        central.extend(extra_len(&fields).to_le_bytes());
+        central.extend([0u8; 2 + 2 + 2 + 4]);
+        central.extend(u32::MAX.to_le_bytes());
+        central.extend(name.as_bytes());
+        central.extend(zip64_extra(&fields));
+    }
+
+    let layout = Zip64Layout {
+        central: out.len(),
+        record: out.len() + central.len(),
+        locator: out.len() + central.len() + 56,
+    };
+    let count = entries.len() as u64;
+    out.extend(&central);
+
+    out.extend(0x0606_4b50u32.to_le_bytes());
+    out.extend(44u64.to_le_bytes());
+    out.extend([45, 0, 45, 0]);
+    out.extend([0u8; 8]);
+    out.extend(count.to_le_bytes());
+    out.extend(count.to_le_bytes());
+    out.extend((central.len() as u64).to_le_bytes());
+    out.extend((layout.central as u64).to_le_bytes());
+
+    out.extend(0x0706_4b50u32.to_le_bytes());
+    out.extend(0u32.to_le_bytes());
+    out.extend((layout.record as u64).to_le_bytes());
+    out.extend(1u32.to_le_bytes());
+
+    out.extend(0x0605_4b50u32.to_le_bytes());
+    out.extend([0u8; 4]);
+    out.extend([0xff; 4]);
+    out.extend(u32::MAX.to_le_bytes());
+    out.extend(u32::MAX.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+    (out, layout)
+}
+
+fn extra_len(fields: &[u64]) -> u16 {
+    if fields.is_empty() {
+        0
+    } else {
+        4 + 8 * fields.len() as u16
+    }
+}
+
+fn zip64_extra(fields: &[u64]) -> Vec<u8> {
+    if fields.is_empty() {
+        return Vec::new();
+    }
+    let mut extra = Vec::new();
+    extra.extend(1u16.to_le_bytes());
+    extra.extend((8 * fields.len() as u16).to_le_bytes());
+    for field in fields {
+        extra.extend(field.to_le_bytes());
+    }
+    extra
+}
+
+fn put_u64(data: &mut [u8], pos: usize, value: u64) {
+    data[pos..pos + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+const ZIP64_ENTRIES: &[(&str, &[u8])] = &[
+    ("fabric.mod.json", b"{\"id\": \"big\"}"),
+    ("assets/big/lang/en_us.json", b"{}"),
+];
+
+#[test]
+fn reads_zip64_records_and_extra_fields() {
+    for saturate_sizes in [true, false] {
+        let (data, layout) = zip64_sample(ZIP64_ENTRIES, saturate_sizes);
+        let archive = Archive::parse(&data).unwrap();
+        assert!(archive.is_zip64());
+        let zip64 = archive.eocd.zip64.unwrap();
+        assert_eq!(zip64.record_offset, layout.record as u64);
+        assert_eq!(zip64.locator_offset, layout.locator as u64);
+        assert_eq!(archive.eocd.entries, 2);
+        assert_eq!(archive.eocd.cd_offset, layout.central as u64);
+
+        for (entry, (name, body)) in archive.entries.iter().zip(ZIP64_ENTRIES) {
+            assert_eq!(entry.name, *name);
+            assert_eq!(entry.compressed_size, body.len() as u64);
+            assert_eq!(entry.uncompressed_size, body.len() as u64);
+            assert_eq!(entry.local.compressed_size, body.len() as u64);
+            assert_eq!(entry.local.uncompressed_size, body.len() as u64);
+            assert_eq!(archive.read(entry).unwrap(), *body);
+        }
+        assert_eq!(
+            archive.entries[1].local_header_offset,
+            30 + 15 + 13 + 20 * u64::from(saturate_sizes)
+        );
+        assert!(
+            zip::verify(&data).unwrap().is_ok(),
+            "saturated sizes: {saturate_sizes}"
+        );
+    }
+}
+
+#[test]
+fn zip64_archives_pad_through_the_comment_only() {
+    let (data, _) = zip64_sample(ZIP64_ENTRIES, true);
+    let padded = zip::pad_zip_file(data.clone(), 500).unwrap();
+    assert_eq!(padded.len(), data.len() + 500);
+    assert!(zip::verify(&padded).unwrap().is_ok());
+    assert!(matches!(
+        zip::append_padding(data, 500),
+        Err(ZipError::Zip64Append)
+    ));
+}
+
+#[test]
+fn rejects_damaged_zip64_archives() {
+    type Corrupt = fn(&mut Vec<u8>, &Zip64Layout);
+    type Expect = fn(&ZipError) -> bool;
+    let cases: &[(&str, Corrupt, Expect)] = &[
+        (
+            "record offset that overflows",
+            |data, l| put_u64(data, l.locator + 8, u64::MAX - 10),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "record overlapping the locator",
+            |data, l| put_u64(data, l.locator + 8, l.locator as u64 - 20),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "record offset without a record",
+            |data, l| put_u64(data, l.locator + 8, l.central as u64),
+            |err| matches!(err, ZipError::BadSignature { .. }),
+        ),
+        (
+            "locator cut short",
+            |data, l| {
+                data.drain(l.locator + 4..l.locator + 12);
+            },
+            |err| matches!(err, ZipError::EocdNotFound(_) | ZipError::Truncated(_)),
+        ),
+        (
+            "central directory size that overflows",
+            |data, l| put_u64(data, l.record + 40, u64::MAX),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "central directory past the record",
+            |data, l| put_u64(data, l.record + 48, l.record as u64),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "entry count that does not match",
+            |data, l| put_u64(data, l.record + 32, 7),
+            |err| matches!(err, ZipError::EntryCountMismatch { declared: 7, .. }),
+        ),
+        (
+            "extra field missing the offset",
+            |data, l| put_u16(data, l.central + 30, 20),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "extra field body past its end",
+            |data, l| put_u16(data, l.central + CENTRAL_LEN + 15 + 2, 0xfff0),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+        (
+            "local header offset at the top of the range",
+            |data, l| put_u64(data, l.central + CENTRAL_LEN + 15 + 20, u64::MAX - 1),
+            |err| matches!(err, ZipError::BadSignature { .. }),
+        ),
+        (
+            "compressed size that overflows the data offset",
+            |data, l| put_u64(data, l.central + CENTRAL_LEN + 15 + 12, u64::MAX - 4),
+            |err| matches!(err, ZipError::Truncated(_)),
+        ),
+    ];
+
+    for (label, corrupt, expect) in cases {
+        let (mut data, layout) = zip64_sample(ZIP64_ENTRIES, true);
+        corrupt(&mut data, &layout);
+        match Archive::parse(&data) {
+            Ok(_) => panic!("{label}: parsed"),
+            Err(err) => assert!(expect(&err), "{label}: {err:?}"),
+        }
+    }
+}
+
+/// A ZIP64 archive with one deflated `fabric.mod.json` whose declared
+/// uncompressed size is `declared`.
+fn lying_sample(content: &[u8], declared: u64) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content).unwrap();
+    let deflated = encoder.finish().unwrap();
+    let (mut data, layout) = zip64_sample(&[("fabric.mod.json", &deflated)], true);
+
+    let name_len = "fabric.mod.json".len();
+    put_u16(&mut data, 8, 8);
+    put_u16(&mut data, layout.central + 10, 8);
+    put_u32(&mut data, 14, crc32fast::hash(content));
+    put_u32(&mut data, layout.central + 16, crc32fast::hash(content));
+    put_u64(&mut data, 30 + name_len + 4, declared);
+    put_u64(
+        &mut data,
+        layout.central + CENTRAL_LEN + name_len + 4,
+        declared,
+    );
+    data
+}
+
+#[test]
+fn declared_sizes_do_not_drive_allocation() {
+    let content = b"{\"schemaVersion\": 1, \"id\": \"liar\", \"version\": \"1.0\"}";
+    let actual = content.len() as u64;
+
+    let honest = lying_sample(content, actual);
+    let archive = Archive::parse(&honest).unwrap();
+    assert_eq!(archive.read(&archive.entries[0]).unwrap(), content);
+
+    for declared in [0x7fff_ffff_ffff_ff00, u64::MAX, actual + 1, actual - 1, 0] {
+        let data = lying_sample(content, declared);
+        let archive = Archive::parse(&data).unwrap();
+        match archive.read(&archive.entries[0]) {
+            Err(ZipError::SizeMismatch {
+                expected,
+                actual: got,
+                ..
+            }) => {
+                assert_eq!(expected, declared);
+                // Inflating stops one byte past the declared size
+                assert_eq!(got, actual.min(declared.saturating_add(1)));
+            }
+            other => panic!("declared {declared}: {other:?}"),
+        }
+        assert!(!zip::verify(&data).unwrap().is_ok(), "declared {declared}");
+        assert!(minecraft_mod_replacer::metadata::read_bytes(&data).is_err());
+    }
+}